@@ -1,26 +1,38 @@
-use std::{env::current_dir, fs};
+//! Print the events of every calendar in a file, in chronological order.
+//!
+//! Usage: `cargo run --example from_file [path]`, where `path` defaults to `calendar.txt`.
+use std::{env, fs};
 
 use anyhow::Context;
 
 pub fn main() -> anyhow::Result<()> {
-    let raw = fs::read_to_string("calendar.txt").with_context(|| {
+    let path = env::args().nth(1).unwrap_or_else(|| "calendar.txt".into());
+    let raw = fs::read_to_string(&path).with_context(|| {
         format!(
             "cannot open {}",
-            current_dir().unwrap().join("calendar.txt").display(),
+            env::current_dir().unwrap_or_default().join(&path).display(),
         )
     })?;
-    let calendar = &mut icalendar::parse(&raw)?[0];
+    let calendars = icalendar::parse(&raw).with_context(|| format!("cannot parse {path}"))?;
+    if calendars.is_empty() {
+        println!("no calendars found in {path}");
+    }
 
-    calendar
-        .events
-        .sort_by(|ev1, ev2| ev1.start.unwrap().cmp(&ev2.start.unwrap()));
-    dbg!(calendar);
-    /*
-    for event in &calendar[0].events {
-        if !event.attachments.is_empty() {
-            println!("{event:?}");
+    for mut calendar in calendars {
+        calendar.sort_events();
+        println!("{} ({} events)", calendar.prod_id, calendar.events.len());
+        for event in &calendar.events {
+            let start = match &event.start {
+                Some(start) => format!("{start:?}"),
+                None => "(no start)".into(),
+            };
+            let summary = event
+                .summary
+                .as_ref()
+                .map(|summary| &*summary.text)
+                .unwrap_or(&event.uid);
+            println!("  {start:<20} {summary}");
         }
     }
-    */
     Ok(())
 }
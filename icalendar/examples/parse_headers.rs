@@ -0,0 +1,71 @@
+//! Compare the time taken by `parse_headers` and a full `parse` on an attachment-heavy calendar.
+//!
+//! Usage: `cargo run --release --example parse_headers [events]`, where `events` defaults to
+//! 2000.
+use std::{env, fmt::Write, hint::black_box, time::Instant};
+
+use anyhow::Context;
+
+pub fn main() -> anyhow::Result<()> {
+    let count: usize = match env::args().nth(1) {
+        Some(count) => count.parse().context("expected a number of events")?,
+        None => 2000,
+    };
+    let input = calendar(count);
+    println!("{count} events, {} MiB", input.len() / (1024 * 1024));
+
+    let start = Instant::now();
+    let headers = black_box(icalendar::parse_headers(&input)?);
+    println!("parse_headers: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let calendars = black_box(icalendar::parse(&input)?);
+    println!("parse:         {:?}", start.elapsed());
+
+    assert_eq!(headers.len(), calendars[0].events.len());
+    Ok(())
+}
+
+/// A calendar where every event has a 12 KiB attachment, 20 attendees and some comments
+fn calendar(count: usize) -> String {
+    let blob = "QUJD".repeat(3 * 1024);
+    let mut output = String::from(
+        "BEGIN:VCALENDAR\r\n\
+        PRODID:-//Example Corp.//Benchmark//EN\r\n\
+        VERSION:2.0\r\n",
+    );
+    for idx in 0..count {
+        write!(
+            output,
+            "BEGIN:VEVENT\r\n\
+            UID:event-{idx}@example.com\r\n\
+            DTSTAMP:20240101T000000Z\r\n\
+            DTSTART:20240102T090000Z\r\n\
+            SUMMARY:Event {idx}\r\n\
+            ORGANIZER;CN=Organizer:mailto:organizer@example.com\r\n"
+        )
+        .unwrap();
+        for attendee in 0..20 {
+            write!(
+                output,
+                "ATTENDEE;ROLE=REQ-PARTICIPANT;PARTSTAT=ACCEPTED;CN=Attendee {attendee}:\
+                mailto:attendee-{attendee}@example.com\r\n"
+            )
+            .unwrap();
+        }
+        for comment in 0..5 {
+            write!(output, "COMMENT:Comment {comment}\\, with escapes\r\n").unwrap();
+        }
+        // long lines are folded
+        output.push_str("ATTACH;FMTTYPE=application/octet-stream;ENCODING=BASE64;VALUE=BINARY:");
+        for (idx, chunk) in blob.as_bytes().chunks(74).enumerate() {
+            if idx > 0 {
+                output.push_str("\r\n ");
+            }
+            output.push_str(std::str::from_utf8(chunk).unwrap());
+        }
+        output.push_str("\r\nEND:VEVENT\r\n");
+    }
+    output.push_str("END:VCALENDAR\r\n");
+    output
+}
@@ -0,0 +1,97 @@
+//! Counting the replies of an event's attendees
+
+use crate::{
+    Event,
+    params::{ParticipationStatus, RsvpExpectation},
+};
+
+/// How many of an event's `ATTENDEE`s have each `PARTSTAT`, see
+/// [`Event::attendance_summary`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttendanceSummary {
+    pub accepted: usize,
+    pub declined: usize,
+    pub tentative: usize,
+    pub needs_action: usize,
+    pub delegated: usize,
+    /// Non-standard statuses, and those only meaningful for `VTODO`s (`COMPLETED` and
+    /// `IN-PROCESS`)
+    pub other: usize,
+    /// Attendees still in `NEEDS-ACTION` who were asked to reply (`RSVP=TRUE`). These are
+    /// also counted in `needs_action`.
+    pub awaiting_reply: usize,
+}
+
+impl AttendanceSummary {
+    /// The number of attendees counted
+    pub fn total(&self) -> usize {
+        self.accepted
+            + self.declined
+            + self.tentative
+            + self.needs_action
+            + self.delegated
+            + self.other
+    }
+}
+
+impl Event<'_> {
+    /// Count the attendees of this event by their participation status.
+    pub fn attendance_summary(&self) -> AttendanceSummary {
+        let mut summary = AttendanceSummary::default();
+        for attendee in &self.attendees {
+            match attendee.participation_status {
+                ParticipationStatus::Accepted => summary.accepted += 1,
+                ParticipationStatus::Declined => summary.declined += 1,
+                ParticipationStatus::Tentative => summary.tentative += 1,
+                ParticipationStatus::NeedsAction => {
+                    summary.needs_action += 1;
+                    if attendee.rsvp == RsvpExpectation::True {
+                        summary.awaiting_reply += 1;
+                    }
+                }
+                ParticipationStatus::Delegated => summary.delegated += 1,
+                ParticipationStatus::Completed
+                | ParticipationStatus::InProcess
+                | ParticipationStatus::Name(_) => summary.other += 1,
+            }
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AttendanceSummary;
+    use crate::test_util;
+
+    #[test]
+    fn attendance_summary() {
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:1\r\n\
+            ATTENDEE;PARTSTAT=ACCEPTED:mailto:a@example.com\r\n\
+            ATTENDEE;PARTSTAT=ACCEPTED;RSVP=TRUE:mailto:b@example.com\r\n\
+            ATTENDEE;PARTSTAT=DECLINED:mailto:c@example.com\r\n\
+            ATTENDEE;PARTSTAT=NEEDS-ACTION;RSVP=TRUE:mailto:d@example.com\r\n\
+            ATTENDEE:mailto:e@example.com\r\n\
+            ATTENDEE;PARTSTAT=X-MAYBE:mailto:f@example.com\r\n\
+            END:VEVENT\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let summary = calendar.events[0].attendance_summary();
+        assert_eq!(
+            summary,
+            AttendanceSummary {
+                accepted: 2,
+                declined: 1,
+                tentative: 0,
+                needs_action: 2,
+                delegated: 0,
+                other: 1,
+                awaiting_reply: 1,
+            }
+        );
+        assert_eq!(summary.total(), 6);
+    }
+}
@@ -0,0 +1,151 @@
+//! Working out when the events of a calendar take up time
+
+use crate::{
+    Calendar, Event, TimeTransparency, TimeZoneError,
+    types::{DateTime, Period},
+};
+
+impl Calendar<'_> {
+    /// The busy times from `start` to `end`, like the `FREEBUSY` of a `VFREEBUSY`.
+    ///
+    /// Recurring events are expanded into their instances (see
+    /// [`EventSeries::instance_events`](crate::EventSeries::instance_events)) and transparent
+    /// events skipped (see [`Event::effective_transparency`]). The times of the other events
+    /// are clipped to the window, and those that overlap or touch are merged. The output is
+    /// in chronological order, with each period an [`Explicit`](Period::Explicit) UTC start
+    /// and end.
+    ///
+    /// Times are converted to UTC as for [`utc_events`](Self::utc_events).
+    ///
+    /// Errors if an event refers to a `TZID` that isn't defined.
+    pub fn free_busy(&self, start: DateTime, end: DateTime) -> Result<Vec<Period>, TimeZoneError> {
+        let (from, to) = (start.to_seconds(), end.to_seconds());
+        // instances are found by their start as written, so also expand those that start
+        // early enough to run into the window, allowing a day either side for UTC offsets
+        let longest = self
+            .events
+            .iter()
+            .filter_map(Event::duration)
+            .map(|duration| duration.as_seconds())
+            .max()
+            .unwrap_or(0)
+            .max(0);
+        let window = (
+            DateTime::from_seconds(from - longest - 86_400, true),
+            DateTime::from_seconds(to + 86_400, true),
+        );
+
+        let mut busy: Vec<(i64, i64)> = vec![];
+        for event in self
+            .event_series()
+            .values()
+            .flat_map(|series| series.instance_events(window))
+            .filter(|event| event.effective_transparency() != TimeTransparency::Transparent)
+        {
+            let Some((busy_from, busy_to)) = self.utc_interval(&event)? else {
+                continue;
+            };
+            let busy_from = busy_from.to_seconds().max(from);
+            let busy_to = busy_to.to_seconds().min(to);
+            if busy_from < busy_to {
+                busy.push((busy_from, busy_to));
+            }
+        }
+        busy.sort_unstable();
+
+        let mut merged: Vec<(i64, i64)> = vec![];
+        for (busy_from, busy_to) in busy {
+            match merged.last_mut() {
+                Some((_, last_to)) if busy_from <= *last_to => {
+                    *last_to = (*last_to).max(busy_to);
+                }
+                _ => merged.push((busy_from, busy_to)),
+            }
+        }
+        Ok(merged
+            .into_iter()
+            .map(|(busy_from, busy_to)| Period::Explicit {
+                start: DateTime::from_seconds(busy_from, true),
+                end: DateTime::from_seconds(busy_to, true),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_util,
+        types::{DateTime, Period},
+    };
+
+    #[test]
+    fn free_busy() {
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:early\r\n\
+            DTSTART:20250701T070000Z\r\n\
+            DTEND:20250701T090000Z\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:adjacent\r\n\
+            DTSTART:20250701T090000Z\r\n\
+            DURATION:PT1H\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:overlapping\r\n\
+            DTSTART:20250701T093000Z\r\n\
+            DTEND:20250701T110000Z\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:transparent\r\n\
+            DTSTART:20250701T120000Z\r\n\
+            DTEND:20250701T130000Z\r\n\
+            TRANSP:TRANSPARENT\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:daily\r\n\
+            DTSTART:20250701T140000Z\r\n\
+            DTEND:20250701T150000Z\r\n\
+            RRULE:FREQ=DAILY;COUNT=3\r\n\
+            END:VEVENT\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let time = |text| DateTime::parse(text).unwrap().1;
+        let period = |start, end| Period::Explicit {
+            start: time(start),
+            end: time(end),
+        };
+
+        assert_eq!(
+            calendar
+                .free_busy(time("20250701T080000Z"), time("20250703T000000Z"))
+                .unwrap(),
+            [
+                period("20250701T080000Z", "20250701T110000Z"),
+                period("20250701T140000Z", "20250701T150000Z"),
+                period("20250702T140000Z", "20250702T150000Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn free_busy_unknown_timezone() {
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:unknown\r\n\
+            DTSTART;TZID=Nowhere/Unknown:20250701T090000\r\n\
+            DTEND;TZID=Nowhere/Unknown:20250701T100000\r\n\
+            END:VEVENT\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let time = |text| DateTime::parse(text).unwrap().1;
+        let error = calendar
+            .free_busy(time("20250701T000000Z"), time("20250702T000000Z"))
+            .unwrap_err();
+        assert!(
+            matches!(&error, crate::TimeZoneError::Unknown(id) if id == "Nowhere/Unknown"),
+            "{error:?}"
+        );
+    }
+}
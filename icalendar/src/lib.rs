@@ -3,55 +3,255 @@
 //! CalDAV iCalendar format.
 //!
 //!
-use std::borrow::Cow;
+use std::{borrow::Cow, cmp::Ordering, collections::HashSet, fmt, iter, mem, ops::RangeInclusive};
 
 use crate::{
     params::{
-        AlternativeTextRepresentation, CalendarUserType, DirectoryEntryReference, FormatType,
-        Language, ParticipationRole, ParticipationStatus, Range, RsvpExpectation, SentBy,
-        TimeZoneIdentifier,
+        AlarmTriggerRelationship, AlternativeTextRepresentation, CalendarUserType,
+        DirectoryEntryReference, FormatType, Language, ParticipationRole, ParticipationStatus,
+        Range, RelationshipType, RsvpExpectation, ScheduleAgent, ScheduleForceSend, ScheduleStatus,
+        SentBy, TimeZoneIdentifier,
     },
-    parser::Lexer,
-    types::{Data, DateOrDateTime, DateTime, Duration, GeoLocation, Priority, VecOne, XName},
-    values::CalendarUserAddress,
+    parser::{Lexer, LineIter},
+    types::{
+        Data, DateOrDateTime, DateTime, Duration, GeoLocation, Name, Place, Priority, Recur, Time,
+        UtcOffset, VecOne, XName,
+    },
+    values::{CalendarUserAddress, Uri},
 };
 
 #[macro_use]
 mod macros;
 
+mod attendance;
+mod color;
+mod free_busy;
+mod meeting;
+mod names;
 pub mod params;
 pub(crate) mod parser;
+mod recurrence;
+mod report;
+mod scheduling;
+#[cfg(any(test, feature = "testing"))]
+pub mod test_util;
+mod timezone;
 pub mod types;
+mod uid;
+mod utc;
+mod validation;
 mod values;
+mod writer;
+
+pub use attendance::AttendanceSummary;
+pub use meeting::{MeetingLink, MeetingProvider};
+pub use parser::{
+    CalendarBuilder, Component, EventHeader, Line, ParseError, ParseErrorKind, ParseOptions,
+    Parsed, Parser, UnknownComponents, Warning,
+};
+pub use recurrence::EventSeries;
+pub use scheduling::ValidationIssue;
+pub use timezone::{TimeZoneError, Transition};
+pub use uid::Uid;
+pub use utc::UtcEvent;
+pub use values::URIError;
+
+pub use base64::DecodeError;
 
 type Result<T = (), E = anyhow::Error> = std::result::Result<T, E>;
 
 /// Parse a file in iCalendar format and return a list of calendars
-pub fn parse(input: &str) -> Result<Vec<Calendar>> {
+///
+/// Input can contain any number of `VCALENDAR`s one after the other, all of which are
+/// returned in order.
+pub fn parse(input: &str) -> Result<Vec<Calendar>, ParseError> {
+    Ok(parse_with(input, ParseOptions::default())?.calendars)
+}
+
+/// Parse only the first calendar in the input, or `None` if the input is empty.
+///
+/// Anything after the first `END:VCALENDAR` is ignored without being read.
+pub fn parse_first(input: &str) -> Result<Option<Calendar<'_>>, ParseError> {
     let mut parser = Lexer::new(input);
+    let calendar = match parser.is_empty() {
+        Ok(true) => return Ok(None),
+        Ok(false) => Calendar::parse(&mut parser),
+        Err(error) => Err(error),
+    };
+    calendar.map(Some).map_err(|error| parser.error(error))
+}
+
+/// Like [`parse`], with control over how strict parsing is.
+pub fn parse_with(input: &str, options: ParseOptions) -> Result<Parsed<'_>, ParseError> {
+    parse_all(Lexer::with_options(input, options))
+}
+
+fn parse_all<'src>(mut parser: Lexer<'src>) -> Result<Parsed<'src>, ParseError> {
+    let calendars = parse_calendars(&mut parser).map_err(|error| parser.error(error))?;
+    Ok(Parsed {
+        calendars,
+        warnings: parser.take_warnings(),
+    })
+}
+
+fn parse_calendars<'src>(parser: &mut Lexer<'src>) -> Result<Vec<Calendar<'src>>> {
     let mut calendars = vec![];
     while !parser.is_empty()? {
-        calendars.push(Calendar::parse(&mut parser)?);
+        calendars.push(Calendar::parse(parser)?);
     }
     Ok(calendars)
 }
 
+/// Parse only the `UID`, `DTSTART` and `SUMMARY` of every event in the input.
+///
+/// All other properties and components are skipped without being interpreted, which is much
+/// cheaper than [`parse`] for building an index of a large calendar. Only the structure of the
+/// input and the properties returned are checked.
+pub fn parse_headers(input: &str) -> Result<Vec<EventHeader<'_>>, ParseError> {
+    let mut parser = Lexer::new(input);
+    parser::parse_headers(&mut parser).map_err(|error| parser.error(error))
+}
+
+/// Split the input into content lines, joining lines that were folded.
+///
+/// Nothing is parsed, so this works on invalid input too. Lines are separated by `\r\n`,
+/// and a line starting with a space continues the one before it.
+pub fn unfold(input: &str) -> impl Iterator<Item = Cow<'_, str>> {
+    LineIter::new(input)
+}
+
 /// iCal parser
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Calendar<'src> {
     pub events: Vec<Event<'src>>,
+    pub todos: Vec<Todo<'src>>,
+    pub journals: Vec<Journal<'src>>,
+    pub timezones: Vec<TimeZone<'src>>,
     pub prod_id: Cow<'src, str>,
+    /// `VERSION` as written, either `2.0` or a range including it such as `1.0;2.0`.
+    ///
+    /// Calendars without a `VERSION` get `2.0`. In lenient mode this can be anything.
+    pub version: Cow<'src, str>,
+    /// `CALSCALE`, which applies to the components after it.
+    ///
+    /// Dates in calendars with other scales are kept as written, as
+    /// [`DateOrDateTime::Raw`]. Nothing (durations, recurrences and so on) is calculated from
+    /// them.
     pub cal_scale: CalScale<'src>,
     pub method: Option<Cow<'src, str>>,
+    /// The calendar-wide timezone from `X-WR-TIMEZONE` (used by Google and Apple), for
+    /// interpreting floating times. Not checked against the timezones defined.
+    pub default_timezone: Option<Cow<'src, str>>,
+    /// The name of the calendar, from `NAME` (RFC 7986) or else `X-WR-CALNAME` (used by Google
+    /// and Apple). Only the first `NAME` is kept. Written as `X-WR-CALNAME`, which more clients
+    /// understand.
+    pub name: Option<Cow<'src, str>>,
+    /// Unsupported components that were skipped, if parsed with
+    /// [`UnknownComponents::Collect`]. Never written out.
+    pub skipped_components: Vec<SkippedComponent<'src>>,
+}
+
+/// A component that was skipped while parsing, see [`Calendar::skipped_components`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkippedComponent<'src> {
+    /// The component name, e.g. `VFREEBUSY`
+    pub name: Cow<'src, str>,
+    /// The lines of input from `BEGIN` to `END`, 1-based and counting folded lines
+    /// separately
+    pub lines: RangeInclusive<usize>,
+}
+
+/// A component of a [`Calendar`], see [`Calendar::components`].
+///
+/// Like [`Component`], but borrowed from a calendar that has been parsed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComponentRef<'a, 'src> {
+    Event(&'a Event<'src>),
+    Todo(&'a Todo<'src>),
+    Journal(&'a Journal<'src>),
+}
+
+impl<'src> ComponentRef<'_, 'src> {
+    /// The `UID` of the component
+    pub fn uid(&self) -> &Cow<'src, str> {
+        match self {
+            ComponentRef::Event(event) => &event.uid,
+            ComponentRef::Todo(todo) => &todo.uid,
+            ComponentRef::Journal(journal) => &journal.uid,
+        }
+    }
+}
+
+impl<'src> Calendar<'src> {
+    /// The events, then the todos, then the journals of this calendar.
+    ///
+    /// Timezones and skipped components aren't included.
+    pub fn components(&self) -> impl Iterator<Item = ComponentRef<'_, 'src>> {
+        let events = self.events.iter().map(ComponentRef::Event);
+        let todos = self.todos.iter().map(ComponentRef::Todo);
+        let journals = self.journals.iter().map(ComponentRef::Journal);
+        events.chain(todos).chain(journals)
+    }
 }
 
-#[derive(Debug)]
+impl Calendar<'_> {
+    /// Sort events by their start, see [`DateOrDateTime::cmp_chronological`].
+    ///
+    /// Events without a start are put at the end, in their original order.
+    pub fn sort_events(&mut self) {
+        self.events.sort_by(Event::cmp_start);
+    }
+
+    /// Remove the `VTIMEZONE` definitions from this calendar.
+    ///
+    /// This is only safe once all times have been converted to UTC, so it fails (leaving the
+    /// calendar untouched) if any event still refers to a timezone by `TZID`.
+    pub fn strip_timezones(&mut self) -> Result {
+        if let Some(id) = self
+            .events
+            .iter()
+            .find_map(|event| event.timezone_ids().next())
+        {
+            anyhow::bail!(
+                "cannot strip timezones: timezone {} is still referenced",
+                id.fmt_value()
+            );
+        }
+        self.timezones.clear();
+        Ok(())
+    }
+
+    /// Rename the category `from` (ignoring case) to `to` in every event.
+    ///
+    /// See [`merge_categories`](Self::merge_categories).
+    pub fn rename_category(&mut self, from: &str, to: &str) {
+        self.merge_categories(&[from], to);
+    }
+
+    /// Replace each of the categories `aliases` (ignoring case) with `into` in every event.
+    ///
+    /// Afterwards each event lists every category once (again ignoring case), at the position
+    /// it first appeared. `CATEGORIES` properties left empty are removed.
+    pub fn merge_categories(&mut self, aliases: &[&str], into: &str) {
+        let aliases: Vec<String> = aliases.iter().map(|alias| alias.to_lowercase()).collect();
+        for event in &mut self.events {
+            event.merge_categories(&aliases, into);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Event<'src> {
     pub class: Class<'src>,
     pub created: Option<DateTime>,
     pub last_modified: Option<DateTime>,
     pub description: Option<AnnotatedText<'src>>,
     pub start: Option<DateOrDateTime>,
+    /// The `TZID` of `start`, if it is in local time
+    pub start_timezone_id: Option<TimeZoneIdentifier<'src>>,
     pub location: Option<AnnotatedText<'src>>,
     pub geo_location: Option<GeoLocation>,
     pub organizer: Option<Organizer<'src>>,
@@ -60,26 +260,497 @@ pub struct Event<'src> {
     pub sequence: Option<u64>,
     pub status: Option<EventStatus>,
     pub summary: Option<AnnotatedText<'src>>,
-    pub time_transparency: TimeTransparency,
+    /// `TRANSP`, `None` if absent (which means [`Opaque`](TimeTransparency::Opaque)). See
+    /// also [`effective_transparency`](Self::effective_transparency).
+    pub time_transparency: Option<TimeTransparency>,
     pub uid: Cow<'src, str>,
+    /// `URL`, a page about the event
+    pub url: Option<Uri<'src>>,
     pub recurrence_id: Option<RecurrenceId<'src>>,
     pub end: Option<EventEnd<'src>>,
+    /// `COLOR` (RFC 7986), a CSS3 color name. See [`color_rgba`](Self::color_rgba).
+    pub color: Option<Cow<'src, str>>,
     pub attachments: Vec<Attachment<'src>>,
     pub attendees: Vec<Attendee<'src>>,
     pub categories: Vec<Categories<'src>>,
     pub comments: Vec<Comment<'src>>,
     pub contacts: Vec<Contact<'src>>,
+    /// `CONFERENCE` (RFC 7986). See [`meeting_link`](Self::meeting_link).
+    pub conferences: Vec<Conference<'src>>,
     pub exception_dates: Vec<ExceptionDateTimes<'src>>,
+    pub recurrence_rules: Vec<Recur>,
+    pub recurrence_dates: Vec<RecurrenceDates<'src>>,
+    pub structured_locations: Vec<StructuredLocation<'src>>,
+    /// Invalid `VALARM`s are skipped, with a warning from [`parse_with`], rather than failing
+    /// the event
+    pub alarms: Vec<Alarm<'src>>,
+    /// Non-standard (`X-`) properties, in the order they appeared
+    pub extensions: Vec<ExtensionProperty<'src>>,
+}
+
+impl<'src> Event<'src> {
+    /// The order of [`Calendar::sort_events`]
+    pub(crate) fn cmp_start(&self, other: &Event<'_>) -> Ordering {
+        match (&self.start, &other.start) {
+            (Some(start1), Some(start2)) => start1.cmp_chronological(start2),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    /// Where the event takes place, combining all the location information available.
+    ///
+    /// The name is taken from `LOCATION`, falling back to the first `VLOCATION` and then
+    /// the `X-APPLE-STRUCTURED-LOCATION` title. Coordinates come from `GEO`, falling back
+    /// in the same order.
+    pub fn place(&self) -> Option<Place<'_>> {
+        let apple = self
+            .extensions
+            .iter()
+            .find(|prop| prop.name == names::X_APPLE_STRUCTURED_LOCATION);
+
+        let name = self
+            .location
+            .as_ref()
+            .map(|location| &location.text)
+            .or_else(|| {
+                self.structured_locations
+                    .iter()
+                    .find_map(|location| location.name.as_ref())
+            })
+            .or_else(|| apple?.param(&names::X_TITLE).map(|title| &title.first))
+            .map(|name| Cow::Borrowed(&**name));
+
+        let coordinates = self
+            .geo_location
+            .or_else(|| {
+                self.structured_locations
+                    .iter()
+                    .find_map(|location| location.coordinates())
+            })
+            .or_else(|| GeoLocation::from_geo_uri(&apple?.value).ok());
+
+        if name.is_none() && coordinates.is_none() {
+            return None;
+        }
+        Some(Place { name, coordinates })
+    }
+
+    /// Whether the event blocks time, from `TRANSP` or else Outlook's
+    /// `X-MICROSOFT-CDO-BUSYSTATUS`.
+    ///
+    /// A busy status of `FREE` is transparent and any other (`TENTATIVE`, `BUSY`, `OOF`, ...)
+    /// opaque. Events with neither are opaque.
+    pub fn effective_transparency(&self) -> TimeTransparency {
+        if let Some(transparency) = &self.time_transparency {
+            return transparency.clone();
+        }
+        let busy_status = self
+            .extensions
+            .iter()
+            .find(|prop| prop.name == names::X_MICROSOFT_CDO_BUSYSTATUS);
+        match busy_status {
+            Some(status) if status.value.eq_ignore_ascii_case("FREE") => {
+                TimeTransparency::Transparent
+            }
+            _ => TimeTransparency::Opaque,
+        }
+    }
+
+    /// Whether this is an all-day event (`DTSTART` is a date).
+    pub fn is_all_day(&self) -> bool {
+        self.start
+            .as_ref()
+            .is_some_and(|start| !start.is_date_time())
+    }
+
+    /// How long the event lasts, or `None` if it has no `DTSTART` (or it starts or ends at a
+    /// [`Raw`](DateOrDateTime::Raw) value and has no `DURATION`).
+    ///
+    /// This is `DURATION` if given, or else the time from `DTSTART` to `DTEND` as written
+    /// (ignoring any `TZID`s). Without either, all-day events last a day and timed events take
+    /// no time.
+    pub fn duration(&self) -> Option<Duration> {
+        let start = self.start.as_ref()?;
+        let seconds = |value: &DateOrDateTime| match value {
+            DateOrDateTime::Date(date) => Some(date.to_days() * 86_400),
+            DateOrDateTime::DateTime(date_time) => Some(date_time.to_seconds()),
+            DateOrDateTime::Raw(_) => None,
+        };
+        Some(match &self.end {
+            Some(EventEnd::Duration(duration)) => duration.clone(),
+            Some(EventEnd::DateTime { value, .. }) => {
+                Duration::from_seconds(seconds(value)? - seconds(start)?)
+            }
+            None if matches!(start, DateOrDateTime::Raw(_)) => return None,
+            None if self.is_all_day() => Duration::from_seconds(86_400),
+            None => Duration::from_seconds(0),
+        })
+    }
+
+    /// When the event ends, or `None` if it has no `DTSTART` (or the end is outside years 0
+    /// to 9999, or needs calculating from a [`Raw`](DateOrDateTime::Raw) `DTSTART`).
+    ///
+    /// This is `DTEND` as written if there is one. Otherwise [`duration`](Self::duration) is
+    /// added to `DTSTART`. If `DTSTART` has a `TZID` defined in `timezones` (or the bundled
+    /// database, with the `tzdb` feature), whole days are added in local time, so a day can
+    /// be 23 or 25 hours long across a daylight saving transition (see
+    /// [`TimeZone::add_duration`]). Otherwise days are 24 hours.
+    pub fn effective_end(&self, timezones: &[TimeZone<'_>]) -> Option<DateOrDateTime> {
+        if let Some(EventEnd::DateTime { value, .. }) = &self.end {
+            return Some(value.clone());
+        }
+        let duration = self.duration()?;
+        match *self.start.as_ref()? {
+            DateOrDateTime::Date(date) => {
+                let midnight = DateTime {
+                    date,
+                    time: Time {
+                        hour: 0,
+                        minute: 0,
+                        second: 0,
+                        utc: false,
+                    },
+                };
+                let end = midnight.checked_add(duration)?;
+                // a duration with hours, minutes or seconds ends part way through a day
+                Some(if end.time == midnight.time {
+                    DateOrDateTime::Date(end.date)
+                } else {
+                    DateOrDateTime::DateTime(end)
+                })
+            }
+            DateOrDateTime::DateTime(start) => {
+                let timezone = self
+                    .start_timezone_id
+                    .as_ref()
+                    .filter(|_| !start.time.utc)
+                    .and_then(|id| timezone::resolve(timezones, id.id()));
+                let end = match timezone {
+                    Some(timezone) => timezone.add_duration(start, &duration),
+                    None => start.checked_add(duration),
+                };
+                end.map(DateOrDateTime::DateTime)
+            }
+            DateOrDateTime::Raw(_) => None,
+        }
+    }
+
+    /// The end of the event as written, or `None` if it has neither `DTEND` nor `DURATION` (or
+    /// the end is outside years 0 to 9999).
+    ///
+    /// This is `DTEND` if there is one, or else `DURATION` added to `DTSTART` with days 24
+    /// hours long. An all-day event's end is always a date: a duration that isn't a whole
+    /// number of days is rounded up to one. Unlike [`effective_end`](Self::effective_end)
+    /// there is no default length and timezones aren't used, which is enough for showing the
+    /// end as it was given.
+    pub fn end_date_or_datetime(&self) -> Option<DateOrDateTime> {
+        let duration = match &self.end {
+            Some(EventEnd::DateTime { value, .. }) => return Some(value.clone()),
+            Some(EventEnd::Duration(duration)) => duration,
+            None => return None,
+        };
+        match *self.start.as_ref()? {
+            DateOrDateTime::Date(date) => {
+                let seconds = duration.as_seconds();
+                let days = seconds.div_euclid(86_400) + i64::from(seconds.rem_euclid(86_400) > 0);
+                let midnight = DateTime {
+                    date,
+                    time: Time {
+                        hour: 0,
+                        minute: 0,
+                        second: 0,
+                        utc: false,
+                    },
+                };
+                let end = midnight.checked_add_seconds(days.checked_mul(86_400)?)?;
+                Some(DateOrDateTime::Date(end.date))
+            }
+            DateOrDateTime::DateTime(start) => start
+                .checked_add(duration.clone())
+                .map(DateOrDateTime::DateTime),
+            DateOrDateTime::Raw(_) => None,
+        }
+    }
+
+    /// All the `TZID`s this event refers to
+    fn timezone_ids(&self) -> impl Iterator<Item = &TimeZoneIdentifier<'src>> {
+        let end = match &self.end {
+            Some(EventEnd::DateTime { timezone_id, .. }) => timezone_id.as_ref(),
+            _ => None,
+        };
+        let start = self.start_timezone_id.as_ref();
+        let recurrence_id = self
+            .recurrence_id
+            .as_ref()
+            .and_then(|id| id.timezone_id.as_ref());
+        let exception_dates = self
+            .exception_dates
+            .iter()
+            .filter_map(|dates| dates.timezone_id.as_ref());
+        let recurrence_dates = self
+            .recurrence_dates
+            .iter()
+            .filter_map(|dates| dates.timezone_id.as_ref());
+        start
+            .into_iter()
+            .chain(end)
+            .chain(recurrence_id)
+            .chain(exception_dates)
+            .chain(recurrence_dates)
+    }
+
+    /// Whether this event is a newer revision of `other`.
+    ///
+    /// Both must describe the same instance (same `UID` and `RECURRENCE-ID`). A higher
+    /// `SEQUENCE` wins (a missing sequence counts as 0), and on equal sequences the newer
+    /// `DTSTAMP` wins.
+    pub fn supersedes(&self, other: &Event<'_>) -> bool {
+        if self.uid != other.uid || self.recurrence_id != other.recurrence_id {
+            return false;
+        }
+        match self.sequence.unwrap_or(0).cmp(&other.sequence.unwrap_or(0)) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            // `None` sorts before any timestamp
+            Ordering::Equal => self.timestamp > other.timestamp,
+        }
+    }
+
+    /// Apply an update (e.g. from an iTIP `REQUEST`) to this event.
+    ///
+    /// `newer` replaces this event if it [supersedes](Self::supersedes) it, otherwise it is
+    /// discarded. Returns whether the update was applied.
+    pub fn merge_update(&mut self, newer: Event<'src>) -> bool {
+        if !newer.supersedes(self) {
+            return false;
+        }
+        *self = newer;
+        true
+    }
+
+    /// The values of every `CATEGORIES` property, in order, each listed once (the first time
+    /// it appears, ignoring case).
+    pub fn all_categories(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        self.categories
+            .iter()
+            .flat_map(|categories| &categories.values)
+            .map(|value| &**value)
+            .filter(|value| seen.insert(value.to_lowercase()))
+            .collect()
+    }
+
+    /// `aliases` must be lowercase, see [`Calendar::merge_categories`]
+    fn merge_categories(&mut self, aliases: &[String], into: &str) {
+        let mut seen: Vec<String> = vec![];
+        for Categories { lang, values } in mem::take(&mut self.categories) {
+            let mut values = iter::once(values.first)
+                .chain(values.rest)
+                .map(|value| {
+                    if aliases.contains(&value.to_lowercase()) {
+                        Cow::Owned(into.to_owned())
+                    } else {
+                        value
+                    }
+                })
+                .filter(|value| {
+                    let key = value.to_lowercase();
+                    let new = !seen.contains(&key);
+                    if new {
+                        seen.push(key);
+                    }
+                    new
+                });
+            if let Some(first) = values.next() {
+                self.categories.push(Categories {
+                    lang,
+                    values: VecOne::from_parts(first, values.collect()),
+                });
+            }
+        }
+    }
+}
+
+/// A to-do (`VTODO`) component
+///
+/// Only the common descriptive properties are currently parsed, others are skipped.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Todo<'src> {
+    pub completed: Option<DateTime>,
+    pub created: Option<DateTime>,
+    pub last_modified: Option<DateTime>,
+    pub description: Option<AnnotatedText<'src>>,
+    pub start: Option<DateOrDateTime>,
+    /// The `TZID` of `start`, if it is in local time
+    pub start_timezone_id: Option<TimeZoneIdentifier<'src>>,
+    pub due: Option<DateOrDateTime>,
+    /// The `TZID` of `due`, if it is in local time
+    pub due_timezone_id: Option<TimeZoneIdentifier<'src>>,
+    pub duration: Option<Duration>,
+    /// 0..=100
+    pub percent_complete: Option<u8>,
+    pub priority: Option<Priority>,
+    pub timestamp: Option<DateTime>,
+    pub sequence: Option<u64>,
+    pub status: Option<TodoStatus>,
+    pub summary: Option<AnnotatedText<'src>>,
+    pub uid: Cow<'src, str>,
+}
+
+/// A journal entry (`VJOURNAL`) component
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Journal<'src> {
+    pub uid: Cow<'src, str>,
+    pub timestamp: Option<DateTime>,
+    pub start: Option<DateOrDateTime>,
+    /// The `TZID` of `start`, if it is in local time
+    pub start_timezone_id: Option<TimeZoneIdentifier<'src>>,
+    pub summary: Option<AnnotatedText<'src>>,
+    /// Unlike other components, journals can have any number of `DESCRIPTION`s
+    pub descriptions: Vec<AnnotatedText<'src>>,
+    pub categories: Vec<Categories<'src>>,
+    pub status: Option<JournalStatus>,
+    /// Non-standard (`X-`) properties, in the order they appeared
+    pub extensions: Vec<ExtensionProperty<'src>>,
+}
+
+/// A timezone definition (`VTIMEZONE`)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeZone<'src> {
+    /// The `TZID` other properties use to refer to this timezone
+    pub id: Cow<'src, str>,
+    pub last_modified: Option<DateTime>,
+    pub url: Option<Cow<'src, str>>,
+    pub observances: Vec<Observance<'src>>,
+}
+
+/// A `STANDARD` or `DAYLIGHT` sub-component of a timezone
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Observance<'src> {
+    pub kind: ObservanceKind,
+    /// Local time (in the `offset_from` offset) this observance first comes into effect
+    pub start: DateTime,
+    pub offset_from: UtcOffset,
+    pub offset_to: UtcOffset,
+    pub recurrence_rules: Vec<Recur>,
+    pub recurrence_dates: Vec<DateTime>,
+    pub names: Vec<AnnotatedText<'src>>,
+    pub comments: Vec<Comment<'src>>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ObservanceKind {
+    Standard,
+    Daylight,
+}
+
+/// A `VLOCATION` component (RFC 9073)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StructuredLocation<'src> {
+    pub uid: Cow<'src, str>,
+    pub name: Option<Cow<'src, str>>,
+    pub description: Option<AnnotatedText<'src>>,
+    pub location_type: Option<Cow<'src, str>>,
+    pub geo_location: Option<GeoLocation>,
+    pub url: Option<Cow<'src, str>>,
+}
+
+impl StructuredLocation<'_> {
+    /// The `GEO` property, or the coordinates of the `URL` if it is a `geo:` URI.
+    pub fn coordinates(&self) -> Option<GeoLocation> {
+        self.geo_location
+            .or_else(|| GeoLocation::from_geo_uri(self.url.as_deref()?).ok())
+    }
+}
+
+/// A `VALARM` component
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Alarm<'src> {
+    /// `ACTION`, e.g. `DISPLAY`, `AUDIO` or `EMAIL`
+    pub action: Cow<'src, str>,
+    pub trigger: AlarmTrigger,
+    pub description: Option<AnnotatedText<'src>>,
+    pub summary: Option<AnnotatedText<'src>>,
+    /// `DURATION`: the time between repeats of the alarm
+    pub duration: Option<Duration>,
+    /// `REPEAT`: how many more times the alarm goes off after the first. Always set together
+    /// with `duration`.
+    pub repeat: Option<u32>,
+    /// Who to email, for `EMAIL` alarms
+    pub attendees: Vec<Attendee<'src>>,
+    /// The sound of `AUDIO` alarms, or attachments of `EMAIL` alarms
+    pub attachments: Vec<Attachment<'src>>,
+    /// `UID` (RFC 9074), identifying the alarm across updates
+    pub uid: Option<Cow<'src, str>>,
+    /// `ACKNOWLEDGED` (RFC 9074): when the alarm was last dismissed, in UTC
+    pub acknowledged: Option<DateTime>,
+    pub related_to: Vec<RelatedTo<'src>>,
+    /// Non-standard (`X-`) properties, in the order they appeared
+    pub extensions: Vec<ExtensionProperty<'src>>,
+}
+
+impl Alarm<'_> {
+    /// If this alarm is a snoozed copy of another (`RELATED-TO;RELTYPE=SNOOZE`, RFC 9074),
+    /// the `UID` of the original alarm.
+    pub fn is_snooze_of(&self) -> Option<&str> {
+        self.related_to
+            .iter()
+            .find(|related| related.rel_type == RelationshipType::Snooze)
+            .map(|related| &*related.uid)
+    }
+}
+
+/// A non-standard property, stored without further interpretation
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtensionProperty<'src> {
+    pub name: XName<'src>,
+    pub params: Vec<(Name<'src>, VecOne<Cow<'src, str>>)>,
+    pub value: Cow<'src, str>,
+}
+
+impl<'src> ExtensionProperty<'src> {
+    /// Get the values of the parameter `name`, if present
+    pub fn param<N>(&self, name: &N) -> Option<&VecOne<Cow<'src, str>>>
+    where
+        N: ?Sized,
+        Name<'src>: PartialEq<N>,
+    {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, values)| values)
+    }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CalScale<'src> {
     #[default]
     Gregorian,
     Other(Cow<'src, str>),
 }
 
-#[derive(Debug, Default)]
+impl fmt::Display for CalScale<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalScale::Gregorian => f.write_str("GREGORIAN"),
+            CalScale::Other(name) => f.write_str(name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Class<'src> {
     #[default]
     Public,
@@ -89,45 +760,126 @@ pub enum Class<'src> {
     XName(XName<'src>),
 }
 
+impl fmt::Display for Class<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Class::Public => f.write_str("PUBLIC"),
+            Class::Private => f.write_str("PRIVATE"),
+            Class::Confidential => f.write_str("CONFIDENTIAL"),
+            Class::Iana(name) => f.write_str(name),
+            Class::XName(name) => fmt::Display::fmt(name, f),
+        }
+    }
+}
+
 /// Text that has optional language and alt representation
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnnotatedText<'src> {
     pub lang: Option<Language<'src>>,
     pub altrep: Option<AlternativeTextRepresentation<'src>>,
+    /// The text with escapes (e.g. `\,` and `\n`) undone
     pub text: Cow<'src, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Organizer<'src> {
     pub common_name: Option<Cow<'src, str>>,
     pub dir: Option<DirectoryEntryReference<'src>>,
     pub sent_by: Option<SentBy<'src>>,
     pub lang: Option<Language<'src>>,
+    pub schedule_agent: Option<ScheduleAgent<'src>>,
+    pub schedule_force_send: Option<ScheduleForceSend<'src>>,
+    pub schedule_status: Option<ScheduleStatus<'src>>,
     pub value: CalendarUserAddress<'src>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EventStatus {
     Tentative,
     Confirmed,
     Cancelled,
 }
 
-#[derive(Debug, Default)]
+impl fmt::Display for EventStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EventStatus::Tentative => "TENTATIVE",
+            EventStatus::Confirmed => "CONFIRMED",
+            EventStatus::Cancelled => "CANCELLED",
+        })
+    }
+}
+
+/// The `STATUS` of a `VTODO`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TodoStatus {
+    NeedsAction,
+    Completed,
+    InProcess,
+    Cancelled,
+}
+
+impl TodoStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TodoStatus::NeedsAction => "NEEDS-ACTION",
+            TodoStatus::Completed => "COMPLETED",
+            TodoStatus::InProcess => "IN-PROCESS",
+            TodoStatus::Cancelled => "CANCELLED",
+        }
+    }
+}
+
+/// The `STATUS` of a `VJOURNAL`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JournalStatus {
+    Draft,
+    Final,
+    Cancelled,
+}
+
+impl JournalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JournalStatus::Draft => "DRAFT",
+            JournalStatus::Final => "FINAL",
+            JournalStatus::Cancelled => "CANCELLED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TimeTransparency {
     #[default]
     Opaque,
     Transparent,
 }
 
-#[derive(Debug)]
+impl fmt::Display for TimeTransparency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TimeTransparency::Opaque => "OPAQUE",
+            TimeTransparency::Transparent => "TRANSPARENT",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RecurrenceId<'src> {
     pub range: Option<Range>,
     pub timezone_id: Option<TimeZoneIdentifier<'src>>,
     pub value: DateOrDateTime,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EventEnd<'src> {
     DateTime {
         value: DateOrDateTime,
@@ -136,13 +888,35 @@ pub enum EventEnd<'src> {
     Duration(Duration),
 }
 
-#[derive(Debug)]
+/// When an alarm goes off (`TRIGGER`)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlarmTrigger {
+    /// An offset from the start or end of the component the alarm belongs to
+    Relative {
+        related: AlarmTriggerRelationship,
+        duration: Duration,
+    },
+    /// A fixed time, in UTC
+    Absolute(DateTime),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Attachment<'src> {
     pub fmt_type: Option<FormatType<'src>>,
     pub data: Data<'src>,
 }
 
-#[derive(Debug)]
+impl Attachment<'_> {
+    /// The media type from `FMTTYPE`, see [`FormatType::media_type`]
+    pub fn media_type(&self) -> Option<String> {
+        self.fmt_type.as_ref().map(FormatType::media_type)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Attendee<'src> {
     pub cutype: CalendarUserType<'src>,
     pub group_or_list_members: Vec<CalendarUserAddress<'src>>,
@@ -155,30 +929,479 @@ pub struct Attendee<'src> {
     pub common_name: Option<Cow<'src, str>>,
     pub dir: Option<DirectoryEntryReference<'src>>,
     pub lang: Option<Language<'src>>,
+    pub schedule_agent: Option<ScheduleAgent<'src>>,
+    pub schedule_force_send: Option<ScheduleForceSend<'src>>,
+    pub schedule_status: Option<ScheduleStatus<'src>>,
+    /// Parameters that aren't recognised, such as Google's `X-NUM-GUESTS`, in the order they
+    /// appeared
+    pub other_params: Vec<(Name<'src>, VecOne<Cow<'src, str>>)>,
+    pub value: CalendarUserAddress<'src>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Categories<'src> {
     pub lang: Option<Language<'src>>,
     pub values: VecOne<Cow<'src, str>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Comment<'src> {
     pub lang: Option<Language<'src>>,
     pub altrep: Option<AlternativeTextRepresentation<'src>>,
     pub value: Cow<'src, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Contact<'src> {
     pub lang: Option<Language<'src>>,
     pub altrep: Option<AlternativeTextRepresentation<'src>>,
     pub value: Cow<'src, str>,
 }
 
-#[derive(Debug)]
+/// `RELATED-TO`: the `UID` of another component and how it relates to this one
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RelatedTo<'src> {
+    pub rel_type: RelationshipType<'src>,
+    pub uid: Cow<'src, str>,
+}
+
+/// `CONFERENCE` (RFC 7986): a way of joining the event remotely
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Conference<'src> {
+    pub uri: Uri<'src>,
+    /// `FEATURE`s of the conference, e.g. `AUDIO`, `VIDEO` or `SCREEN`
+    pub features: Vec<Cow<'src, str>>,
+    /// `LABEL`, a description of the conference for users
+    pub label: Option<Cow<'src, str>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExceptionDateTimes<'src> {
     pub timezone_id: Option<TimeZoneIdentifier<'src>>,
     pub values: VecOne<DateOrDateTime>,
 }
+
+/// Extra instances of a recurring component (`RDATE`)
+///
+/// `VALUE=PERIOD` is not supported yet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecurrenceDates<'src> {
+    pub timezone_id: Option<TimeZoneIdentifier<'src>>,
+    pub values: VecOne<DateOrDateTime>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{
+        TimeTransparency, test_util,
+        types::{Date, DateOrDateTime, DateTime, GeoLocation, Place},
+    };
+
+    #[test]
+    fn components() {
+        let input = test_util::calendar(
+            "BEGIN:VTODO\r\n\
+            UID:todo\r\n\
+            END:VTODO\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:event-1\r\n\
+            DTSTAMP:20250704T180000Z\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VJOURNAL\r\n\
+            UID:journal\r\n\
+            END:VJOURNAL\r\n\
+            BEGIN:VFREEBUSY\r\n\
+            UID:free-busy\r\n\
+            END:VFREEBUSY\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:event-2\r\n\
+            DTSTAMP:20250704T180000Z\r\n\
+            END:VEVENT\r\n",
+        );
+        let calendar = super::parse(&input).unwrap().remove(0);
+        assert_eq!(calendar.components().count(), 4);
+        let uids: Vec<_> = calendar
+            .components()
+            .map(|component| component.uid().clone())
+            .collect();
+        assert_eq!(uids, ["event-1", "event-2", "todo", "journal"]);
+        assert!(matches!(
+            calendar.components().nth(2),
+            Some(super::ComponentRef::Todo(todo)) if *todo == calendar.todos[0]
+        ));
+    }
+
+    #[test]
+    fn effective_end_across_dst() {
+        let input = |start: &str, duration: &str| {
+            test_util::calendar(&format!(
+                "BEGIN:VTIMEZONE\r\n\
+                TZID:America/New_York\r\n\
+                BEGIN:DAYLIGHT\r\n\
+                DTSTART:20070311T020000\r\n\
+                TZOFFSETFROM:-0500\r\n\
+                TZOFFSETTO:-0400\r\n\
+                RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\r\n\
+                END:DAYLIGHT\r\n\
+                BEGIN:STANDARD\r\n\
+                DTSTART:20071104T020000\r\n\
+                TZOFFSETFROM:-0400\r\n\
+                TZOFFSETTO:-0500\r\n\
+                RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\r\n\
+                END:STANDARD\r\n\
+                END:VTIMEZONE\r\n\
+                BEGIN:VEVENT\r\n\
+                UID:1\r\n\
+                DTSTART{start}\r\n\
+                DURATION:{duration}\r\n\
+                END:VEVENT\r\n"
+            ))
+        };
+        let effective_end = |start: &str, duration: &str| {
+            let input = input(start, duration);
+            let calendar = super::parse(&input).unwrap().remove(0);
+            let end = calendar.events[0]
+                .effective_end(&calendar.timezones)
+                .unwrap();
+            let DateOrDateTime::DateTime(end) = end else {
+                panic!("expected a date-time end");
+            };
+            (end, calendar.timezones[0].to_utc(end))
+        };
+        let local = |input: &str| DateTime::parse(input).unwrap().1;
+
+        // clocks go forward at 2am on 2025-03-09, so this day is 23 hours long
+        let start_utc = local("20250308T170000Z");
+        let (end, end_utc) = effective_end(";TZID=America/New_York:20250308T120000", "P1D");
+        assert_eq!(end, local("20250309T120000"));
+        assert_eq!(end_utc.to_seconds() - start_utc.to_seconds(), 23 * 3600);
+
+        // exact durations don't keep the time of day
+        let (end, _) = effective_end(";TZID=America/New_York:20250308T120000", "PT24H");
+        assert_eq!(end, local("20250309T130000"));
+
+        // without a timezone days are 24 hours
+        let (end, _) = effective_end(":20250308T120000Z", "P1D");
+        assert_eq!(end, local("20250309T120000Z"));
+    }
+
+    #[test]
+    fn all_day_and_duration() {
+        let input =
+            test_util::event("DTSTART;VALUE=DATE:20240101\r\nDTEND;VALUE=DATE:20240103\r\n");
+        let all_day = test_util::parse_event(&input);
+        assert!(all_day.is_all_day());
+        assert_eq!(all_day.duration().unwrap().to_string(), "P2D");
+
+        let input = test_util::event("DTSTART:20240101T090000Z\r\nDTEND:20240102T103015Z\r\n");
+        let timed = test_util::parse_event(&input);
+        assert!(!timed.is_all_day());
+        assert_eq!(timed.duration().unwrap().to_string(), "P1DT1H30M15S");
+
+        let input = test_util::event("DTSTART:20240101T090000Z\r\nDURATION:PT45M\r\n");
+        let timed = test_util::parse_event(&input);
+        assert_eq!(timed.duration().unwrap().to_string(), "PT45M");
+
+        let input = test_util::event("");
+        let no_start = test_util::parse_event(&input);
+        assert!(!no_start.is_all_day());
+        assert_eq!(no_start.duration(), None);
+    }
+
+    #[test]
+    fn display_tokens() {
+        use super::{CalScale, EventStatus};
+
+        assert_eq!(EventStatus::Tentative.to_string(), "TENTATIVE");
+        assert_eq!(EventStatus::Confirmed.to_string(), "CONFIRMED");
+        assert_eq!(EventStatus::Cancelled.to_string(), "CANCELLED");
+        assert_eq!(TimeTransparency::Opaque.to_string(), "OPAQUE");
+        assert_eq!(TimeTransparency::Transparent.to_string(), "TRANSPARENT");
+        assert_eq!(CalScale::Gregorian.to_string(), "GREGORIAN");
+        assert_eq!(CalScale::Other("JULIAN".into()).to_string(), "JULIAN");
+
+        // and they are written back
+        let input = test_util::event("STATUS:TENTATIVE\r\nTRANSP:TRANSPARENT\r\n");
+        let written = super::parse(&input).unwrap()[0].to_ics_string();
+        assert!(written.contains("\r\nSTATUS:TENTATIVE\r\nTRANSP:TRANSPARENT\r\n"));
+    }
+
+    #[test]
+    fn end_date_or_datetime() {
+        let end =
+            |props: &str| test_util::parse_event(&test_util::event(props)).end_date_or_datetime();
+        let date = |text| DateOrDateTime::Date(Date::parse(text).unwrap().1);
+        let date_time = |text| DateOrDateTime::DateTime(DateTime::parse(text).unwrap().1);
+
+        // explicit ends are returned as written
+        assert_eq!(
+            end("DTSTART;VALUE=DATE:20240101\r\nDTEND;VALUE=DATE:20240103\r\n"),
+            Some(date("20240103"))
+        );
+        assert_eq!(
+            end("DTSTART:20240101T090000Z\r\nDTEND;TZID=Fixed:20240101T110000\r\n"),
+            Some(date_time("20240101T110000"))
+        );
+
+        // durations are added to the start
+        assert_eq!(
+            end("DTSTART:20240131T230000Z\r\nDURATION:PT1H30M\r\n"),
+            Some(date_time("20240201T003000Z"))
+        );
+        assert_eq!(
+            end("DTSTART;VALUE=DATE:20240228\r\nDURATION:P1W\r\n"),
+            Some(date("20240306"))
+        );
+        // all-day events end on a date, with part of a day rounded up to a whole one
+        assert_eq!(
+            end("DTSTART;VALUE=DATE:20240101\r\nDURATION:P2D\r\n"),
+            Some(date("20240103"))
+        );
+        assert_eq!(
+            end("DTSTART;VALUE=DATE:20240101\r\nDURATION:P1DT12H\r\n"),
+            Some(date("20240103"))
+        );
+        assert_eq!(
+            end("DTSTART;VALUE=DATE:20240101\r\nDURATION:PT1H\r\n"),
+            Some(date("20240102"))
+        );
+
+        assert_eq!(end("DTSTART:20240101T090000Z\r\n"), None);
+        assert_eq!(end("DURATION:PT1H\r\n"), None);
+    }
+
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<super::Calendar<'static>>();
+        assert_send_sync::<super::Event<'static>>();
+    }
+
+    #[test]
+    fn rename_category() {
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\nUID:1\r\nCATEGORIES:wrk,meeting\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:2\r\nCATEGORIES:Work\r\nCATEGORIES:WRK\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:3\r\nCATEGORIES:job,home,Wrk\r\nEND:VEVENT\r\n",
+        );
+        let categories = |calendar: &super::Calendar| -> Vec<Vec<String>> {
+            calendar
+                .events
+                .iter()
+                .map(|event| {
+                    event
+                        .categories
+                        .iter()
+                        .flat_map(|categories| &categories.values)
+                        .map(|value| value.to_string())
+                        .collect()
+                })
+                .collect()
+        };
+
+        let mut calendar = super::parse(&input).unwrap().remove(0);
+        calendar.rename_category("wrk", "work");
+        assert_eq!(
+            categories(&calendar),
+            [
+                vec!["work", "meeting"],
+                vec!["Work"],
+                vec!["job", "home", "work"]
+            ]
+        );
+        // the emptied property is removed
+        assert_eq!(calendar.events[1].categories.len(), 1);
+
+        calendar.merge_categories(&["job", "WORK"], "Work");
+        assert_eq!(
+            categories(&calendar),
+            [vec!["Work", "meeting"], vec!["Work"], vec!["Work", "home"]]
+        );
+    }
+
+    #[test]
+    fn all_categories() {
+        let input = test_util::event("CATEGORIES:Work,Meeting\r\nCATEGORIES:meeting,Planning\r\n");
+        let event = test_util::parse_event(&input);
+        assert_eq!(event.all_categories(), ["Work", "Meeting", "Planning"]);
+
+        let input = test_util::event("");
+        assert!(test_util::parse_event(&input).all_categories().is_empty());
+    }
+
+    #[test]
+    fn sort_events() {
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\nUID:none\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:time\r\nDTSTART:20240103T093000Z\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:date\r\nDTSTART;VALUE=DATE:20240103\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:earlier\r\nDTSTART:20240102T140000\r\nEND:VEVENT\r\n",
+        );
+        let mut calendar = super::parse(&input).unwrap().remove(0);
+        calendar.sort_events();
+        let uids: Vec<_> = calendar.events.iter().map(|ev| &*ev.uid).collect();
+        assert_eq!(uids, ["earlier", "date", "time", "none"]);
+    }
+
+    #[test]
+    fn place_from_geo_and_location() {
+        let input = test_util::event("LOCATION:Conference Room 1\r\nGEO:37.386013;-122.082932\r\n");
+        let event = test_util::parse_event(&input);
+        assert_eq!(
+            event.place(),
+            Some(Place {
+                name: Some(Cow::Borrowed("Conference Room 1")),
+                coordinates: Some(GeoLocation {
+                    latitude: 37.386013,
+                    longitude: -122.082932
+                }),
+            })
+        );
+    }
+
+    #[test]
+    fn place_from_apple_structured_location() {
+        let input = test_util::event(
+            "X-APPLE-STRUCTURED-LOCATION;VALUE=URI;X-TITLE=Apple Park:geo:37.334722,-122.008889\r\n",
+        );
+        let event = test_util::parse_event(&input);
+        assert_eq!(
+            event.place(),
+            Some(Place {
+                name: Some(Cow::Borrowed("Apple Park")),
+                coordinates: Some(GeoLocation {
+                    latitude: 37.334722,
+                    longitude: -122.008889
+                }),
+            })
+        );
+
+        let input = test_util::event("");
+        assert_eq!(test_util::parse_event(&input).place(), None);
+    }
+
+    #[test]
+    fn effective_transparency() {
+        let event =
+            |props| test_util::parse_event(&test_util::event(props)).effective_transparency();
+        assert_eq!(
+            event("X-MICROSOFT-CDO-BUSYSTATUS:FREE\r\n"),
+            TimeTransparency::Transparent
+        );
+        assert_eq!(
+            event("X-MICROSOFT-CDO-BUSYSTATUS:OOF\r\n"),
+            TimeTransparency::Opaque
+        );
+        // TRANSP wins
+        assert_eq!(
+            event("TRANSP:OPAQUE\r\nX-MICROSOFT-CDO-BUSYSTATUS:FREE\r\n"),
+            TimeTransparency::Opaque
+        );
+        assert_eq!(event(""), TimeTransparency::Opaque);
+    }
+
+    #[test]
+    fn strip_timezones() {
+        let calendar_input = |end: &str| {
+            test_util::calendar(&format!(
+                "BEGIN:VTIMEZONE\r\n\
+                TZID:Europe/London\r\n\
+                BEGIN:STANDARD\r\n\
+                DTSTART:19701025T020000\r\n\
+                TZOFFSETFROM:+0100\r\n\
+                TZOFFSETTO:+0000\r\n\
+                END:STANDARD\r\n\
+                END:VTIMEZONE\r\n\
+                BEGIN:VEVENT\r\n\
+                UID:20111215T112233Z-1234@example.com\r\n\
+                DTSTART:20240103T093000Z\r\n\
+                {end}\r\n\
+                END:VEVENT\r\n"
+            ))
+        };
+
+        let input = calendar_input("DTEND:20240103T100000Z");
+        let mut calendar = super::parse(&input).unwrap().remove(0);
+        assert_eq!(calendar.timezones.len(), 1);
+        calendar.strip_timezones().unwrap();
+        assert!(calendar.timezones.is_empty());
+
+        let input = calendar_input("DTEND;TZID=Europe/London:20240103T100000");
+        let mut calendar = super::parse(&input).unwrap().remove(0);
+        assert!(calendar.strip_timezones().is_err());
+        assert_eq!(calendar.timezones.len(), 1);
+    }
+
+    /// An event revision with the given `SEQUENCE` and `DTSTAMP`
+    fn revision(sequence: u64, timestamp: &str, summary: &str) -> String {
+        test_util::calendar(&format!(
+            "BEGIN:VEVENT\r\n\
+            UID:20111215T112233Z-1234@example.com\r\n\
+            DTSTAMP:{timestamp}\r\n\
+            SEQUENCE:{sequence}\r\n\
+            SUMMARY:{summary}\r\n\
+            END:VEVENT\r\n"
+        ))
+    }
+
+    #[test]
+    fn merge_update_higher_sequence() {
+        let original = revision(1, "20240102T090000Z", "Original");
+        let update = revision(2, "20240101T090000Z", "Moved");
+        let mut event = test_util::parse_event(&original);
+        let newer = test_util::parse_event(&update);
+        assert!(newer.supersedes(&event));
+        assert!(!event.supersedes(&newer));
+
+        assert!(event.merge_update(newer));
+        assert_eq!(event.sequence, Some(2));
+        assert_eq!(event.summary.unwrap().text, "Moved");
+    }
+
+    #[test]
+    fn merge_update_rejects_older_timestamp() {
+        let original = revision(3, "20240102T090000Z", "Original");
+        let stale = revision(3, "20240101T090000Z", "Stale");
+        let mut event = test_util::parse_event(&original);
+        let older = test_util::parse_event(&stale);
+        assert!(!older.supersedes(&event));
+
+        assert!(!event.merge_update(older));
+        assert_eq!(event.summary.unwrap().text, "Original");
+
+        // a different instance is never an update
+        let other = test_util::event("SEQUENCE:10\r\nRECURRENCE-ID:20240105T090000Z\r\n");
+        let mut event = test_util::parse_event(&original);
+        assert!(!event.merge_update(test_util::parse_event(&other)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:20111215T112233Z-1234@example.com\r\n\
+            DTSTAMP:20111215T112233Z\r\n\
+            DTSTART:20111217T152336Z\r\n\
+            SUMMARY;LANGUAGE=en:Planning meeting\r\n\
+            ORGANIZER;CN=Jane Doe:mailto:jane@example.com\r\n\
+            ATTENDEE;PARTSTAT=ACCEPTED;RSVP=TRUE:mailto:john@example.com\r\n\
+            CATEGORIES:MEETING,WORK\r\n\
+            GEO:37.386013;-122.082932\r\n\
+            END:VEVENT\r\n",
+        );
+        let calendars = super::parse(&input).unwrap();
+        let json = serde_json::to_string(&calendars).unwrap();
+        let round_tripped: Vec<super::Calendar> = serde_json::from_str(&json).unwrap();
+        assert_eq!(calendars, round_tripped);
+    }
+}
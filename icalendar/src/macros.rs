@@ -14,3 +14,26 @@ macro_rules! itry {
     };
 }
 */
+
+/// Implement `Serialize` and `Deserialize` using the string form of a type.
+///
+/// Serialization uses the `Display` impl, deserialization the supplied parse
+/// function (`String -> Result<Self>`).
+#[cfg(feature = "serde")]
+macro_rules! impl_serde_str {
+    ($ty:ident $(<$lt:lifetime>)?, $parse:expr) => {
+        impl$(<$lt>)? serde::Serialize for $ty$(<$lt>)? {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de, $($lt)?> serde::Deserialize<'de> for $ty$(<$lt>)? {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let parse: fn(String) -> $crate::Result<Self> = $parse;
+                let input = <String as serde::Deserialize>::deserialize(deserializer)?;
+                parse(input).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
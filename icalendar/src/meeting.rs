@@ -0,0 +1,155 @@
+//! Finding the link to join an online meeting
+
+use std::borrow::Cow;
+
+use crate::Event;
+
+/// A link for joining an event online, see [`Event::meeting_link`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeetingLink<'a> {
+    pub url: Cow<'a, str>,
+    pub provider: MeetingProvider,
+}
+
+/// The service hosting an online meeting, detected from its URL
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MeetingProvider {
+    GoogleMeet,
+    Zoom,
+    MicrosoftTeams,
+    /// A service not listed here. Only links from `CONFERENCE` can have this provider.
+    Other,
+}
+
+impl MeetingProvider {
+    /// The provider hosting `host`, matching subdomains (e.g. `us02web.zoom.us`)
+    fn from_host(host: &str) -> Option<Self> {
+        const DOMAINS: [(&str, MeetingProvider); 3] = [
+            ("meet.google.com", MeetingProvider::GoogleMeet),
+            ("zoom.us", MeetingProvider::Zoom),
+            ("teams.microsoft.com", MeetingProvider::MicrosoftTeams),
+        ];
+        let host = host.to_ascii_lowercase();
+        DOMAINS.into_iter().find_map(|(domain, provider)| {
+            let subdomain = host
+                .strip_suffix(domain)
+                .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('.'));
+            subdomain.then_some(provider)
+        })
+    }
+}
+
+impl Event<'_> {
+    /// The link for joining this event online.
+    ///
+    /// The first web (`http` or `https`) `CONFERENCE` is preferred. Otherwise the `LOCATION`
+    /// and then the `DESCRIPTION` are searched for the first link to Google Meet, Zoom or
+    /// Microsoft Teams.
+    pub fn meeting_link(&self) -> Option<MeetingLink<'_>> {
+        let conference = self.conferences.iter().find_map(|conference| {
+            let url = conference.uri.to_string();
+            let (scheme, _) = url.split_once("://")?;
+            if !(scheme.eq_ignore_ascii_case("https") || scheme.eq_ignore_ascii_case("http")) {
+                return None;
+            }
+            let provider = MeetingProvider::from_host(url_host(&url)?);
+            Some(MeetingLink {
+                provider: provider.unwrap_or(MeetingProvider::Other),
+                url: Cow::Owned(url),
+            })
+        });
+        conference.or_else(|| {
+            [&self.location, &self.description]
+                .into_iter()
+                .flatten()
+                .find_map(|text| find_meeting_link(&text.text))
+        })
+    }
+}
+
+/// The first `http` or `https` link to a known provider in `text`
+fn find_meeting_link(text: &str) -> Option<MeetingLink<'_>> {
+    // schemes aren't case sensitive. Only ASCII is lowercased, so offsets are the same
+    let lowercase = text.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(idx) = lowercase[from..].find("http") {
+        let start = from + idx;
+        from = start + "http".len();
+        let scheme = &lowercase[start..];
+        if !(scheme.starts_with("https://") || scheme.starts_with("http://")) {
+            continue;
+        }
+        let rest = &text[start..];
+        let end = rest
+            .find(|ch: char| ch.is_whitespace() || "\\<>\"'".contains(ch))
+            .unwrap_or(rest.len());
+        let url = rest[..end].trim_end_matches(['.', ',', ';', ':', ')', ']']);
+        from = start + end;
+        if let Some(provider) = url_host(url).and_then(MeetingProvider::from_host) {
+            return Some(MeetingLink {
+                url: Cow::Borrowed(url),
+                provider,
+            });
+        }
+    }
+    None
+}
+
+/// The host of an absolute URL, without any port or user info
+fn url_host(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MeetingProvider;
+    use crate::test_util;
+
+    #[test]
+    fn meeting_link() {
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:1\r\n\
+            CONFERENCE;VALUE=URI;FEATURE=PHONE:tel:+1-555-0100\r\n\
+            CONFERENCE;VALUE=URI;FEATURE=AUDIO,VIDEO;LABEL=Meeting room:https://meet.google.com/abc-defg-hij\r\n\
+            DESCRIPTION:Join at https://example.zoom.us/j/123\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:2\r\n\
+            LOCATION:Room 101\r\n\
+            DESCRIPTION:Agenda: budget.\\nJoin Zoom Meeting (https://us02web.zoom.us/j/85\r\n \
+            7?pwd=abc).\\nhttps://zoom.us/download\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:3\r\n\
+            DESCRIPTION:See https://example.com/zoom.us\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:4\r\n\
+            DESCRIPTION:Join: HTTP://Teams.Microsoft.com/l/meetup-join/1\r\n\
+            END:VEVENT\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+
+        let event = &calendar.events[0];
+        assert_eq!(event.conferences[1].features, ["AUDIO", "VIDEO"]);
+        assert_eq!(event.conferences[1].label.as_deref(), Some("Meeting room"));
+        let link = event.meeting_link().unwrap();
+        assert_eq!(link.url, "https://meet.google.com/abc-defg-hij");
+        assert_eq!(link.provider, MeetingProvider::GoogleMeet);
+
+        let link = calendar.events[1].meeting_link().unwrap();
+        assert_eq!(link.url, "https://us02web.zoom.us/j/857?pwd=abc");
+        assert_eq!(link.provider, MeetingProvider::Zoom);
+
+        assert_eq!(calendar.events[2].meeting_link(), None);
+
+        let link = calendar.events[3].meeting_link().unwrap();
+        assert_eq!(link.url, "HTTP://Teams.Microsoft.com/l/meetup-join/1");
+        assert_eq!(link.provider, MeetingProvider::MicrosoftTeams);
+    }
+}
@@ -0,0 +1,143 @@
+//! Names of the properties and parameters we recognise
+//!
+//! Comparing against these rather than string literals means a misspelt name is a compile
+//! error rather than a property that is silently never parsed.
+
+use crate::types::Name;
+
+macro_rules! names {
+    ($($ident:ident = $name:literal),* $(,)?) => {
+        $(pub(crate) const $ident: Name<'static> = Name::iana($name);)*
+
+        /// Every name in this module, for testing
+        #[cfg(test)]
+        const ALL: &[Name<'static>] = &[$($ident),*];
+    };
+}
+
+/// Extension names, which are given without their `X-` and have no vendor ID
+macro_rules! x_names {
+    ($($ident:ident = $name:literal),* $(,)?) => {
+        $(pub(crate) const $ident: Name<'static> = Name::x($name);)*
+
+        /// Every extension name in this module, for testing
+        #[cfg(test)]
+        const ALL_X: &[Name<'static>] = &[$($ident),*];
+    };
+}
+
+names! {
+    // component delimiters
+    BEGIN = "BEGIN",
+    END = "END",
+
+    // properties
+    ACKNOWLEDGED = "ACKNOWLEDGED",
+    ACTION = "ACTION",
+    ATTACH = "ATTACH",
+    ATTENDEE = "ATTENDEE",
+    CALSCALE = "CALSCALE",
+    CATEGORIES = "CATEGORIES",
+    CLASS = "CLASS",
+    COLOR = "COLOR",
+    COMMENT = "COMMENT",
+    COMPLETED = "COMPLETED",
+    CONFERENCE = "CONFERENCE",
+    CONTACT = "CONTACT",
+    CREATED = "CREATED",
+    DESCRIPTION = "DESCRIPTION",
+    DTEND = "DTEND",
+    DTSTAMP = "DTSTAMP",
+    DTSTART = "DTSTART",
+    DUE = "DUE",
+    DURATION = "DURATION",
+    EXDATE = "EXDATE",
+    GEO = "GEO",
+    LAST_MODIFIED = "LAST-MODIFIED",
+    LOCATION = "LOCATION",
+    LOCATION_TYPE = "LOCATION-TYPE",
+    METHOD = "METHOD",
+    NAME = "NAME",
+    ORGANIZER = "ORGANIZER",
+    PERCENT_COMPLETE = "PERCENT-COMPLETE",
+    PRIORITY = "PRIORITY",
+    PRODID = "PRODID",
+    RDATE = "RDATE",
+    RECURRENCE_ID = "RECURRENCE-ID",
+    RELATED_TO = "RELATED-TO",
+    REPEAT = "REPEAT",
+    RRULE = "RRULE",
+    SEQUENCE = "SEQUENCE",
+    STATUS = "STATUS",
+    SUMMARY = "SUMMARY",
+    TRANSP = "TRANSP",
+    TRIGGER = "TRIGGER",
+    TZID = "TZID",
+    TZNAME = "TZNAME",
+    TZOFFSETFROM = "TZOFFSETFROM",
+    TZOFFSETTO = "TZOFFSETTO",
+    TZURL = "TZURL",
+    UID = "UID",
+    URL = "URL",
+    VERSION = "VERSION",
+
+    // parameters (`TZID` is both)
+    ALTREP = "ALTREP",
+    CN = "CN",
+    CUTYPE = "CUTYPE",
+    DELEGATED_FROM = "DELEGATED-FROM",
+    DELEGATED_TO = "DELEGATED-TO",
+    DIR = "DIR",
+    ENCODING = "ENCODING",
+    FEATURE = "FEATURE",
+    FMTTYPE = "FMTTYPE",
+    LABEL = "LABEL",
+    LANGUAGE = "LANGUAGE",
+    MEMBER = "MEMBER",
+    PARTSTAT = "PARTSTAT",
+    RANGE = "RANGE",
+    RELATED = "RELATED",
+    RELTYPE = "RELTYPE",
+    ROLE = "ROLE",
+    RSVP = "RSVP",
+    SCHEDULE_AGENT = "SCHEDULE-AGENT",
+    SCHEDULE_FORCE_SEND = "SCHEDULE-FORCE-SEND",
+    SCHEDULE_STATUS = "SCHEDULE-STATUS",
+    SENT_BY = "SENT-BY",
+    VALUE = "VALUE",
+}
+
+x_names! {
+    // properties
+    X_APPLE_STRUCTURED_LOCATION = "APPLE-STRUCTURED-LOCATION",
+    X_MICROSOFT_CDO_BUSYSTATUS = "MICROSOFT-CDO-BUSYSTATUS",
+    X_WR_CALNAME = "WR-CALNAME",
+    X_WR_TIMEZONE = "WR-TIMEZONE",
+
+    // parameters
+    X_TITLE = "TITLE",
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Name;
+
+    #[test]
+    fn names_are_iana_tokens() {
+        for name in super::ALL {
+            let Name::Iana(text) = name else {
+                panic!("{name} is not an IANA name");
+            };
+            assert_eq!(&Name::parse(text.clone()).unwrap(), name, "{text}");
+        }
+    }
+
+    #[test]
+    fn x_names_parse() {
+        for name in super::ALL_X {
+            assert!(name.is_extension(), "{name}");
+            let text = name.to_string();
+            assert_eq!(&Name::parse(text.as_str()).unwrap(), name, "{text}");
+        }
+    }
+}
@@ -3,14 +3,15 @@
 //! TODO Cowify
 
 use core::fmt;
-use std::{borrow::Cow, error::Error as StdError};
+use std::{borrow::Cow, error::Error as StdError, iter};
 
-use anyhow::{anyhow, bail};
+use anyhow::{Context, anyhow, bail};
+use mediatype::MediaType;
 use oxilangtag::LanguageTag;
 
 use crate::{
-    Result,
-    parser::helpers::check_param_text,
+    Result, names,
+    parser::helpers::{check_param_text, safe_char},
     types::{Name, VecOne},
     values::{CalendarUserAddress, Uri},
 };
@@ -48,6 +49,24 @@ impl<E> From<E> for SingleParamError<E> {
     }
 }
 
+/// Format a param value, quoting it only if it contains characters that aren't allowed
+/// unquoted.
+///
+/// Values containing `"` or control characters can't be written at all, and are quoted anyway.
+pub(crate) fn fmt_param_value(value: &str) -> impl fmt::Display + '_ {
+    struct ParamValue<'a>(&'a str);
+    impl fmt::Display for ParamValue<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            if self.0.chars().all(|ch| safe_char(ch).is_ok()) {
+                f.write_str(self.0)
+            } else {
+                write!(f, "\"{}\"", self.0)
+            }
+        }
+    }
+    ParamValue(value)
+}
+
 pub(crate) trait ParseParam<'src>: Sized {
     const PARAM_NAME: Name<'static>;
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self>;
@@ -55,11 +74,12 @@ pub(crate) trait ParseParam<'src>: Sized {
 
 // ALTREP
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AlternativeTextRepresentation<'src>(pub Uri<'src>);
 
 impl<'src> ParseParam<'src> for AlternativeTextRepresentation<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("ALTREP");
+    const PARAM_NAME: Name<'static> = names::ALTREP;
 
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let input = input.get_single()?;
@@ -67,6 +87,8 @@ impl<'src> ParseParam<'src> for AlternativeTextRepresentation<'src> {
     }
 }
 
+// URI and cal-address param values must always be quoted (RFC 5545 section 3.2), so these
+// don't use `fmt_param_value`.
 impl fmt::Display for AlternativeTextRepresentation<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}=\"{}\"", Self::PARAM_NAME, self.0)
@@ -79,7 +101,7 @@ impl fmt::Display for AlternativeTextRepresentation<'_> {
 pub(crate) struct CommonName<'src>(pub Cow<'src, str>);
 
 impl<'src> ParseParam<'src> for CommonName<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("CN");
+    const PARAM_NAME: Name<'static> = names::CN;
 
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let input = input.get_single()?;
@@ -89,19 +111,14 @@ impl<'src> ParseParam<'src> for CommonName<'src> {
 
 impl fmt::Display for CommonName<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // Would be faster to always quote.
-        // Same for others below
-        if self.0.contains([':', ';', ',']) {
-            write!(f, "{}=\"{}\"", Self::PARAM_NAME, self.0)
-        } else {
-            write!(f, "{}={}", Self::PARAM_NAME, self.0)
-        }
+        write!(f, "{}={}", Self::PARAM_NAME, fmt_param_value(&self.0))
     }
 }
 
 // CUTYPE
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CalendarUserType<'src> {
     #[default]
     Individual,
@@ -112,7 +129,7 @@ pub enum CalendarUserType<'src> {
     Name(Name<'src>),
 }
 impl<'src> ParseParam<'src> for CalendarUserType<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("CUTYPE");
+    const PARAM_NAME: Name<'static> = names::CUTYPE;
 
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let input = input.get_single()?;
@@ -149,7 +166,7 @@ impl fmt::Display for CalendarUserType<'_> {
 pub(crate) struct Delegators<'src>(pub VecOne<CalendarUserAddress<'src>>);
 
 impl<'src> ParseParam<'src> for Delegators<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("DELEGATED-FROM");
+    const PARAM_NAME: Name<'static> = names::DELEGATED_FROM;
 
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let first = CalendarUserAddress::try_from(input.first)?;
@@ -177,7 +194,7 @@ impl<'src> fmt::Display for Delegators<'src> {
 pub(crate) struct Delegatees<'src>(pub VecOne<CalendarUserAddress<'src>>);
 
 impl<'src> ParseParam<'src> for Delegatees<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("DELEGATED-TO");
+    const PARAM_NAME: Name<'static> = names::DELEGATED_TO;
 
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         Ok(Delegatees(
@@ -198,11 +215,12 @@ impl<'src> fmt::Display for Delegatees<'src> {
 
 // DIR
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DirectoryEntryReference<'src>(pub Uri<'src>);
 
 impl<'src> ParseParam<'src> for DirectoryEntryReference<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("DIR");
+    const PARAM_NAME: Name<'static> = names::DIR;
 
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let value = input.get_single()?;
@@ -259,14 +277,24 @@ impl fmt::Display for Encoding {
 
 // FMTTYPE
 
-#[derive(Debug)]
-pub struct FormatType<'src>(Name<'src>);
+/// The media type of an attachment as written, e.g. `application/pdf`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FormatType<'src>(Cow<'src, str>);
+
+impl FormatType<'_> {
+    /// The media type in lowercase, since they aren't case sensitive
+    pub fn media_type(&self) -> String {
+        self.0.to_ascii_lowercase()
+    }
+}
 
 impl<'src> ParseParam<'src> for FormatType<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("FMTTYPE");
+    const PARAM_NAME: Name<'static> = names::FMTTYPE;
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let input = input.get_single()?;
-        Ok(FormatType(Name::parse(input)?))
+        MediaType::parse(&input).with_context(|| format!("invalid media type `{input}`"))?;
+        Ok(FormatType(input))
     }
 }
 
@@ -317,11 +345,11 @@ impl<'src> fmt::Display for FreeBusyTimeType<'src> {
 
 // LANGUAGE
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Language<'src>(pub LanguageTag<Cow<'src, str>>);
 
 impl<'src> ParseParam<'src> for Language<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("LANGUAGE");
+    const PARAM_NAME: Name<'static> = names::LANGUAGE;
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let value = input.get_single()?;
         Ok(Language(LanguageTag::parse(value)?))
@@ -334,12 +362,30 @@ impl<'src> fmt::Display for Language<'src> {
     }
 }
 
+/// Serialized as the bare language tag
+#[cfg(feature = "serde")]
+impl serde::Serialize for Language<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Language<'_> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let input = <String as serde::Deserialize>::deserialize(deserializer)?;
+        LanguageTag::parse(Cow::Owned(input))
+            .map(Language)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 // MEMBER
 
 pub(crate) struct GroupOrListMember<'src>(pub VecOne<CalendarUserAddress<'src>>);
 
 impl<'src> ParseParam<'src> for GroupOrListMember<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("MEMBER");
+    const PARAM_NAME: Name<'static> = names::MEMBER;
     fn parse_value(input: VecOne<Cow<'src, str>>) -> anyhow::Result<Self> {
         Ok(GroupOrListMember(
             input.map(|v| Ok(CalendarUserAddress::try_from(v)?))?,
@@ -365,7 +411,8 @@ impl<'src> fmt::Display for GroupOrListMember<'src> {
 /// Expected one of 'needs-action', 'accepted', 'declined' or 'delegated' for event,
 /// any for todo, and one of 'needs-action', 'accepted', 'declined' for participant
 /// status, but any text that would be a valid [`Name`] is valid.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParticipationStatus<'src> {
     NeedsAction,
     Accepted,
@@ -378,7 +425,7 @@ pub enum ParticipationStatus<'src> {
 }
 
 impl<'src> ParseParam<'src> for ParticipationStatus<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("PARTSTAT");
+    const PARAM_NAME: Name<'static> = names::PARTSTAT;
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let input = input.get_single()?;
         Ok(match &*input {
@@ -406,7 +453,7 @@ impl<'src> fmt::Display for ParticipationStatus<'src> {
         match self {
             ParticipationStatus::NeedsAction => f.write_str("NEEDS-ACTION"),
             ParticipationStatus::Accepted => f.write_str("ACCEPTED"),
-            ParticipationStatus::Declined => f.write_str("DELCINED"),
+            ParticipationStatus::Declined => f.write_str("DECLINED"),
             ParticipationStatus::Tentative => f.write_str("TENTATIVE"),
             ParticipationStatus::Delegated => f.write_str("DELEGATED"),
             ParticipationStatus::Completed => f.write_str("COMPLETED"),
@@ -418,14 +465,15 @@ impl<'src> fmt::Display for ParticipationStatus<'src> {
 
 // RANGE
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Range {
     ThisAndPrior,
     ThisAndFuture,
 }
 
 impl<'src> ParseParam<'src> for Range {
-    const PARAM_NAME: Name<'static> = Name::iana("RANGE");
+    const PARAM_NAME: Name<'static> = names::RANGE;
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let input = input.get_single()?;
         Ok(match &*input {
@@ -452,27 +500,28 @@ impl fmt::Display for Range {
 
 // RELATED
 
+/// Whether an alarm [trigger](crate::AlarmTrigger) is relative to the start or the end of
+/// its component
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlarmTriggerRelationship {
     Start,
     End,
 }
 
-impl AlarmTriggerRelationship {
-    pub const PARAM_NAME: &'static str = "RELATED";
-    pub fn parse_value(
-        first: &str,
-        rest: &[&str],
-    ) -> Result<Self, SingleParamError<anyhow::Error>> {
-        if !rest.is_empty() {
-            return Err(SingleParamError::SingleParam);
-        }
-        Ok(match first {
+impl<'src> ParseParam<'src> for AlarmTriggerRelationship {
+    const PARAM_NAME: Name<'static> = names::RELATED;
+    fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
+        let input = input.get_single()?;
+        Ok(match &*input {
             "START" => Self::Start,
             "END" => Self::End,
-            other => return Err(anyhow!("expected `START` or `END`, found {other}").into()),
+            other => bail!("expected `START` or `END`, found {other}"),
         })
     }
+}
 
+impl AlarmTriggerRelationship {
     pub fn as_str(&self) -> &'static str {
         match self {
             AlarmTriggerRelationship::Start => "START",
@@ -495,27 +544,27 @@ impl fmt::Display for AlarmTriggerRelationship {
 
 // RELTYPE
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RelationshipType<'src> {
     Parent,
     Child,
     Sibling,
+    /// The component is a snoozed copy of the related alarm (RFC 9074)
+    Snooze,
     Name(Name<'src>),
 }
 
-impl<'src> RelationshipType<'src> {
-    pub const PARAM_NAME: &'static str = "RELTYPE";
-    pub fn parse_value(
-        first: &'src str,
-        rest: &[&'src str],
-    ) -> Result<Self, SingleParamError<anyhow::Error>> {
-        if !rest.is_empty() {
-            return Err(SingleParamError::SingleParam);
-        }
-        Ok(match first {
+impl<'src> ParseParam<'src> for RelationshipType<'src> {
+    const PARAM_NAME: Name<'static> = names::RELTYPE;
+    fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
+        let input = input.get_single()?;
+        Ok(match &*input {
             "PARENT" => Self::Parent,
             "CHILD" => Self::Child,
             "SIBLING" => Self::Sibling,
-            other => Self::Name(Name::parse(Cow::Borrowed(other))?),
+            "SNOOZE" => Self::Snooze,
+            _ => Self::Name(Name::parse(input)?),
         })
     }
 }
@@ -533,6 +582,7 @@ impl<'src> fmt::Display for RelationshipType<'src> {
             RelationshipType::Parent => f.write_str("PARENT"),
             RelationshipType::Child => f.write_str("CHILD"),
             RelationshipType::Sibling => f.write_str("SIBLING"),
+            RelationshipType::Snooze => f.write_str("SNOOZE"),
             RelationshipType::Name(name) => fmt::Display::fmt(name, f),
         }
     }
@@ -542,7 +592,8 @@ impl<'src> fmt::Display for RelationshipType<'src> {
 
 /// Specifies the participation role for the calendar user specified
 /// by the property in the group schedule calendar component.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParticipationRole<'src> {
     /// Indicates the chair of the calendar entry
     Chair,
@@ -557,7 +608,7 @@ pub enum ParticipationRole<'src> {
 }
 
 impl<'src> ParseParam<'src> for ParticipationRole<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("ROLE");
+    const PARAM_NAME: Name<'static> = names::ROLE;
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let input = input.get_single()?;
         Ok(match &*input {
@@ -593,14 +644,15 @@ impl<'src> fmt::Display for ParticipationRole<'src> {
 
 /// To specify whether there is an expectation of a favor of a reply from the
 /// calendar user specified by the property value.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RsvpExpectation {
     True,
     False,
 }
 
 impl<'src> ParseParam<'src> for RsvpExpectation {
-    const PARAM_NAME: Name<'static> = Name::iana("RSVP");
+    const PARAM_NAME: Name<'static> = names::RSVP;
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let input = input.get_single()?;
         Ok(match &*input {
@@ -632,13 +684,139 @@ impl fmt::Display for RsvpExpectation {
     }
 }
 
+// SCHEDULE-AGENT
+
+/// Who is expected to deliver scheduling messages to the calendar user (RFC 6638)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScheduleAgent<'src> {
+    /// The server (the default)
+    Server,
+    /// The client
+    Client,
+    /// Nobody
+    None,
+    /// Other iana or experimental agent
+    Name(Name<'src>),
+}
+
+impl<'src> ParseParam<'src> for ScheduleAgent<'src> {
+    const PARAM_NAME: Name<'static> = names::SCHEDULE_AGENT;
+    fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
+        let input = input.get_single()?;
+        Ok(match &*input {
+            "SERVER" => Self::Server,
+            "CLIENT" => Self::Client,
+            "NONE" => Self::None,
+            _ => Self::Name(Name::parse(input)?),
+        })
+    }
+}
+
+impl<'src> fmt::Display for ScheduleAgent<'src> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}=", Self::PARAM_NAME)?;
+        match self {
+            ScheduleAgent::Server => f.write_str("SERVER"),
+            ScheduleAgent::Client => f.write_str("CLIENT"),
+            ScheduleAgent::None => f.write_str("NONE"),
+            ScheduleAgent::Name(name) => fmt::Display::fmt(name, f),
+        }
+    }
+}
+
+// SCHEDULE-FORCE-SEND
+
+/// Asks the server to send a scheduling message even when it otherwise wouldn't (RFC 6638)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScheduleForceSend<'src> {
+    Request,
+    Reply,
+    /// Other iana or experimental value
+    Name(Name<'src>),
+}
+
+impl<'src> ParseParam<'src> for ScheduleForceSend<'src> {
+    const PARAM_NAME: Name<'static> = names::SCHEDULE_FORCE_SEND;
+    fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
+        let input = input.get_single()?;
+        Ok(match &*input {
+            "REQUEST" => Self::Request,
+            "REPLY" => Self::Reply,
+            _ => Self::Name(Name::parse(input)?),
+        })
+    }
+}
+
+impl<'src> fmt::Display for ScheduleForceSend<'src> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}=", Self::PARAM_NAME)?;
+        match self {
+            ScheduleForceSend::Request => f.write_str("REQUEST"),
+            ScheduleForceSend::Reply => f.write_str("REPLY"),
+            ScheduleForceSend::Name(name) => fmt::Display::fmt(name, f),
+        }
+    }
+}
+
+// SCHEDULE-STATUS
+
+/// The status codes (e.g. `2.0`) of the last scheduling messages sent to the calendar user
+/// (RFC 6638)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScheduleStatus<'src>(pub VecOne<Cow<'src, str>>);
+
+impl<'src> ParseParam<'src> for ScheduleStatus<'src> {
+    const PARAM_NAME: Name<'static> = names::SCHEDULE_STATUS;
+    fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
+        // the list is quoted as a whole, so may arrive as one value
+        let mut codes = iter::once(input.first)
+            .chain(input.rest)
+            .flat_map(|value| match value {
+                Cow::Borrowed(value) => value.split(',').map(Cow::Borrowed).collect::<Vec<_>>(),
+                Cow::Owned(value) => value
+                    .split(',')
+                    .map(|code| Cow::Owned(code.to_owned()))
+                    .collect(),
+            });
+        let first = codes.next().expect("at least one value");
+        let codes = VecOne::from_parts(first, codes.collect());
+        for code in &codes {
+            check_status_code(code)?;
+        }
+        Ok(ScheduleStatus(codes))
+    }
+}
+
+/// Status codes are 2 or 3 numbers separated by `.`
+fn check_status_code(code: &str) -> Result<()> {
+    let parts: Vec<_> = code.split('.').collect();
+    if !(2..=3).contains(&parts.len())
+        || parts
+            .iter()
+            .any(|part| part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()))
+    {
+        bail!("invalid status code `{code}`");
+    }
+    Ok(())
+}
+
+impl<'src> fmt::Display for ScheduleStatus<'src> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}=\"{}\"", Self::PARAM_NAME, self.0.display())
+    }
+}
+
 // SENT-BY
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SentBy<'src>(pub CalendarUserAddress<'src>);
 
 impl<'src> ParseParam<'src> for SentBy<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("SENT-BY");
+    const PARAM_NAME: Name<'static> = names::SENT_BY;
 
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let value = input.get_single()?;
@@ -655,14 +833,15 @@ impl<'src> fmt::Display for SentBy<'src> {
 // TZID
 
 /// Timezone is not checked against database, just validated.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TimeZoneIdentifier<'src> {
     prefix: bool,
     value: Cow<'src, str>,
 }
 
 impl<'src> ParseParam<'src> for TimeZoneIdentifier<'src> {
-    const PARAM_NAME: Name<'static> = Name::iana("SENT-BY");
+    const PARAM_NAME: Name<'static> = names::TZID;
     fn parse_value(input: VecOne<Cow<'src, str>>) -> Result<Self> {
         let input = input.get_single()?;
         let prefix = input.starts_with('/');
@@ -686,6 +865,16 @@ impl<'src> ParseParam<'src> for TimeZoneIdentifier<'src> {
 }
 
 impl<'src> TimeZoneIdentifier<'src> {
+    /// Refer to the timezone `id`. A leading `/` marks a globally unique id.
+    pub fn new(id: impl Into<Cow<'src, str>>) -> Result<Self> {
+        Self::parse_value(VecOne::new(id.into()))
+    }
+
+    /// The timezone id, without any leading `/`
+    pub fn id(&self) -> &str {
+        &self.value
+    }
+
     pub fn fmt_value(&self) -> impl fmt::Display {
         struct FmtValue<'a>(&'a TimeZoneIdentifier<'a>);
         impl<'a> fmt::Display for FmtValue<'a> {
@@ -729,7 +918,7 @@ pub enum Value<'src> {
 }
 
 impl<'src> Value<'src> {
-    pub const PARAM_NAME: &'static str = "ROLE";
+    pub const PARAM_NAME: &'static str = "VALUE";
     pub fn parse_value(
         first: &'src str,
         rest: &[&'src str],
@@ -779,3 +968,26 @@ impl<'src> fmt::Display for Value<'src> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::CommonName;
+    use crate::parser::Line;
+
+    #[test]
+    fn common_name_quoting() {
+        let quoted = CommonName(Cow::Borrowed("Doe, Jane"));
+        assert_eq!(quoted.to_string(), "CN=\"Doe, Jane\"");
+        let clean = CommonName(Cow::Borrowed("Jane Doe"));
+        assert_eq!(clean.to_string(), "CN=Jane Doe");
+
+        // both re-parse to the original value
+        for cn in [quoted, clean] {
+            let line = Line::parse(format!("ATTENDEE;{cn}:mailto:jane@example.com")).unwrap();
+            let (_, values) = line.params_iter().next().unwrap();
+            assert_eq!(values.first, cn.0);
+        }
+    }
+}
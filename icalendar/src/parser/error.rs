@@ -1,7 +1,79 @@
-use std::num::ParseIntError;
+use std::{error::Error as StdError, fmt, num::ParseIntError};
 
 use thiserror::Error;
 
+/// Why parsing a calendar failed, and where.
+#[derive(Debug)]
+pub struct ParseError {
+    /// The line of the input the error was found on, counting from 1
+    pub line: usize,
+    /// The byte offset in the input of the start of that line
+    pub offset: usize,
+    /// How many calendars were read completely before the error, e.g. `1` for an error in
+    /// the second calendar of the input
+    pub calendars: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl StdError for ParseError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.kind.source()
+    }
+}
+
+/// What went wrong, see [`ParseError`].
+///
+/// Errors while parsing are reported as [`anyhow::Error`]s internally, and converted to this
+/// at the public API.
+#[derive(Debug, Error)]
+pub enum ParseErrorKind {
+    /// A required property is missing, e.g. the `UID` of a `VEVENT`
+    #[error("missing {property} on {component}")]
+    MissingRequired { component: String, property: String },
+    /// A property that can appear at most once was repeated
+    #[error("expected at most 1 {property}, found at least 2")]
+    Duplicate { property: String },
+    /// A component that isn't supported, when they are errors (see
+    /// [`UnknownComponents::Error`](crate::UnknownComponents::Error))
+    #[error("unsupported component {component}")]
+    Unsupported { component: String },
+    /// Anything else: a malformed line, an invalid value, a component that isn't closed...
+    #[error(transparent)]
+    BadValue(Box<dyn StdError + Send + Sync>),
+}
+
+impl ParseErrorKind {
+    pub(crate) fn missing(component: &str, property: &str) -> anyhow::Error {
+        ParseErrorKind::MissingRequired {
+            component: component.to_string(),
+            property: property.to_string(),
+        }
+        .into()
+    }
+
+    pub(crate) fn duplicate(property: impl ToString) -> anyhow::Error {
+        ParseErrorKind::Duplicate {
+            property: property.to_string(),
+        }
+        .into()
+    }
+}
+
+impl From<anyhow::Error> for ParseErrorKind {
+    /// Unwrap errors that were raised as a `ParseErrorKind`, the others are a `BadValue`
+    fn from(error: anyhow::Error) -> Self {
+        error
+            .downcast()
+            .unwrap_or_else(|error| ParseErrorKind::BadValue(error.into()))
+    }
+}
+
 /// Couldn't parse the input as an iCalendar document.
 // This error is only built if the parse failed (unrecoverable error)
 // so we are less bothered about if some variants are large
@@ -0,0 +1,169 @@
+//! Parsing just enough of each event to index it
+
+use std::borrow::Cow;
+
+use anyhow::{Context, bail};
+
+use crate::{
+    CalScale, Result, names,
+    parser::{
+        Lexer, ParseErrorKind,
+        helpers::{parse_start, unescape_text},
+    },
+    types::{DateOrDateTime, Name},
+};
+
+/// The identifying properties of an event, see [`parse_headers`](crate::parse_headers)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventHeader<'src> {
+    pub uid: Cow<'src, str>,
+    pub start: Option<DateOrDateTime>,
+    /// The `SUMMARY` text, as in [`Event::summary`](crate::Event::summary)
+    pub summary: Option<Cow<'src, str>>,
+}
+
+impl<'src> EventHeader<'src> {
+    /// Assumes `BEGIN:VEVENT` was already parsed
+    fn parse(parser: &mut Lexer<'src>) -> Result<Self> {
+        let mut uid = None;
+        let mut start = None;
+        let mut summary = None;
+        while let Some(next) = parser.take_next()? {
+            if next.name == names::END {
+                if next.value != "VEVENT" {
+                    bail!("expected VEVENT, found {}", next.value);
+                }
+                let Some(uid) = uid else {
+                    return Err(ParseErrorKind::missing("VEVENT", "UID"));
+                };
+                return Ok(EventHeader {
+                    uid,
+                    start,
+                    summary,
+                });
+            } else if next.name == names::UID {
+                set_once(&mut uid, next.value, &next.name)?;
+            } else if next.name == names::DTSTART {
+                let value =
+                    parse_start(&next.value, parser.is_gregorian()).context("invalid DTSTART")?;
+                set_once(&mut start, value, &next.name)?;
+            } else if next.name == names::SUMMARY {
+                set_once(&mut summary, unescape_text(next.value), &next.name)?;
+            } else if next.name == names::BEGIN {
+                parser.skip_current()?;
+            }
+            // everything else is ignored without being looked at
+        }
+        Err(parser.unexpected_eof())
+    }
+}
+
+fn set_once<T>(slot: &mut Option<T>, value: T, name: &Name<'_>) -> Result {
+    if slot.is_some() {
+        return Err(ParseErrorKind::duplicate(name));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// The headers of the events of every calendar in the input, in order
+pub(crate) fn parse_headers<'src>(parser: &mut Lexer<'src>) -> Result<Vec<EventHeader<'src>>> {
+    let mut output = vec![];
+    while let Some(begin) = parser.take_next()? {
+        if !(begin.name == names::BEGIN && begin.value == "VCALENDAR") {
+            bail!("expected `BEGIN:VCALENDAR`");
+        }
+        parser.set_gregorian(true);
+        loop {
+            let Some(next) = parser.take_next()? else {
+                return Err(parser.unexpected_eof());
+            };
+            if next.name == names::END {
+                if next.value != "VCALENDAR" {
+                    bail!("expected VCALENDAR, found {}", next.value);
+                }
+                break;
+            } else if next.name == names::CALSCALE {
+                let cal_scale = super::parse_cal_scale(next)?;
+                parser.set_gregorian(cal_scale == CalScale::Gregorian);
+            } else if next.name == names::BEGIN {
+                if next.value == "VEVENT" {
+                    output.push(EventHeader::parse(parser)?);
+                } else {
+                    parser.skip_current()?;
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{
+        test_util,
+        types::{Date, DateOrDateTime},
+    };
+
+    #[test]
+    fn headers_match_full_parse() {
+        let input = include_str!("../../examples/calendars.ics");
+        let headers = crate::parse_headers(input).unwrap();
+        let events: Vec<_> = crate::parse(input)
+            .unwrap()
+            .into_iter()
+            .flat_map(|calendar| calendar.events)
+            .collect();
+        assert_eq!(headers.len(), events.len());
+        for (header, event) in headers.iter().zip(&events) {
+            assert_eq!(header.uid, event.uid);
+            assert_eq!(header.start, event.start);
+            assert_eq!(
+                header.summary,
+                event.summary.as_ref().map(|summary| summary.text.clone())
+            );
+        }
+        assert_eq!(headers[1].uid, "holiday@example.com");
+        assert_eq!(
+            headers[1].start,
+            Some(DateOrDateTime::Date(Date {
+                full_year: 2024,
+                month: 1,
+                day: 3
+            }))
+        );
+        assert_eq!(headers[1].summary, Some(Cow::Borrowed("Team holiday")));
+
+        // text is unescaped the same way
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:escaped@example.com\r\n\
+            DTSTART:20240101T090000Z\r\n\
+            SUMMARY:Lunch\\, dinner\\; drinks\\nat 7\r\n\
+            END:VEVENT\r\n",
+        );
+        let header = crate::parse_headers(&input).unwrap().remove(0);
+        let event = crate::parse(&input).unwrap().remove(0).events.remove(0);
+        assert_eq!(
+            header.summary.as_deref(),
+            Some("Lunch, dinner; drinks\nat 7")
+        );
+        assert_eq!(header.summary, event.summary.map(|summary| summary.text));
+
+        // both reject a malformed start
+        let input = input.replace("20240101T090000Z", "20240101T090000Zjunk");
+        assert!(crate::parse(&input).is_err());
+        let error = crate::parse_headers(&input).unwrap_err();
+        assert_eq!(error.to_string(), "line 6: invalid DTSTART");
+
+        // and keep dates of other calendar scales as written
+        let input = input.replace("VERSION:2.0\r\n", "VERSION:2.0\r\nCALSCALE:CHINESE\r\n");
+        let header = crate::parse_headers(&input).unwrap().remove(0);
+        let event = crate::parse(&input).unwrap().remove(0).events.remove(0);
+        assert_eq!(header.start, event.start);
+        assert_eq!(header.start.unwrap().date(), None);
+    }
+}
@@ -6,8 +6,8 @@ use anyhow::{anyhow, bail};
 use thiserror::Error;
 
 use crate::{
-    Result,
-    parser::{ParserError, VALUE_PARAM, line::Line},
+    Result, names,
+    parser::{ParserError, line::Line},
     types::{Date, DateOrDateTime, DateTime, VecOne},
 };
 
@@ -27,6 +27,34 @@ pub fn check_param_text<'src>(input: &'src str) -> Result<()> {
     Ok(())
 }
 
+/// Undo the escapes of a single `TEXT` value (`\\`, `\;`, `\,` and `\n`). Any other
+/// backslash is kept, as are commas and semicolons that should have been escaped.
+pub fn unescape_text(input: Cow<'_, str>) -> Cow<'_, str> {
+    if !input.contains('\\') {
+        return input;
+    }
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            output.push(ch);
+            continue;
+        }
+        match chars.peek().copied() {
+            Some(next @ ('\\' | ';' | ',')) => {
+                chars.next();
+                output.push(next);
+            }
+            Some('n' | 'N') => {
+                chars.next();
+                output.push('\n');
+            }
+            _ => output.push('\\'),
+        }
+    }
+    Cow::Owned(output)
+}
+
 /// Returns `input` without the start and end quotes
 pub fn quoted_string(input: Cow<'_, str>) -> anyhow::Result<Cow<'_, str>> {
     let mut iter = input.chars();
@@ -118,58 +146,56 @@ pub fn split_once<'a>(input: Cow<'a, str>, delim: char) -> (Cow<'a, str>, Cow<'a
 /// found.
 ///
 /// This function will only split outside quoted strings (i.e. when the
-/// number of seen quotes is odd).
+/// number of seen quotes is even). Fails if a quoted string isn't closed before
+/// `test_ch` or the end of the input, since otherwise the rest of the input would
+/// silently be taken as part of the string.
 pub fn split_once_outside_quotes<'a>(
     input: Cow<'a, str>,
     test_ch: char,
-) -> (Cow<'a, str>, Cow<'a, str>) {
-    match input {
-        Cow::Borrowed(input) => split_once_outside_quotes_borrowed(input, test_ch),
-        Cow::Owned(input) => split_once_outside_quotes_owned(input, test_ch),
-    }
+) -> Result<(Cow<'a, str>, Cow<'a, str>)> {
+    let Some(split_idx) = find_outside_quotes(&input, test_ch)? else {
+        return Ok((input, Cow::Borrowed("")));
+    };
+    Ok(split_around(input, split_idx, test_ch))
 }
 
-fn split_once_outside_quotes_borrowed<'a>(
-    input: &'a str,
-    test_ch: char,
+/// Split around the `ch` at byte index `split_idx`
+pub fn split_around<'a>(
+    input: Cow<'a, str>,
+    split_idx: usize,
+    ch: char,
 ) -> (Cow<'a, str>, Cow<'a, str>) {
-    let mut chars = input.chars();
-    let mut inside_quote = false;
-    while let Some(ch) = chars.next() {
-        if ch == '"' {
-            inside_quote = !inside_quote;
-        } else if ch == test_ch && !inside_quote {
-            let rest = chars.as_str();
-            let first = &input[..input.len() - rest.len() - test_ch.len_utf8()];
-            return (Cow::Borrowed(first), Cow::Borrowed(rest));
+    match input {
+        Cow::Borrowed(input) => (
+            Cow::Borrowed(&input[..split_idx]),
+            Cow::Borrowed(&input[split_idx + ch.len_utf8()..]),
+        ),
+        Cow::Owned(mut input) => {
+            let after = input.split_off(split_idx + ch.len_utf8());
+            // remove `ch`
+            input.pop();
+            (Cow::Owned(input), Cow::Owned(after))
         }
     }
-    (Cow::Borrowed(input), Cow::Borrowed(""))
 }
 
-fn split_once_outside_quotes_owned(
-    mut input: String,
-    test_ch: char,
-) -> (Cow<'static, str>, Cow<'static, str>) {
-    let mut chars = input.chars();
-    let mut inside_quote = false;
-    let mut first_end_idx = None;
-    while let Some(ch) = chars.next() {
+/// The byte index of the first `test_ch` outside a quoted string, if there is one
+pub fn find_outside_quotes(input: &str, test_ch: char) -> Result<Option<usize>> {
+    let mut quote_start = None;
+    for (idx, ch) in input.char_indices() {
         if ch == '"' {
-            inside_quote = !inside_quote;
-        } else if ch == test_ch && !inside_quote {
-            let rest = chars.as_str();
-            first_end_idx = Some(input.len() - rest.len());
-            break;
+            quote_start = match quote_start {
+                Some(_) => None,
+                None => Some(idx),
+            };
+        } else if ch == test_ch && quote_start.is_none() {
+            return Ok(Some(idx));
         }
     }
-    let Some(first_end_idx) = first_end_idx else {
-        return (Cow::Owned(input), Cow::Borrowed(""));
-    };
-    let after = input.split_off(first_end_idx);
-    // remove `test_ch`
-    input.pop();
-    (Cow::Owned(input), Cow::Owned(after))
+    if let Some(start) = quote_start {
+        bail!("unterminated quoted string `{}`", &input[start..]);
+    }
+    Ok(None)
 }
 
 pub fn pop_front_bytes(input: &mut String, chars: usize) {
@@ -198,6 +224,18 @@ pub fn check_iana_token(input: &str) -> Result {
     }
 }
 
+/// Like [`check_iana_token`], but only accepting the ASCII characters RFC 5545 allows
+pub fn check_ascii_iana_token(input: &str) -> Result {
+    if input
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+    {
+        Ok(())
+    } else {
+        Err(anyhow!("{input} is not a valid name"))
+    }
+}
+
 /// 1 or 2 digit positive integer
 ///
 /// min and max are inclusive
@@ -312,12 +350,18 @@ pub fn opt_vec_one_to_vec<T>(input: Option<VecOne<T>>) -> Vec<T> {
     v
 }
 
+/// Parse all of a `DTSTART` value. Values of calendars that aren't `gregorian` are kept as
+/// written.
+pub fn parse_start(value: &str, gregorian: bool) -> Result<DateOrDateTime> {
+    all_consuming(value, |input| DateOrDateTime::parse_in(input, gregorian))
+}
+
 /// Expect a datetime value, unless there is the parameter VALUE=DATE, in which
-/// case date instead
-pub fn parse_date_or_datetime(input: &mut Line<'_>) -> Result<DateOrDateTime> {
+/// case date instead. Values of calendars that aren't `gregorian` are kept as written.
+pub fn parse_date_or_datetime(input: &mut Line<'_>, gregorian: bool) -> Result<DateOrDateTime> {
     let is_datetime = input
         .params
-        .take(&VALUE_PARAM)
+        .take(&names::VALUE)
         .map(|v| -> Result<_> {
             let v = v.get_single()?;
             Ok(match &*v {
@@ -329,21 +373,26 @@ pub fn parse_date_or_datetime(input: &mut Line<'_>) -> Result<DateOrDateTime> {
         .transpose()?
         .unwrap_or(true);
 
-    Ok(if is_datetime {
-        let (_, datetime) = DateTime::parse(&*input.value)?;
+    Ok(if !gregorian {
+        raw_date_or_datetime(&input.value, is_datetime)?
+    } else if is_datetime {
+        let (_, datetime) = DateTime::parse(&input.value)?;
         DateOrDateTime::DateTime(datetime)
     } else {
-        let (_, date) = Date::parse(&*input.value)?;
+        let (_, date) = Date::parse(&input.value)?;
         DateOrDateTime::Date(date)
     })
 }
 
 /// Expect a datetime value, unless there is the parameter VALUE=DATE, in which
-/// case date instead
-pub fn parse_date_or_datetime_list(input: &mut Line<'_>) -> Result<VecOne<DateOrDateTime>> {
+/// case date instead. Values of calendars that aren't `gregorian` are kept as written.
+pub fn parse_date_or_datetime_list(
+    input: &mut Line<'_>,
+    gregorian: bool,
+) -> Result<VecOne<DateOrDateTime>> {
     let is_datetime = input
         .params
-        .take(&VALUE_PARAM)
+        .take(&names::VALUE)
         .map(|v| -> Result<_> {
             let v = v.get_single()?;
             Ok(match &*v {
@@ -356,7 +405,16 @@ pub fn parse_date_or_datetime_list(input: &mut Line<'_>) -> Result<VecOne<DateOr
         .unwrap_or(true);
 
     let input = &*input.value;
-    Ok(if is_datetime {
+    Ok(if !gregorian {
+        let mut values = input.split(',');
+        // `split` always returns at least one item
+        let first = raw_date_or_datetime(values.next().unwrap(), is_datetime)?;
+        let mut output = VecOne::new(first);
+        for value in values {
+            output.push(raw_date_or_datetime(value, is_datetime)?);
+        }
+        output
+    } else if is_datetime {
         let (mut input, datetime) = DateTime::parse(input)?;
         let mut output = VecOne::new(DateOrDateTime::DateTime(datetime));
         while let Ok((i, _)) = tag(",")(input) {
@@ -377,6 +435,16 @@ pub fn parse_date_or_datetime_list(input: &mut Line<'_>) -> Result<VecOne<DateOr
     })
 }
 
+/// A value of a non-Gregorian calendar, only checking it is a date-time if `is_datetime` or
+/// a date otherwise
+fn raw_date_or_datetime(value: &str, is_datetime: bool) -> Result<DateOrDateTime> {
+    if value.contains('T') != is_datetime {
+        let expected = if is_datetime { "DATE-TIME" } else { "DATE" };
+        bail!("expected a {expected} value, found `{value}`");
+    }
+    Ok(DateOrDateTime::Raw(value.to_owned().into()))
+}
+
 #[derive(Debug, Error)]
 #[error("expected `{tag}`")]
 pub struct TagError {
@@ -393,6 +461,18 @@ pub fn tag(tag: &'static str) -> impl FnMut(&str) -> Result<(&str, &str), TagErr
     }
 }
 
+/// Run `parser`, and error if it doesn't consume all the input
+pub fn all_consuming<'a, T>(
+    input: &'a str,
+    parser: impl FnOnce(&'a str) -> Result<(&'a str, T)>,
+) -> Result<T> {
+    let (rest, value) = parser(input)?;
+    if !rest.is_empty() {
+        bail!("unexpected trailing characters `{rest}`");
+    }
+    Ok(value)
+}
+
 /// Returns Ok(None) if no digits, Err if overflow
 pub fn parse_u32(input: &str) -> Result<Option<(&str, u32)>> {
     fn int(ch: char) -> u32 {
@@ -418,10 +498,39 @@ fn take_digit(input: &str) -> Option<(&str, char)> {
     Some((iter.as_str(), ch))
 }
 
+/// `PERCENT-COMPLETE` is an integer between 0 and 100 inclusive
+pub fn parse_percent_complete(input: &str) -> Result<u8> {
+    let Ok(value) = input.parse::<u8>() else {
+        bail!("expected PERCENT-COMPLETE between 0 and 100, found `{input}`");
+    };
+    if value > 100 {
+        bail!("expected PERCENT-COMPLETE between 0 and 100, found {value}");
+    }
+    Ok(value)
+}
+
+/// Some properties (e.g. `CREATED`, `COMPLETED`) must be specified in UTC
+pub fn check_utc(label: &str, value: &DateTime) -> Result {
+    if !value.time.utc {
+        bail!("expected UTC time for {label}, found `{value}`");
+    }
+    Ok(())
+}
+
+/// `DUE` and `DURATION` cannot both be specified
+pub fn check_due_duration(has_due: bool, has_duration: bool) -> Result {
+    if has_due && has_duration {
+        bail!("expected 0..=1 of DUE | DURATION, found 2");
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::borrow::Cow;
 
+    use crate::types::{Date, DateTime, Time};
+
     #[test]
     fn split_once() {
         let input = "first;second;";
@@ -467,23 +576,41 @@ mod tests {
     fn split_once_outside_quotes() {
         let input = "first;second;";
 
-        let (first, rest) = super::split_once_outside_quotes(Cow::Borrowed(input), ';');
+        let (first, rest) = super::split_once_outside_quotes(Cow::Borrowed(input), ';').unwrap();
         assert_eq!(first, "first");
         assert_eq!(rest, "second;");
 
-        let (first, rest) = super::split_once_outside_quotes(Cow::Owned(input.to_string()), ';');
+        let (first, rest) =
+            super::split_once_outside_quotes(Cow::Owned(input.to_string()), ';').unwrap();
         assert_eq!(first, "first");
         assert_eq!(rest, "second;");
 
         let input = "first second";
 
-        let (first, rest) = super::split_once_outside_quotes(Cow::Borrowed(input), ';');
+        let (first, rest) = super::split_once_outside_quotes(Cow::Borrowed(input), ';').unwrap();
         assert_eq!(first, "first second");
         assert_eq!(rest, "");
 
-        let (first, rest) = super::split_once_outside_quotes(Cow::Owned(input.to_string()), ';');
+        let (first, rest) =
+            super::split_once_outside_quotes(Cow::Owned(input.to_string()), ';').unwrap();
         assert_eq!(first, "first second");
         assert_eq!(rest, "");
+
+        let input = "a=\"b;c\";d";
+
+        let (first, rest) = super::split_once_outside_quotes(Cow::Borrowed(input), ';').unwrap();
+        assert_eq!(first, "a=\"b;c\"");
+        assert_eq!(rest, "d");
+
+        let (first, rest) =
+            super::split_once_outside_quotes(Cow::Owned(input.to_string()), ';').unwrap();
+        assert_eq!(first, "a=\"b;c\"");
+        assert_eq!(rest, "d");
+
+        let input = "a=\"b;c";
+        let error = super::split_once_outside_quotes(Cow::Borrowed(input), ';').unwrap_err();
+        assert_eq!(error.to_string(), "unterminated quoted string `\"b;c`");
+        assert!(super::split_once_outside_quotes(Cow::Owned(input.to_string()), ';').is_err());
     }
 
     #[test]
@@ -492,4 +619,48 @@ mod tests {
         super::pop_front_bytes(&mut input, 3);
         assert_eq!(input, "test");
     }
+
+    #[test]
+    fn percent_complete() {
+        assert_eq!(super::parse_percent_complete("0").unwrap(), 0);
+        assert_eq!(super::parse_percent_complete("39").unwrap(), 39);
+        assert_eq!(super::parse_percent_complete("100").unwrap(), 100);
+        assert!(super::parse_percent_complete("101").is_err());
+        assert!(super::parse_percent_complete("256").is_err());
+        assert!(super::parse_percent_complete("-1").is_err());
+        assert!(super::parse_percent_complete("").is_err());
+    }
+
+    #[test]
+    fn check_utc() {
+        let mut datetime = DateTime {
+            date: Date {
+                full_year: 2007,
+                month: 4,
+                day: 7,
+            },
+            time: Time {
+                hour: 0,
+                minute: 0,
+                second: 0,
+                utc: true,
+            },
+        };
+        assert!(super::check_utc("COMPLETED", &datetime).is_ok());
+        datetime.time.utc = false;
+        assert_eq!(
+            super::check_utc("COMPLETED", &datetime)
+                .unwrap_err()
+                .to_string(),
+            "expected UTC time for COMPLETED, found `20070407T000000`"
+        );
+    }
+
+    #[test]
+    fn check_due_duration() {
+        assert!(super::check_due_duration(false, false).is_ok());
+        assert!(super::check_due_duration(true, false).is_ok());
+        assert!(super::check_due_duration(false, true).is_ok());
+        assert!(super::check_due_duration(true, true).is_err());
+    }
 }
@@ -0,0 +1,137 @@
+//! Pull-based parsing of calendar components
+
+use std::borrow::Cow;
+
+use anyhow::bail;
+
+use crate::{
+    Event, Journal, Result, TimeZone, Todo, names,
+    parser::{Lexer, ParseError},
+};
+
+/// A component found inside a `VCALENDAR`.
+#[derive(Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum Component<'src> {
+    Event(Event<'src>),
+    Todo(Todo<'src>),
+    Journal(Journal<'src>),
+    TimeZone(TimeZone<'src>),
+    /// A component we don't parse (yet). Contains the component name (e.g. `VFREEBUSY`),
+    /// its contents are skipped.
+    Unknown(Cow<'src, str>),
+}
+
+/// Incremental parser yielding one component at a time.
+///
+/// Unlike [`parse`](crate::parse), which returns whole calendars, this allows reacting to
+/// components as soon as they have been read. Calendar properties (`PRODID`, `VERSION`, ...)
+/// are skipped over. Input can contain multiple `VCALENDAR`s, components from all of them
+/// are returned in order.
+///
+/// ```
+/// # use icalendar::{Component, Parser};
+/// # let input = "BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n";
+/// let mut parser = Parser::new(input);
+/// while let Some(component) = parser.next_component()? {
+///     match component {
+///         Component::Event(event) => println!("event {}", event.uid),
+///         Component::Todo(todo) => println!("todo {}", todo.uid),
+///         Component::Journal(journal) => println!("journal {}", journal.uid),
+///         Component::TimeZone(timezone) => println!("timezone {}", timezone.id),
+///         Component::Unknown(name) => println!("skipped {name}"),
+///     }
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct Parser<'src> {
+    lexer: Lexer<'src>,
+    in_calendar: bool,
+}
+
+impl<'src> Parser<'src> {
+    pub fn new(input: &'src str) -> Self {
+        Self {
+            lexer: Lexer::new(input),
+            in_calendar: false,
+        }
+    }
+
+    /// Parse the next component.
+    ///
+    /// Returns `Ok(None)` once the input is exhausted.
+    pub fn next_component(&mut self) -> Result<Option<Component<'src>>, ParseError> {
+        self.next_component_inner()
+            .map_err(|error| self.lexer.error(error))
+    }
+
+    fn next_component_inner(&mut self) -> Result<Option<Component<'src>>> {
+        loop {
+            if !self.in_calendar {
+                let Some(begin) = self.lexer.take_next()? else {
+                    return Ok(None);
+                };
+                if !(begin.name == names::BEGIN && begin.value == "VCALENDAR") {
+                    bail!("expected `BEGIN:VCALENDAR`");
+                }
+                self.in_calendar = true;
+            }
+
+            let Some(next) = self.lexer.take_next()? else {
+                return Err(self.lexer.unexpected_eof());
+            };
+            if next.name == names::END {
+                if next.value != "VCALENDAR" {
+                    bail!("expected VCALENDAR, found {}", next.value);
+                }
+                self.in_calendar = false;
+            } else if next.name == names::BEGIN {
+                return Ok(Some(if next.value == "VEVENT" {
+                    Component::Event(Event::parse(&mut self.lexer)?)
+                } else if next.value == "VTODO" {
+                    Component::Todo(Todo::parse(&mut self.lexer)?)
+                } else if next.value == "VJOURNAL" {
+                    Component::Journal(Journal::parse(&mut self.lexer)?)
+                } else if next.value == "VTIMEZONE" {
+                    Component::TimeZone(TimeZone::parse(&mut self.lexer)?)
+                } else {
+                    self.lexer.skip_current()?;
+                    Component::Unknown(next.value)
+                }));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Component, Parser};
+    use crate::test_util;
+
+    #[test]
+    fn next_component() {
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:first@example.com\r\n\
+            DTSTAMP:20111215T112233Z\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:second@example.com\r\n\
+            DTSTAMP:20111215T112233Z\r\n\
+            END:VEVENT\r\n",
+        );
+        let mut parser = Parser::new(&input);
+
+        let Some(Component::Event(event)) = parser.next_component().unwrap() else {
+            panic!("expected event");
+        };
+        assert_eq!(event.uid, "first@example.com");
+
+        let Some(Component::Event(event)) = parser.next_component().unwrap() else {
+            panic!("expected event");
+        };
+        assert_eq!(event.uid, "second@example.com");
+
+        assert!(parser.next_component().unwrap().is_none());
+    }
+}
@@ -1,11 +1,12 @@
 use std::{borrow::Cow, collections::VecDeque};
 
-use anyhow::bail;
+use anyhow::anyhow;
 
 use crate::{
-    Result,
+    Result, names,
     parser::{
-        helpers::{check_iana_token, pop_front_bytes},
+        ParseError, ParseOptions, Warning,
+        helpers::{check_ascii_iana_token, check_iana_token, pop_front_bytes},
         line::{Line, LineIter},
     },
     types::{Name, XName},
@@ -14,17 +15,141 @@ use crate::{
 /// this is kinda like a lexer so call it that, even though it's not exactly
 pub struct Lexer<'src> {
     input: LineIter<'src>,
-    cache: VecDeque<Line<'src>>,
+    /// The length of the whole input
+    len: usize,
+    /// Lines read ahead, with the physical line number they end on and the byte offset they
+    /// start at
+    cache: VecDeque<(usize, usize, Line<'src>)>,
+    /// The physical line number the last line taken ends on
+    line_number: usize,
+    /// The byte offset the last line taken starts at
+    offset: usize,
+    options: ParseOptions,
+    warnings: Vec<Warning>,
+    /// Whether the calendar being parsed uses the Gregorian calendar scale
+    gregorian: bool,
+    /// The components the last line taken is inside, innermost last, with the line number of
+    /// their `BEGIN`
+    open: Vec<(String, usize)>,
+    /// How many calendars have been read up to their `END`
+    calendars: usize,
 }
 
 impl<'src> Lexer<'src> {
     pub fn new(input: &'src str) -> Self {
+        Self::with_options(input, ParseOptions::default())
+    }
+
+    pub fn with_options(input: &'src str, options: ParseOptions) -> Self {
         Self {
             input: LineIter::new(input),
+            len: input.len(),
             cache: VecDeque::with_capacity(3),
+            line_number: 0,
+            offset: 0,
+            options,
+            warnings: vec![],
+            gregorian: true,
+            open: vec![],
+            calendars: 0,
+        }
+    }
+
+    /// Handle an error that lenient parsing can ignore: in lenient mode it is recorded as a
+    /// warning, otherwise it is returned.
+    pub fn recover(&mut self, error: anyhow::Error) -> Result {
+        if !self.options.lenient {
+            return Err(error);
+        }
+        self.warnings.push(Warning {
+            line: self.line_number,
+            message: error.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Parse a component with `parse`, after its `BEGIN` line was taken.
+    ///
+    /// In lenient mode a component that fails to parse is skipped (up to its `END` line) with
+    /// a warning, and `None` is returned.
+    pub fn parse_or_skip<T>(
+        &mut self,
+        component: &str,
+        parse: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<Option<T>> {
+        if self.options.lenient {
+            self.skip_invalid(component, parse)
+        } else {
+            parse(self).map(Some)
+        }
+    }
+
+    /// Like [`parse_or_skip`](Self::parse_or_skip), but skips a component that fails to parse
+    /// in strict mode too.
+    pub fn skip_invalid<T>(
+        &mut self,
+        component: &str,
+        parse: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<Option<T>> {
+        let depth = self.open.len();
+        let error = match parse(self) {
+            Ok(value) => return Ok(Some(value)),
+            Err(error) => error,
+        };
+        self.warnings.push(Warning {
+            line: self.line_number,
+            message: format!("skipped {component}: {error:#}"),
+        });
+        while self.open.len() >= depth {
+            if self.take_next()?.is_none() {
+                return Err(self.unexpected_eof());
+            }
+        }
+        Ok(None)
+    }
+
+    /// Convert an error from parsing to the public error type, with where it was found
+    pub fn error(&self, error: anyhow::Error) -> ParseError {
+        ParseError {
+            line: self.line_number,
+            offset: self.offset,
+            calendars: self.calendars,
+            kind: error.into(),
+        }
+    }
+
+    /// The error for input ending early, naming the innermost component that isn't closed
+    pub fn unexpected_eof(&self) -> anyhow::Error {
+        match self.open.last() {
+            Some((name, line)) => {
+                anyhow!("unexpected EOF: `BEGIN:{name}` on line {line} has no `END:{name}`")
+            }
+            None => anyhow!("unexpected EOF"),
         }
     }
 
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    /// Whether dates should be checked against the Gregorian calendar
+    pub fn is_gregorian(&self) -> bool {
+        self.gregorian
+    }
+
+    pub fn set_gregorian(&mut self, gregorian: bool) {
+        self.gregorian = gregorian;
+    }
+
+    /// The 1-based line number (in the folded input) of the end of the last line taken
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
     pub fn is_empty(&mut self) -> Result<bool> {
         Ok(self.next()?.is_none())
     }
@@ -33,20 +158,36 @@ impl<'src> Lexer<'src> {
         if !self.ensure_cache()? {
             return Ok(None);
         }
-        Ok(Some(self.cache.front().unwrap()))
+        Ok(Some(&self.cache.front().unwrap().2))
     }
 
     pub fn take_next(&mut self) -> Result<Option<Line<'src>>> {
         if !self.ensure_cache()? {
             return Ok(None);
         }
-        Ok(Some(self.cache.pop_front().unwrap()))
+        let (line_number, offset, line) = self.cache.pop_front().unwrap();
+        self.line_number = line_number;
+        self.offset = offset;
+        if line.name == names::BEGIN {
+            self.open.push((line.value.to_string(), line_number));
+        } else if line.name == names::END
+            && self.open.pop().is_some_and(|(name, _)| name == "VCALENDAR")
+            && self.open.is_empty()
+        {
+            self.calendars += 1;
+        }
+        Ok(Some(line))
     }
 
     pub fn step(&mut self) {
-        if self.cache.pop_front().is_none() {
+        if let Some((line_number, offset, _)) = self.cache.pop_front() {
+            self.line_number = line_number;
+            self.offset = offset;
+        } else {
             // skip an uncached line if there are no cached ones
+            self.offset = self.input_offset();
             self.input.next();
+            self.line_number = self.input.line_number();
         }
     }
 
@@ -54,28 +195,45 @@ impl<'src> Lexer<'src> {
     pub fn skip_current(&mut self) -> Result {
         let mut depth = 1;
         while let Some(line) = self.take_next()? {
-            if &line.name == "BEGIN" {
+            if line.name == names::BEGIN {
                 depth += 1;
-            } else if &line.name == "END" {
+            } else if line.name == names::END {
                 depth -= 1;
             }
             if depth == 0 {
                 return Ok(());
             }
         }
-        bail!("unexpected EOF");
+        Err(self.unexpected_eof())
+    }
+
+    /// The byte offset of the next uncached line
+    fn input_offset(&self) -> usize {
+        self.len - self.input.remainder().len()
     }
 
     /// Make sure there is at least one line in the cache.
     ///
     /// Returns false if it wasn't possible because the iterator is exhausted
     ///
-    /// If this function errors a line will be lost
+    /// If this function errors a line will be lost, and it is taken as the current line
     fn ensure_cache(&mut self) -> Result<bool> {
         if self.cache.is_empty() {
+            let offset = self.input_offset();
             match self.input.next() {
                 Some(line) => {
-                    self.cache.push_back(Line::parse(line)?);
+                    let line = Line::parse(line).and_then(|line| {
+                        if self.options.strict_names {
+                            check_ascii_names(&line)?;
+                        }
+                        Ok(line)
+                    });
+                    let line = line.inspect_err(|_| {
+                        self.line_number = self.input.line_number();
+                        self.offset = offset;
+                    })?;
+                    self.cache
+                        .push_back((self.input.line_number(), offset, line));
                     Ok(true)
                 }
                 None => Ok(false),
@@ -86,6 +244,16 @@ impl<'src> Lexer<'src> {
     }
 }
 
+/// The strict check of [`ParseOptions::strict_names`], on top of the usual one in
+/// [`Name::parse`]
+fn check_ascii_names(line: &Line<'_>) -> Result {
+    check_ascii_iana_token(&line.name.to_string())?;
+    for (name, _) in line.params_iter() {
+        check_ascii_iana_token(&name.to_string())?;
+    }
+    Ok(())
+}
+
 impl<'src> Name<'src> {
     pub fn parse(input: impl Into<Cow<'src, str>>) -> Result<Self> {
         Self::parse_inner(input.into())
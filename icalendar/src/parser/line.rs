@@ -5,9 +5,9 @@ use anyhow::bail;
 use crate::{
     parser::{
         ParamMap,
-        helpers::{split_once, split_once_outside_quotes, try_split_once},
+        helpers::{find_outside_quotes, split_around, split_once, split_once_outside_quotes},
     },
-    types::Name,
+    types::{Name, VecOne},
 };
 
 /// Lines are split on `\r\n`, but single lines can also be split with `\r\n ` (extra space) between them.
@@ -15,11 +15,27 @@ use crate::{
 /// This iterator returns 'unfolded' lines
 pub struct LineIter<'src> {
     input: &'src str,
+    /// The number of physical (folded) lines returned so far
+    line_number: usize,
 }
 
 impl<'src> LineIter<'src> {
     pub fn new(input: &'src str) -> Self {
-        Self { input }
+        Self {
+            input,
+            line_number: 0,
+        }
+    }
+
+    /// The 1-based physical line number of the last line of input returned, counting each
+    /// folded part separately
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// The input that hasn't been returned yet
+    pub fn remainder(&self) -> &'src str {
+        self.input
     }
 }
 
@@ -36,6 +52,7 @@ impl<'src> Iterator for LineIter<'src> {
                 line => {
                     // last line
                     self.input = "";
+                    self.line_number += 1;
                     return Some(Cow::Borrowed(line));
                 }
             }
@@ -43,6 +60,7 @@ impl<'src> Iterator for LineIter<'src> {
         if !second.starts_with(" ") {
             // skip first line and `\r\n` - we will be on a char boundary
             self.input = &self.input[first.len() + 2..];
+            self.line_number += 1;
             return Some(Cow::Borrowed(first));
         }
 
@@ -51,11 +69,13 @@ impl<'src> Iterator for LineIter<'src> {
         // first char is space, we are on a char boundary
         output.push_str(&second[1..]);
         let mut len = first.len() + 2 + second.len();
+        self.line_number += 2;
         while let Some(next) = iter.next() {
             if next.starts_with(" ") {
                 // first char is space, we are on a char boundary
                 output.push_str(&next[1..]);
                 len += next.len() + 2;
+                self.line_number += 1;
             } else {
                 // `next` is following line
                 // add 2 for "\r\n"
@@ -81,14 +101,21 @@ pub struct Line<'src> {
 }
 
 impl<'src> Line<'src> {
-    pub(crate) fn parse(input: impl Into<Cow<'src, str>>) -> anyhow::Result<Self> {
+    /// Parse a single unfolded line
+    pub fn parse(input: impl Into<Cow<'src, str>>) -> anyhow::Result<Self> {
         let input = input.into();
 
-        // no escaping in name so easier to parse
-        let (prefix, value) = match try_split_once(input, ':') {
-            Ok(v) => v,
-            Err(input) => bail!("malformed icalendar line: {input}"),
+        // no escaping in name so easier to parse, but quoted param values can contain ':'
+        // (e.g. `SENT-BY="mailto:..."`). If a quote isn't closed, split at the first ':' so
+        // that the param it is in gets the blame.
+        let colon = find_outside_quotes(&input, ':')
+            .ok()
+            .flatten()
+            .or_else(|| input.find(':'));
+        let Some(colon) = colon else {
+            bail!("malformed icalendar line: {input}");
         };
+        let (prefix, value) = split_around(input, colon, ':');
         let (name, params_str) = split_once(prefix, ';');
 
         let name = Name::parse(name)?;
@@ -97,7 +124,7 @@ impl<'src> Line<'src> {
         let mut loop_rest = params_str;
         while !loop_rest.is_empty() {
             // slightly inefficient to look ahead for ';' I think but much simpler and easier to program.
-            let (param, rest) = split_once_outside_quotes(loop_rest, ';');
+            let (param, rest) = split_once_outside_quotes(loop_rest, ';')?;
             params.parse_param(param)?;
             loop_rest = rest;
         }
@@ -108,6 +135,13 @@ impl<'src> Line<'src> {
         })
     }
 
+    /// All parameters of the line in the order they appear, without interpreting them.
+    ///
+    /// Repeated parameters are merged into the first occurrence.
+    pub fn params_iter(&self) -> impl Iterator<Item = (&Name<'src>, &VecOne<Cow<'src, str>>)> {
+        self.params.iter()
+    }
+
     pub fn first_iana_param(&self) -> Option<&Cow<'src, str>> {
         self.params.iana().next().map(|(v, _)| v)
     }
@@ -128,7 +162,9 @@ mod tests {
             fn $name() {
                 let input = $input;
                 let output: Vec<_> = super::LineIter::new(input).collect();
-                assert_eq!(output, $output)
+                assert_eq!(output, $output);
+                let output: Vec<_> = crate::unfold(input).collect();
+                assert_eq!(output, $output);
             }
         };
     }
@@ -191,4 +227,57 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn colon_in_quoted_param() {
+        let input = "ORGANIZER;SENT-BY=\"mailto:assistant@example.com\":mailto:boss@example.com";
+        let line = Line::parse(input).unwrap();
+        assert_eq!(line.name.to_string(), "ORGANIZER");
+        assert_eq!(line.value, "mailto:boss@example.com");
+        let params: Vec<_> = line
+            .params_iter()
+            .map(|(name, values)| (name.to_string(), values.into_iter().cloned().collect()))
+            .collect();
+        assert_eq!(
+            params,
+            [(
+                "SENT-BY".to_string(),
+                vec![Cow::Borrowed("mailto:assistant@example.com")]
+            )]
+        );
+    }
+
+    #[test]
+    fn unterminated_quote() {
+        let input = "ATTENDEE;CN=\"Doe, John;ROLE=CHAIR:mailto:john@example.com";
+        let error = Line::parse(input).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unterminated quoted string `\"Doe, John;ROLE=CHAIR`"
+        );
+    }
+
+    #[test]
+    fn params_iter() {
+        let input = "ATTENDEE;ROLE=CHAIR;X-FOO=bar;CN=\"Doe, John\";X-BAZ=a,\"b;c\":mailto:john@example.com";
+        let line = Line::parse(input).unwrap();
+        let params: Vec<_> = line
+            .params_iter()
+            .map(|(name, values)| {
+                (
+                    name.to_string(),
+                    values.into_iter().map(|v| v.as_ref()).collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            params,
+            [
+                ("ROLE".to_string(), vec!["CHAIR"]),
+                ("X-FOO".to_string(), vec!["bar"]),
+                ("CN".to_string(), vec!["Doe, John"]),
+                ("X-BAZ".to_string(), vec!["a", "b;c"]),
+            ]
+        );
+    }
 }
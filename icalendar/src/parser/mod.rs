@@ -2,13 +2,14 @@
 
 use std::borrow::Cow;
 
-use anyhow::{anyhow, bail};
+use anyhow::{Context, anyhow, bail};
 
 mod line;
-use line::Line;
+pub use line::Line;
+pub(crate) use line::LineIter;
 
 mod error;
-pub use error::ParserError;
+pub use error::{ParseError, ParseErrorKind, ParserError};
 
 pub(crate) mod helpers;
 mod lexer;
@@ -17,30 +18,44 @@ pub(crate) use lexer::Lexer;
 mod param_map;
 pub(crate) use param_map::ParamMap;
 
+mod incremental;
+pub use incremental::{Component, Parser};
+
+mod options;
+pub use options::{ParseOptions, Parsed, UnknownComponents, Warning};
+
+mod headers;
+pub use headers::EventHeader;
+pub(crate) use headers::parse_headers;
+
 use crate::{
-    AnnotatedText, Attachment, Attendee, CalScale, Calendar, Categories, Class, Comment, Contact,
-    Event, EventEnd, EventStatus, ExceptionDateTimes, Organizer, RecurrenceId, Result,
-    TimeTransparency,
+    Alarm, AlarmTrigger, AnnotatedText, Attachment, Attendee, CalScale, Calendar, Categories,
+    Class, Comment, Conference, Contact, Event, EventEnd, EventStatus, ExceptionDateTimes,
+    ExtensionProperty, Journal, JournalStatus, Observance, ObservanceKind, Organizer,
+    RecurrenceDates, RecurrenceId, RelatedTo, Result, SkippedComponent, StructuredLocation,
+    TimeTransparency, TimeZone, Todo, TodoStatus, names,
     params::{
-        CommonName, Delegatees, Delegators, DirectoryEntryReference, GroupOrListMember, Language,
-        SentBy,
+        AlarmTriggerRelationship, CommonName, Delegatees, Delegators, DirectoryEntryReference,
+        GroupOrListMember, Language, SentBy, TimeZoneIdentifier,
     },
     parser::helpers::{
-        check_iana_token, opt_vec_one_to_vec, parse_date_or_datetime, parse_date_or_datetime_list,
+        all_consuming, check_due_duration, check_iana_token, check_utc, opt_vec_one_to_vec,
+        parse_date_or_datetime, parse_date_or_datetime_list, parse_percent_complete, parse_start,
+        unescape_text,
+    },
+    types::{
+        Data, DateOrDateTime, DateTime, Duration, GeoLocation, Name, Priority, Recur, UtcOffset,
+        VecOne,
     },
-    types::{Data, DateOrDateTime, DateTime, Duration, GeoLocation, Name, Priority},
-    values::Text,
+    values::{CalendarUserAddress, Text, Uri},
 };
 
-const ENCODING_PARAM: Name = Name::iana("ENCODING");
-const VALUE_PARAM: Name = Name::iana("VALUE");
-
 /// Macor for builders that expect 0 or 1 instances of a field
 macro_rules! impl_set_01 {
-    ($id:ident, $setter:ident, $ty:ty, $label:literal) => {
+    ($id:ident, $setter:ident, $ty:ty, $label:expr) => {
         fn $setter(&mut self, $id: $ty) -> Result {
             if self.$id.is_some() {
-                bail!(concat!("expected 0..=1 ", $label, ", found at least 2"));
+                return Err(crate::parser::ParseErrorKind::duplicate($label));
             }
             self.$id = Some($id);
             Ok(())
@@ -52,7 +67,7 @@ macro_rules! impl_set_1 {
     ($id:ident, $setter:ident, $ty:ty, $label:literal) => {
         fn $setter(&mut self, $id: $ty) -> Result {
             if self.$id.is_some() {
-                bail!(concat!("expected 1 ", $label, ", found at least 2"));
+                return Err(crate::parser::ParseErrorKind::duplicate($label));
             }
             self.$id = Some($id);
             Ok(())
@@ -66,127 +81,543 @@ impl<'src> Calendar<'src> {
         let Some(begin) = parser.take_next()? else {
             bail!("empty iterator: call is_empty before this function to avoid");
         };
-        if !(&begin.name == "BEGIN" && begin.value == "VCALENDAR") {
+        if !(begin.name == names::BEGIN && begin.value == "VCALENDAR") {
             bail!("expected `BEGIN:VCALENDAR`");
         }
+        parser.set_gregorian(true);
 
         let mut builder = CalendarBuilder::new();
         while let Some(next) = parser.take_next()? {
-            if &next.name == "END" {
+            if next.name == names::END {
                 if next.value != "VCALENDAR" {
                     bail!("expected VCALENDAR, found {}", next.value);
                 }
                 return Ok(builder.build()?);
-            } else if &next.name == "PRODID" {
+            } else if next.name == names::PRODID {
                 builder.set_prod_id(parse_prodid(next)?)?;
-            } else if &next.name == "VERSION" {
-                builder.set_version(parse_version(next)?)?;
-            } else if &next.name == "CALSCALE" {
-                builder.set_cal_scale(parse_cal_scale(next)?)?;
-            } else if &next.name == "METHOD" {
+            } else if next.name == names::VERSION {
+                if let Err(error) = check_version(&next) {
+                    parser.recover(error)?;
+                }
+                builder.set_version(next.value)?;
+            } else if next.name == names::CALSCALE {
+                let cal_scale = parse_cal_scale(next)?;
+                parser.set_gregorian(cal_scale == CalScale::Gregorian);
+                builder.set_cal_scale(cal_scale)?;
+            } else if next.name == names::METHOD {
                 check_iana_token(&next.value)?;
                 builder.set_method(next.value)?;
-            } else if &next.name == "BEGIN" {
+            } else if next.name == names::X_WR_TIMEZONE {
+                builder.set_default_timezone(next.value)?;
+            } else if next.name == names::NAME {
+                // there can be one for each language
+                builder.name.get_or_insert(next.value);
+            } else if next.name == names::X_WR_CALNAME {
+                builder.set_x_name(next.value)?;
+            } else if next.name == names::BEGIN {
                 // VEVENT, VTODO, etc.
                 if next.value == "VEVENT" {
                     builder.events.push(Event::parse(parser)?);
+                } else if next.value == "VTODO" {
+                    builder
+                        .todos
+                        .extend(parser.parse_or_skip("VTODO", Todo::parse)?);
+                } else if next.value == "VJOURNAL" {
+                    builder
+                        .journals
+                        .extend(parser.parse_or_skip("VJOURNAL", Journal::parse)?);
+                } else if next.value == "VTIMEZONE" {
+                    if parser.options().parse_timezones {
+                        builder.timezones.push(TimeZone::parse(parser)?);
+                    } else {
+                        parser.skip_current()?;
+                    }
                 } else {
-                    // TODO error instead?
-                    parser.skip_current()?;
+                    let first_line = parser.line_number();
+                    match parser.options().unknown_components {
+                        UnknownComponents::Skip => parser.skip_current()?,
+                        UnknownComponents::Error => {
+                            return Err(ParseErrorKind::Unsupported {
+                                component: next.value.to_string(),
+                            }
+                            .into());
+                        }
+                        UnknownComponents::Collect => {
+                            parser.skip_current()?;
+                            builder.skipped_components.push(SkippedComponent {
+                                name: next.value,
+                                lines: first_line..=parser.line_number(),
+                            });
+                        }
+                    }
                 }
             }
         }
-        bail!("unexpected EOF");
+        Err(parser.unexpected_eof())
     }
 }
 
 impl<'src> Event<'src> {
     fn parse(parser: &mut Lexer<'src>) -> Result<Self> {
         let mut builder = EventBuilder::default();
-        while let Some(next) = parser.take_next()? {
-            if &next.name == "END" {
+        while let Some(mut next) = parser.take_next()? {
+            if next.name == names::END {
                 if next.value != "VEVENT" {
                     bail!("expected VEVENT, found {}", next.value);
                 }
                 return Ok(builder.build()?);
-            } else if &next.name == "CLASS" {
-                builder.set_class(parse_class(next.value)?)?;
-            } else if &next.name == "CREATED" {
-                builder.set_created(DateTime::parse(&*next.value)?.1)?;
-            } else if &next.name == "DESCRIPTION" {
+            } else if next.name == names::CLASS {
+                builder.set_class(parse_class(next.value).context("invalid CLASS")?)?;
+            } else if next.name == names::COLOR {
+                builder.set_color(next.value)?;
+            } else if next.name == names::CREATED {
+                // errors from `parse_timestamp` and `parse_organizer` already name the property
+                builder.set_created(parse_timestamp(parser, &next)?)?;
+            } else if next.name == names::DESCRIPTION {
+                builder
+                    .set_description(parse_annotated_text(next).context("invalid DESCRIPTION")?)?;
+            } else if next.name == names::DTSTART {
+                builder.start_timezone_id = next.params.take_ty().context("invalid DTSTART")?;
+                let start =
+                    parse_start(&next.value, parser.is_gregorian()).context("invalid DTSTART")?;
+                builder.set_start(start)?;
+            } else if next.name == names::GEO {
+                builder.set_geo_location(next.value.parse().context("invalid GEO")?)?;
+            } else if next.name == names::LAST_MODIFIED {
+                builder.set_last_modified(parse_timestamp(parser, &next)?)?;
+            } else if next.name == names::LOCATION {
+                builder.set_location(parse_annotated_text(next).context("invalid LOCATION")?)?;
+            } else if next.name == names::ORGANIZER {
+                builder.set_organizer(parse_organizer(parser, next)?)?;
+            } else if next.name == names::PRIORITY {
+                builder.set_priority(next.value.parse().context("invalid PRIORITY")?)?;
+            } else if next.name == names::DTSTAMP {
+                builder.set_timestamp(parse_timestamp(parser, &next)?)?;
+            } else if next.name == names::SEQUENCE {
+                builder.set_sequence(next.value.parse().context("invalid SEQUENCE")?)?;
+            } else if next.name == names::STATUS {
+                builder.set_status(parse_event_status(next).context("invalid STATUS")?)?;
+            } else if next.name == names::SUMMARY {
+                builder.set_summary(parse_annotated_text(next).context("invalid SUMMARY")?)?;
+            } else if next.name == names::TRANSP {
+                builder.set_time_transparency(
+                    parse_time_transparency(next).context("invalid TRANSP")?,
+                )?;
+            } else if next.name == names::UID {
+                builder.set_uid(next.value)?;
+            } else if next.name == names::URL {
+                builder.set_url(parse_url(next).context("invalid URL")?)?;
+            } else if next.name == names::RECURRENCE_ID {
+                let recurrence_id = parse_recurrence_id(next, parser.is_gregorian())
+                    .context("invalid RECURRENCE-ID")?;
+                builder.set_recurrence_id(recurrence_id)?;
+            } else if next.name == names::DTEND {
+                builder.set_end(
+                    parse_datetime_end(next, parser.is_gregorian()).context("invalid DTEND")?,
+                )?;
+            } else if next.name == names::DURATION {
+                let (_, duration) = Duration::parse(&next.value).context("invalid DURATION")?;
+                builder.set_end(EventEnd::Duration(duration))?;
+            } else if next.name == names::ATTACH {
+                let attachment = parse_attachment(parser, next).context("invalid ATTACH")?;
+                builder.attachments.push(attachment);
+            } else if next.name == names::ATTENDEE {
+                builder
+                    .attendees
+                    .push(parse_attendee(next).context("invalid ATTENDEE")?);
+            } else if next.name == names::CATEGORIES {
+                builder
+                    .categories
+                    .push(parse_categories(next).context("invalid CATEGORIES")?);
+            } else if next.name == names::COMMENT {
+                builder
+                    .comments
+                    .push(parse_comment(next).context("invalid COMMENT")?);
+            } else if next.name == names::CONTACT {
+                builder
+                    .contacts
+                    .push(parse_contact(next).context("invalid CONTACT")?);
+            } else if next.name == names::CONFERENCE {
+                builder
+                    .conferences
+                    .push(parse_conference(next).context("invalid CONFERENCE")?);
+            } else if next.name == names::EXDATE {
+                let dates =
+                    parse_exception_dates(next, parser.is_gregorian()).context("invalid EXDATE")?;
+                builder.exception_dates.push(dates);
+            } else if next.name == names::RRULE {
+                builder
+                    .recurrence_rules
+                    .push(next.value.parse().context("invalid RRULE")?);
+            } else if next.name == names::RDATE {
+                let dates =
+                    parse_recurrence_dates(next, parser.is_gregorian()).context("invalid RDATE")?;
+                builder.recurrence_dates.push(dates);
+            } else if next.name.is_extension() {
+                if parser.options().keep_unknown {
+                    builder.extensions.push(parse_extension(next));
+                }
+            } else if next.name == names::BEGIN {
+                if next.value == "VLOCATION" {
+                    builder
+                        .structured_locations
+                        .push(StructuredLocation::parse(parser)?);
+                } else if next.value == "VALARM" {
+                    // alarms are optional extras, so an invalid one doesn't fail the event
+                    builder
+                        .alarms
+                        .extend(parser.skip_invalid("VALARM", Alarm::parse)?);
+                } else {
+                    // skip all other subtrees
+                    parser.skip_current()?;
+                }
+            }
+        }
+        Err(parser.unexpected_eof())
+    }
+}
+
+impl<'src> Alarm<'src> {
+    fn parse(parser: &mut Lexer<'src>) -> Result<Self> {
+        let mut builder = AlarmBuilder::default();
+        while let Some(next) = parser.take_next()? {
+            if next.name == names::END {
+                if next.value != "VALARM" {
+                    bail!("expected VALARM, found {}", next.value);
+                }
+                return builder.build();
+            } else if next.name == names::ACTION {
+                check_iana_token(&next.value)?;
+                builder.set_action(next.value)?;
+            } else if next.name == names::TRIGGER {
+                builder.set_trigger(parse_trigger(next)?)?;
+            } else if next.name == names::DESCRIPTION {
+                builder.set_description(parse_annotated_text(next)?)?;
+            } else if next.name == names::SUMMARY {
+                builder.set_summary(parse_annotated_text(next)?)?;
+            } else if next.name == names::DURATION {
+                builder.set_duration(Duration::parse(&next.value)?.1)?;
+            } else if next.name == names::REPEAT {
+                builder.set_repeat(next.value.parse().context("invalid REPEAT")?)?;
+            } else if next.name == names::ATTENDEE {
+                builder
+                    .attendees
+                    .push(parse_attendee(next).context("invalid ATTENDEE")?);
+            } else if next.name == names::ATTACH {
+                let attachment = parse_attachment(parser, next).context("invalid ATTACH")?;
+                builder.attachments.push(attachment);
+            } else if next.name == names::UID {
+                builder.set_uid(next.value)?;
+            } else if next.name == names::ACKNOWLEDGED {
+                builder.set_acknowledged(parse_timestamp(parser, &next)?)?;
+            } else if next.name == names::RELATED_TO {
+                builder.related_to.push(parse_related_to(next)?);
+            } else if next.name.is_extension() {
+                if parser.options().keep_unknown {
+                    builder.extensions.push(parse_extension(next));
+                }
+            } else if next.name == names::BEGIN {
+                parser.skip_current()?;
+            }
+        }
+        Err(parser.unexpected_eof())
+    }
+}
+
+impl<'src> TimeZone<'src> {
+    fn parse(parser: &mut Lexer<'src>) -> Result<Self> {
+        let mut builder = TimeZoneBuilder::default();
+        while let Some(next) = parser.take_next()? {
+            if next.name == names::END {
+                if next.value != "VTIMEZONE" {
+                    bail!("expected VTIMEZONE, found {}", next.value);
+                }
+                return builder.build();
+            } else if next.name == names::TZID {
+                builder.set_id(next.value)?;
+            } else if next.name == names::LAST_MODIFIED {
+                builder.set_last_modified(parse_timestamp(parser, &next)?)?;
+            } else if next.name == names::TZURL {
+                builder.set_url(next.value)?;
+            } else if next.name == names::BEGIN {
+                if next.value == "STANDARD" {
+                    let observance = Observance::parse(parser, ObservanceKind::Standard)?;
+                    builder.observances.push(observance);
+                } else if next.value == "DAYLIGHT" {
+                    let observance = Observance::parse(parser, ObservanceKind::Daylight)?;
+                    builder.observances.push(observance);
+                } else {
+                    parser.skip_current()?;
+                }
+            }
+        }
+        Err(parser.unexpected_eof())
+    }
+}
+
+impl<'src> Observance<'src> {
+    fn parse(parser: &mut Lexer<'src>, kind: ObservanceKind) -> Result<Self> {
+        let name = match kind {
+            ObservanceKind::Standard => "STANDARD",
+            ObservanceKind::Daylight => "DAYLIGHT",
+        };
+        let mut builder = ObservanceBuilder::default();
+        while let Some(mut next) = parser.take_next()? {
+            if next.name == names::END {
+                if next.value != name {
+                    bail!("expected {name}, found {}", next.value);
+                }
+                return builder.build(kind);
+            } else if next.name == names::DTSTART {
+                builder.set_start(DateTime::parse(&next.value)?.1)?;
+            } else if next.name == names::TZOFFSETFROM {
+                builder.set_offset_from(parse_utc_offset(parser, &next)?)?;
+            } else if next.name == names::TZOFFSETTO {
+                builder.set_offset_to(parse_utc_offset(parser, &next)?)?;
+            } else if next.name == names::RRULE {
+                builder.recurrence_rules.push(next.value.parse()?);
+            } else if next.name == names::RDATE {
+                for date in &parse_date_or_datetime_list(&mut next, true)? {
+                    let DateOrDateTime::DateTime(date) = date else {
+                        bail!("expected DATE-TIME values for RDATE in {name}");
+                    };
+                    builder.recurrence_dates.push(*date);
+                }
+            } else if next.name == names::TZNAME {
+                builder.names.push(parse_annotated_text(next)?);
+            } else if next.name == names::COMMENT {
+                builder.comments.push(parse_comment(next)?);
+            } else if next.name == names::BEGIN {
+                parser.skip_current()?;
+            }
+        }
+        Err(parser.unexpected_eof())
+    }
+}
+
+impl<'src> StructuredLocation<'src> {
+    fn parse(parser: &mut Lexer<'src>) -> Result<Self> {
+        let mut builder = StructuredLocationBuilder::default();
+        while let Some(next) = parser.take_next()? {
+            if next.name == names::END {
+                if next.value != "VLOCATION" {
+                    bail!("expected VLOCATION, found {}", next.value);
+                }
+                return builder.build();
+            } else if next.name == names::UID {
+                builder.set_uid(next.value)?;
+            } else if next.name == names::NAME {
+                builder.set_name(next.value)?;
+            } else if next.name == names::DESCRIPTION {
                 builder.set_description(parse_annotated_text(next)?)?;
-            } else if &next.name == "DTSTART" {
-                builder.set_start(DateOrDateTime::parse(&*next.value)?.1)?;
-            } else if &next.name == "GEO" {
+            } else if next.name == names::LOCATION_TYPE {
+                builder.set_location_type(next.value)?;
+            } else if next.name == names::GEO {
                 builder.set_geo_location(next.value.parse()?)?;
-            } else if &next.name == "LAST-MODIFIED" {
-                builder.set_last_modified(DateTime::parse(&*next.value)?.1)?;
-            } else if &next.name == "LOCATION" {
-                builder.set_location(parse_annotated_text(next)?)?;
-            } else if &next.name == "ORGANIZER" {
-                builder.set_organizer(parse_organizer(next)?)?;
-            } else if &next.name == "PRIORITY" {
+            } else if next.name == names::URL {
+                builder.set_url(next.value)?;
+            } else if next.name == names::BEGIN {
+                parser.skip_current()?;
+            }
+        }
+        Err(parser.unexpected_eof())
+    }
+}
+
+impl<'src> Todo<'src> {
+    fn parse(parser: &mut Lexer<'src>) -> Result<Self> {
+        let mut builder = TodoBuilder::default();
+        while let Some(mut next) = parser.take_next()? {
+            if next.name == names::END {
+                if next.value != "VTODO" {
+                    bail!("expected VTODO, found {}", next.value);
+                }
+                return builder.build();
+            } else if next.name == names::COMPLETED {
+                builder.set_completed(parse_timestamp(parser, &next)?)?;
+            } else if next.name == names::CREATED {
+                builder.set_created(parse_timestamp(parser, &next)?)?;
+            } else if next.name == names::DESCRIPTION {
+                builder.set_description(parse_annotated_text(next)?)?;
+            } else if next.name == names::DTSTART {
+                builder.start_timezone_id = next.params.take_ty()?;
+                builder.set_start(parse_start(&next.value, parser.is_gregorian())?)?;
+            } else if next.name == names::DUE {
+                builder.due_timezone_id = next.params.take_ty()?;
+                builder.set_due(parse_date_or_datetime(&mut next, parser.is_gregorian())?)?;
+            } else if next.name == names::DURATION {
+                builder.set_duration(Duration::parse(&next.value)?.1)?;
+            } else if next.name == names::LAST_MODIFIED {
+                builder.set_last_modified(parse_timestamp(parser, &next)?)?;
+            } else if next.name == names::PERCENT_COMPLETE {
+                builder.set_percent_complete(parse_percent_complete(&next.value)?)?;
+            } else if next.name == names::PRIORITY {
                 builder.set_priority(next.value.parse()?)?;
-            } else if &next.name == "DTSTAMP" {
-                builder.set_timestamp(DateTime::parse(&*next.value)?.1)?;
-            } else if &next.name == "SEQ" {
+            } else if next.name == names::DTSTAMP {
+                builder.set_timestamp(parse_timestamp(parser, &next)?)?;
+            } else if next.name == names::SEQUENCE {
                 builder.set_sequence(next.value.parse()?)?;
-            } else if &next.name == "STATUS" {
-                builder.set_status(parse_event_status(next)?)?;
-            } else if &next.name == "SUMMARY" {
+            } else if next.name == names::STATUS {
+                builder.set_status(parse_todo_status(next)?)?;
+            } else if next.name == names::SUMMARY {
                 builder.set_summary(parse_annotated_text(next)?)?;
-            } else if &next.name == "TRANSP" {
-                builder.set_time_transparency(parse_time_transparency(next)?)?;
-            } else if &next.name == "UID" {
+            } else if next.name == names::UID {
                 builder.set_uid(next.value)?;
-            } else if &next.name == "RECURRENCE-ID" {
-                builder.set_recurrence_id(parse_recurrence_id(next)?)?;
-            } else if &next.name == "DTEND" {
-                builder.set_end(parse_datetime_end(next)?)?;
-            } else if &next.name == "DURATION" {
-                builder.set_end(EventEnd::Duration(Duration::parse(&*next.value)?.1))?;
-            } else if &next.name == "ATTACHMENT" {
-                builder.attachments.push(parse_attachment(next)?);
-            } else if &next.name == "ATTENDEE" {
-                builder.attendees.push(parse_attendee(next)?);
-            } else if &next.name == "CATEGORIES" {
-                builder.categories.push(parse_categories(next)?);
-            } else if &next.name == "COMMENT" {
-                builder.comments.push(parse_comment(next)?);
-            } else if &next.name == "CONTACT" {
-                builder.contacts.push(parse_contact(next)?);
-            } else if &next.name == "EXDATE" {
-                builder.exception_dates.push(parse_exception_dates(next)?);
-            } else if &next.name == "BEGIN" {
+            } else if next.name == names::BEGIN {
                 // skip all other subtrees
                 parser.skip_current()?;
             }
         }
-        bail!("unexpected EOF")
+        Err(parser.unexpected_eof())
+    }
+}
+
+impl<'src> Journal<'src> {
+    fn parse(parser: &mut Lexer<'src>) -> Result<Self> {
+        let mut builder = JournalBuilder::default();
+        while let Some(mut next) = parser.take_next()? {
+            if next.name == names::END {
+                if next.value != "VJOURNAL" {
+                    bail!("expected VJOURNAL, found {}", next.value);
+                }
+                return builder.build();
+            } else if next.name == names::UID {
+                builder.set_uid(next.value)?;
+            } else if next.name == names::DTSTAMP {
+                builder.set_timestamp(parse_timestamp(parser, &next)?)?;
+            } else if next.name == names::DTSTART {
+                builder.start_timezone_id = next.params.take_ty()?;
+                builder.set_start(parse_start(&next.value, parser.is_gregorian())?)?;
+            } else if next.name == names::SUMMARY {
+                builder.set_summary(parse_annotated_text(next)?)?;
+            } else if next.name == names::DESCRIPTION {
+                builder.descriptions.push(parse_annotated_text(next)?);
+            } else if next.name == names::CATEGORIES {
+                builder.categories.push(parse_categories(next)?);
+            } else if next.name == names::STATUS {
+                builder.set_status(parse_journal_status(next)?)?;
+            } else if next.name.is_extension() {
+                if parser.options().keep_unknown {
+                    builder.extensions.push(parse_extension(next));
+                }
+            } else if next.name == names::BEGIN {
+                parser.skip_current()?;
+            }
+        }
+        Err(parser.unexpected_eof())
+    }
+}
+
+fn parse_extension(input: Line<'_>) -> ExtensionProperty<'_> {
+    let Name::XName(name) = input.name else {
+        unreachable!("only called for extension properties");
+    };
+    ExtensionProperty {
+        name,
+        params: input.params.into_params().collect(),
+        value: input.value,
     }
 }
 
 fn parse_prodid<'src>(input: Line<'src>) -> Result<Cow<'src, str>> {
-    debug_assert_eq!(&input.name, "PRODID");
+    debug_assert_eq!(input.name, names::PRODID);
     if let Some(param) = input.first_iana_param() {
         bail!("unexpected param {param:?}");
     }
     Ok(input.value)
 }
 
-fn parse_version<'src>(input: Line<'src>) -> Result {
-    debug_assert_eq!(&input.name, "VERSION");
+/// Check the calendar can be read as version 2.0. The value is either a version or a
+/// `minver;maxver` range.
+fn check_version(input: &Line<'_>) -> Result {
+    debug_assert_eq!(input.name, names::VERSION);
     if let Some(param) = input.first_iana_param() {
         bail!("unexpected param {param:?}");
     }
-    if input.value != "2.0" {
-        bail!("only version 2.0 supported");
+    let (min, max) = match input.value.split_once(';') {
+        Some((min, max)) => (parse_version_number(min)?, parse_version_number(max)?),
+        None => {
+            let version = parse_version_number(&input.value)?;
+            (version, version)
+        }
+    };
+    if !(min..=max).contains(&(2, 0)) {
+        bail!("unsupported VERSION {}: only 2.0 is supported", input.value);
     }
     Ok(())
 }
 
+/// `major.minor`, or just `major` for `major.0`
+fn parse_version_number(input: &str) -> Result<(u32, u32)> {
+    let (major, minor) = input.split_once('.').unwrap_or((input, "0"));
+    match (major.parse(), minor.parse()) {
+        (Ok(major), Ok(minor)) => Ok((major, minor)),
+        _ => bail!("invalid version `{input}`"),
+    }
+}
+
+/// Parse a `TZOFFSETFROM` or `TZOFFSETTO`.
+///
+/// Outlook writes UTC as `-0000`, which RFC 5545 doesn't allow. In lenient mode it is read as
+/// `+0000` with a warning.
+fn parse_utc_offset(parser: &mut Lexer<'_>, input: &Line<'_>) -> Result<UtcOffset> {
+    let negative_zero = input
+        .value
+        .strip_prefix('-')
+        .is_some_and(|rest| !rest.is_empty() && rest.bytes().all(|b| b == b'0'));
+    if negative_zero {
+        parser.recover(anyhow!(
+            "`{}` is not a valid UTC offset in {}",
+            input.value,
+            input.name
+        ))?;
+        return Ok(UtcOffset::from_seconds(0));
+    }
+    input.value.parse()
+}
+
+/// Parse a `CREATED`, `LAST-MODIFIED`, `DTSTAMP`, `COMPLETED` or `ACKNOWLEDGED` timestamp,
+/// which must be in UTC.
+///
+/// Some producers write fractional seconds (`20250704T100000.500Z`), which RFC 5545
+/// doesn't allow. In lenient mode the fraction is dropped with a warning, and local times
+/// are kept with a warning.
+fn parse_timestamp(parser: &mut Lexer<'_>, input: &Line<'_>) -> Result<DateTime> {
+    // some producers write the (default) value type explicitly
+    let value_type = input.params.iter().find(|(name, _)| **name == names::VALUE);
+    if let Some((_, value_type)) = value_type {
+        let value_type = value_type.clone().get_single()?;
+        if !value_type.eq_ignore_ascii_case("DATE-TIME") {
+            bail!("unexpected VALUE param {value_type} in {}", input.name);
+        }
+    }
+    let (rest, mut timestamp) = DateTime::parse(&input.value)?;
+    if !rest.is_empty() {
+        let Some(fraction) = rest.strip_prefix('.') else {
+            bail!("unexpected trailing input `{rest}` in {}", input.name);
+        };
+        let (digits, utc) = match fraction.strip_suffix('Z') {
+            Some(digits) => (digits, true),
+            None => (fraction, false),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            bail!("unexpected trailing input `{rest}` in {}", input.name);
+        }
+        parser.recover(anyhow!(
+            "fractional seconds are not allowed in {}: `{}`",
+            input.name,
+            input.value
+        ))?;
+        timestamp.time.utc = utc;
+    }
+    if let Err(error) = check_utc(&input.name.to_string(), &timestamp) {
+        parser.recover(error)?;
+    }
+    Ok(timestamp)
+}
+
 fn parse_cal_scale<'src>(input: Line<'src>) -> Result<CalScale<'src>> {
-    debug_assert_eq!(&input.name, "CALSCALE");
+    debug_assert_eq!(input.name, names::CALSCALE);
     if let Some(param) = input.first_iana_param() {
         bail!("unexpected param {param:?}");
     }
@@ -220,32 +651,81 @@ fn parse_annotated_text<'src>(mut input: Line<'src>) -> Result<AnnotatedText<'sr
     Ok(AnnotatedText {
         lang,
         altrep,
-        text: input.value,
+        text: unescape_text(input.value),
     })
 }
 
-fn parse_organizer<'src>(mut input: Line<'src>) -> Result<Organizer<'src>> {
+/// Some producers write a bare email address (`ORGANIZER:jsmith@example.com`), which isn't a
+/// URI. In lenient mode it is taken as a `mailto:` address with a warning.
+fn parse_organizer<'src>(parser: &mut Lexer<'_>, mut input: Line<'src>) -> Result<Organizer<'src>> {
     let common_name = input.params.take_ty::<CommonName<'src>>()?;
     let dir = input.params.take_ty()?;
     let sent_by = input.params.take_ty()?;
     let lang = input.params.take_ty()?;
-    let value = input.value.try_into()?;
+    let schedule_agent = input.params.take_ty()?;
+    let schedule_force_send = input.params.take_ty()?;
+    let schedule_status = input.params.take_ty()?;
+    let value = match CalendarUserAddress::try_from(input.value.clone()) {
+        Ok(value) => value,
+        Err(error) if looks_like_email(&input.value) => {
+            parser.recover(anyhow::Error::new(error).context(format!(
+                "ORGANIZER address without mailto: `{}`",
+                input.value
+            )))?;
+            CalendarUserAddress::try_from(Cow::Owned(format!("mailto:{}", input.value)))
+                .context("invalid ORGANIZER address")?
+        }
+        Err(error) => return Err(anyhow::Error::new(error).context("invalid ORGANIZER address")),
+    };
 
     Ok(Organizer {
         dir,
         common_name: common_name.map(|v| v.0),
         sent_by,
         lang,
+        schedule_agent,
+        schedule_force_send,
+        schedule_status,
         value,
     })
 }
 
+/// Whether `value` is shaped like `local@domain.tld`
+fn looks_like_email(value: &str) -> bool {
+    let Some((local, domain)) = value.split_once('@') else {
+        return false;
+    };
+    let valid = |part: &str| !part.is_empty() && !part.contains(['@', ':', ' ', '<', '>']);
+    valid(local) && valid(domain) && domain.contains('.')
+}
+
+// Each component allows a different set of `STATUS` values
+
 fn parse_event_status(input: Line<'_>) -> Result<EventStatus> {
     match &*input.value {
         "TENTATIVE" => Ok(EventStatus::Tentative),
         "CONFIRMED" => Ok(EventStatus::Confirmed),
         "CANCELLED" => Ok(EventStatus::Cancelled),
-        other => bail!("unexpected status {other}"),
+        other => bail!("unexpected status {other} in VEVENT"),
+    }
+}
+
+fn parse_todo_status(input: Line<'_>) -> Result<TodoStatus> {
+    match &*input.value {
+        "NEEDS-ACTION" => Ok(TodoStatus::NeedsAction),
+        "COMPLETED" => Ok(TodoStatus::Completed),
+        "IN-PROCESS" => Ok(TodoStatus::InProcess),
+        "CANCELLED" => Ok(TodoStatus::Cancelled),
+        other => bail!("unexpected status {other} in VTODO"),
+    }
+}
+
+fn parse_journal_status(input: Line<'_>) -> Result<JournalStatus> {
+    match &*input.value {
+        "DRAFT" => Ok(JournalStatus::Draft),
+        "FINAL" => Ok(JournalStatus::Final),
+        "CANCELLED" => Ok(JournalStatus::Cancelled),
+        other => bail!("unexpected status {other} in VJOURNAL"),
     }
 }
 
@@ -257,10 +737,10 @@ fn parse_time_transparency(input: Line<'_>) -> Result<TimeTransparency> {
     }
 }
 
-fn parse_recurrence_id<'src>(mut input: Line<'src>) -> Result<RecurrenceId<'src>> {
+fn parse_recurrence_id<'src>(mut input: Line<'src>, gregorian: bool) -> Result<RecurrenceId<'src>> {
     let range = input.params.take_ty()?;
     let timezone_id = input.params.take_ty()?;
-    let value = parse_date_or_datetime(&mut input)?;
+    let value = parse_date_or_datetime(&mut input, gregorian)?;
 
     Ok(RecurrenceId {
         range,
@@ -269,31 +749,96 @@ fn parse_recurrence_id<'src>(mut input: Line<'src>) -> Result<RecurrenceId<'src>
     })
 }
 
-fn parse_datetime_end<'src>(mut input: Line<'src>) -> Result<EventEnd<'src>> {
+fn parse_datetime_end<'src>(mut input: Line<'src>, gregorian: bool) -> Result<EventEnd<'src>> {
     let timezone_id = input.params.take_ty()?;
 
-    let value = parse_date_or_datetime(&mut input)?;
+    let value = parse_date_or_datetime(&mut input, gregorian)?;
 
     Ok(EventEnd::DateTime { value, timezone_id })
 }
 
-fn parse_attachment<'src>(mut input: Line<'src>) -> Result<Attachment<'src>> {
-    let fmt_type = input.params.take_ty()?;
-    let data = if let Some(v) = input.params.take(&VALUE_PARAM) {
-        let v = v.get_single()?;
-        if v != "BINARY" {
-            bail!("only BINARY value is allowed");
+fn parse_related_to(mut input: Line<'_>) -> Result<RelatedTo<'_>> {
+    let rel_type = input.params.take_ty()?.unwrap_or_default();
+    Ok(RelatedTo {
+        rel_type,
+        uid: input.value,
+    })
+}
+
+/// `TRIGGER`: a duration, or a UTC time with `VALUE=DATE-TIME`
+pub(crate) fn parse_trigger(mut input: Line<'_>) -> Result<AlarmTrigger> {
+    let is_datetime = match input.params.take(&names::VALUE) {
+        Some(value) => match &*value.get_single()? {
+            "DATE-TIME" => true,
+            "DURATION" => false,
+            other => bail!("unexpected TRIGGER VALUE param {other}"),
+        },
+        None => false,
+    };
+
+    if is_datetime {
+        if input
+            .params
+            .take_ty::<AlarmTriggerRelationship>()?
+            .is_some()
+        {
+            bail!("RELATED is only allowed on relative triggers");
         }
-        let Some(enc) = input.params.take(&ENCODING_PARAM) else {
+        let value = all_consuming(&input.value, DateTime::parse)?;
+        check_utc("TRIGGER", &value)?;
+        Ok(AlarmTrigger::Absolute(value))
+    } else {
+        let related = input.params.take_ty()?.unwrap_or_default();
+        let duration = all_consuming(&input.value, Duration::parse)?;
+        Ok(AlarmTrigger::Relative { related, duration })
+    }
+}
+
+/// Inline data is checked against `max_bytes` before being decoded
+fn parse_attachment<'src>(
+    parser: &mut Lexer<'src>,
+    mut input: Line<'src>,
+) -> Result<Attachment<'src>> {
+    // the media type is only informational, so a bad one needn't lose the attachment
+    let fmt_type = match input.params.take_ty() {
+        Ok(fmt_type) => fmt_type,
+        Err(error) => {
+            parser.recover(error)?;
+            None
+        }
+    };
+    let is_binary = match input.params.take(&names::VALUE) {
+        Some(value) => match &*value.get_single()? {
+            "BINARY" => true,
+            "URI" => false,
+            other => bail!("unexpected VALUE param {other}"),
+        },
+        None => false,
+    };
+    let data = if is_binary {
+        let Some(enc) = input.params.take(&names::ENCODING) else {
             bail!("cannot have VALUE without ENCODING");
         };
         let enc = enc.get_single()?;
         if enc != "BASE64" {
             bail!("only BASE64 encoding is allowed");
         }
-        Data::parse_blob(input.value)?
+        // every 4 characters of base64 are 3 bytes, less any `=` padding
+        let encoded_len = input.value.trim_end_matches('=').len();
+        let decoded_len = encoded_len / 4 * 3 + encoded_len % 4 * 3 / 4;
+        let max_bytes = parser.options().max_attachment_bytes;
+        if let Some(max_bytes) = max_bytes.filter(|max_bytes| decoded_len > *max_bytes) {
+            bail!("attachment of {decoded_len} bytes is larger than the limit of {max_bytes}");
+        }
+        Data::parse_blob(input.value).context("invalid attachment data")?
     } else {
-        Data::parse_uri(input.value)?
+        match Data::parse_uri(input.value.clone()) {
+            Ok(data) => data,
+            Err(error) => {
+                parser.recover(error.context(format!("`{}` is not a valid URI", input.value)))?;
+                Data::Raw(input.value)
+            }
+        }
     };
 
     Ok(Attachment { fmt_type, data })
@@ -318,6 +863,9 @@ fn parse_attendee<'src>(mut input: Line<'src>) -> Result<Attendee<'src>> {
     let cn = input.params.take_ty::<CommonName<'src>>()?;
     let dir = input.params.take_ty::<DirectoryEntryReference<'src>>()?;
     let lang = input.params.take_ty::<Language<'src>>()?;
+    let schedule_agent = input.params.take_ty()?;
+    let schedule_force_send = input.params.take_ty()?;
+    let schedule_status = input.params.take_ty()?;
 
     Ok(Attendee {
         cutype: cutype.unwrap_or_default(),
@@ -331,6 +879,11 @@ fn parse_attendee<'src>(mut input: Line<'src>) -> Result<Attendee<'src>> {
         common_name: cn.map(|v| v.0),
         dir,
         lang,
+        schedule_agent,
+        schedule_force_send,
+        schedule_status,
+        other_params: input.params.into_params().collect(),
+        value: CalendarUserAddress::try_from(input.value).context("invalid ATTENDEE address")?,
     })
 }
 
@@ -351,10 +904,43 @@ fn parse_comment<'src>(mut input: Line<'src>) -> Result<Comment<'src>> {
     Ok(Comment {
         lang,
         altrep,
-        value: input.value,
+        value: unescape_text(input.value),
+    })
+}
+
+fn parse_conference<'src>(mut input: Line<'src>) -> Result<Conference<'src>> {
+    // `VALUE=URI` is required, but we don't insist on it
+    if let Some(value) = input.params.take(&names::VALUE) {
+        let value = value.get_single()?;
+        if value != "URI" {
+            bail!("only URI value is allowed for CONFERENCE, found {value}");
+        }
+    }
+    let features = opt_vec_one_to_vec(input.params.take(&names::FEATURE));
+    let label = input
+        .params
+        .take(&names::LABEL)
+        .map(VecOne::get_single)
+        .transpose()?;
+    let uri = Uri::try_from(input.value).context("invalid CONFERENCE URI")?;
+    Ok(Conference {
+        uri,
+        features,
+        label,
     })
 }
 
+/// `URL`, which some producers write with the (default) `VALUE=URI`
+fn parse_url<'src>(mut input: Line<'src>) -> Result<Uri<'src>> {
+    if let Some(value) = input.params.take(&names::VALUE) {
+        let value = value.get_single()?;
+        if value != "URI" {
+            bail!("only URI value is allowed for URL, found {value}");
+        }
+    }
+    Ok(Uri::try_from(input.value)?)
+}
+
 fn parse_contact<'src>(mut input: Line<'src>) -> Result<Contact<'src>> {
     let lang = input.params.take_ty()?;
     let altrep = input.params.take_ty()?;
@@ -362,60 +948,158 @@ fn parse_contact<'src>(mut input: Line<'src>) -> Result<Contact<'src>> {
     Ok(Contact {
         lang,
         altrep,
-        value: input.value,
+        value: unescape_text(input.value),
     })
 }
 
-fn parse_exception_dates<'src>(mut input: Line<'src>) -> Result<ExceptionDateTimes<'src>> {
+fn parse_exception_dates<'src>(
+    mut input: Line<'src>,
+    gregorian: bool,
+) -> Result<ExceptionDateTimes<'src>> {
     let timezone_id = input.params.take_ty()?;
-    let values = parse_date_or_datetime_list(&mut input)?;
+    let values = parse_date_or_datetime_list(&mut input, gregorian)?;
     Ok(ExceptionDateTimes {
         timezone_id,
         values,
     })
 }
 
-struct CalendarBuilder<'src> {
+fn parse_recurrence_dates<'src>(
+    mut input: Line<'src>,
+    gregorian: bool,
+) -> Result<RecurrenceDates<'src>> {
+    let timezone_id = input.params.take_ty()?;
+    let values = parse_date_or_datetime_list(&mut input, gregorian)?;
+    Ok(RecurrenceDates {
+        timezone_id,
+        values,
+    })
+}
+
+/// Builds a [`Calendar`], either while parsing or for export.
+///
+/// ```
+/// # use icalendar::CalendarBuilder;
+/// let mut builder = CalendarBuilder::for_export("-//Example Corp.//Planner//EN");
+/// # let event = icalendar::parse("BEGIN:VCALENDAR\r\nPRODID:x\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:1\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n")?.remove(0).events.remove(0);
+/// builder.add_event(event);
+/// let calendar = builder.build()?;
+/// print!("{}", calendar.to_ics_string());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct CalendarBuilder<'src> {
     prod_id: Option<Cow<'src, str>>,
-    version_set: bool,
+    version: Option<Cow<'src, str>>,
     cal_scale: Option<CalScale<'src>>,
     method: Option<Cow<'src, str>>,
+    default_timezone: Option<Cow<'src, str>>,
+    name: Option<Cow<'src, str>>,
+    x_name: Option<Cow<'src, str>>,
     events: Vec<Event<'src>>,
+    todos: Vec<Todo<'src>>,
+    journals: Vec<Journal<'src>>,
+    timezones: Vec<TimeZone<'src>>,
+    skipped_components: Vec<SkippedComponent<'src>>,
 }
 
 impl<'src> CalendarBuilder<'src> {
     fn new() -> Self {
         Self {
             prod_id: None,
-            version_set: false,
+            version: None,
             cal_scale: None,
             method: None,
+            default_timezone: None,
+            name: None,
+            x_name: None,
             events: vec![],
+            todos: vec![],
+            journals: vec![],
+            timezones: vec![],
+            skipped_components: vec![],
+        }
+    }
+
+    /// Start a calendar for export, produced by `prod_id` (e.g. `-//Example Corp.//Planner//EN`).
+    pub fn for_export(prod_id: impl Into<Cow<'src, str>>) -> Self {
+        Self {
+            prod_id: Some(prod_id.into()),
+            version: Some(Cow::Borrowed("2.0")),
+            ..Self::new()
+        }
+    }
+
+    pub fn add_event(&mut self, event: Event<'src>) {
+        self.events.push(event);
+    }
+
+    /// Add an event, along with definitions of the timezones it refers to by `TZID`.
+    ///
+    /// Definitions are generated from the bundled timezone database, unless one with the same
+    /// id has already been added. Fails if a timezone is not in the database.
+    #[cfg(feature = "tzdb")]
+    pub fn add_event_with_timezone(
+        &mut self,
+        event: Event<'src>,
+    ) -> Result<(), crate::TimeZoneError> {
+        for id in event.timezone_ids() {
+            if !self.timezones.iter().any(|timezone| timezone.id == id.id()) {
+                let timezone = TimeZone::from_tzdb(id.id()).map_err(|source| {
+                    crate::TimeZoneError::Export {
+                        id: id.id().to_string(),
+                        source: source.into(),
+                    }
+                })?;
+                self.timezones.push(timezone);
+            }
         }
+        self.events.push(event);
+        Ok(())
+    }
+
+    pub fn add_todo(&mut self, todo: Todo<'src>) {
+        self.todos.push(todo);
+    }
+
+    pub fn add_journal(&mut self, journal: Journal<'src>) {
+        self.journals.push(journal);
+    }
+
+    pub fn add_timezone(&mut self, timezone: TimeZone<'src>) {
+        self.timezones.push(timezone);
     }
 
-    fn build(self) -> Result<Calendar<'src>> {
+    pub fn build(self) -> Result<Calendar<'src>> {
         Ok(Calendar {
             prod_id: self
                 .prod_id
-                .ok_or_else(|| anyhow!("PRODID not specified"))?,
+                .ok_or_else(|| ParseErrorKind::missing("VCALENDAR", "PRODID"))?,
+            version: self.version.unwrap_or(Cow::Borrowed("2.0")),
             cal_scale: self.cal_scale.unwrap_or_default(),
             method: self.method,
+            default_timezone: self.default_timezone,
+            name: self.name.or(self.x_name),
             events: self.events,
+            todos: self.todos,
+            journals: self.journals,
+            timezones: self.timezones,
+            skipped_components: self.skipped_components,
         })
     }
 
     impl_set_01!(prod_id, set_prod_id, Cow<'src, str>, "PRODID");
 
-    fn set_version(&mut self, (): ()) -> Result {
-        if self.version_set {
-            bail!("expected 1 VERSION, found at least 2");
-        }
-        Ok(())
-    }
+    impl_set_1!(version, set_version, Cow<'src, str>, "VERSION");
 
     impl_set_01!(cal_scale, set_cal_scale, CalScale<'src>, "CALSCALE");
     impl_set_01!(method, set_method, Cow<'src, str>, "METHOD");
+    impl_set_01!(
+        default_timezone,
+        set_default_timezone,
+        Cow<'src, str>,
+        names::X_WR_TIMEZONE
+    );
+    impl_set_01!(x_name, set_x_name, Cow<'src, str>, names::X_WR_CALNAME);
 }
 
 #[derive(Default)]
@@ -424,6 +1108,7 @@ pub struct EventBuilder<'src> {
     created: Option<DateTime>,
     description: Option<AnnotatedText<'src>>,
     start: Option<DateOrDateTime>,
+    start_timezone_id: Option<TimeZoneIdentifier<'src>>,
     geo: Option<GeoLocation>,
     last_modified: Option<DateTime>,
     location: Option<AnnotatedText<'src>>,
@@ -435,29 +1120,29 @@ pub struct EventBuilder<'src> {
     summary: Option<AnnotatedText<'src>>,
     time_transparency: Option<TimeTransparency>,
     uid: Option<Cow<'src, str>>,
+    url: Option<Uri<'src>>,
     recurrence_id: Option<RecurrenceId<'src>>,
     end: Option<EventEnd<'src>>,
+    color: Option<Cow<'src, str>>,
     attachments: Vec<Attachment<'src>>,
     attendees: Vec<Attendee<'src>>,
     categories: Vec<Categories<'src>>,
     comments: Vec<Comment<'src>>,
     contacts: Vec<Contact<'src>>,
+    conferences: Vec<Conference<'src>>,
     exception_dates: Vec<ExceptionDateTimes<'src>>,
+    recurrence_rules: Vec<Recur>,
+    recurrence_dates: Vec<RecurrenceDates<'src>>,
+    structured_locations: Vec<StructuredLocation<'src>>,
+    alarms: Vec<Alarm<'src>>,
+    extensions: Vec<ExtensionProperty<'src>>,
 }
 
 impl<'src> EventBuilder<'src> {
     impl_set_01!(class, set_class, Class<'src>, "CLASS");
+    impl_set_01!(color, set_color, Cow<'src, str>, "COLOR");
 
-    fn set_created(&mut self, created: DateTime) -> Result {
-        if self.created.is_some() {
-            bail!("expected 0..=1 CREATED, found at least 2");
-        }
-        if !created.time.utc {
-            bail!("expected UTC time");
-        }
-        self.created = Some(created);
-        Ok(())
-    }
+    impl_set_01!(created, set_created, DateTime, "CREATED");
 
     impl_set_01!(
         description,
@@ -472,7 +1157,7 @@ impl<'src> EventBuilder<'src> {
     impl_set_01!(organizer, set_organizer, Organizer<'src>, "ORGANIZER");
     impl_set_01!(priority, set_priority, Priority, "PRIORITY");
     impl_set_01!(timestamp, set_timestamp, DateTime, "DTSTAMP");
-    impl_set_01!(sequence, set_sequence, u64, "SEQ");
+    impl_set_01!(sequence, set_sequence, u64, "SEQUENCE");
     impl_set_01!(status, set_status, EventStatus, "STATUS");
     impl_set_01!(summary, set_summary, AnnotatedText<'src>, "SUMMARY");
     impl_set_01!(
@@ -482,6 +1167,7 @@ impl<'src> EventBuilder<'src> {
         "TRANSP"
     );
     impl_set_1!(uid, set_uid, Cow<'src, str>, "UID");
+    impl_set_01!(url, set_url, Uri<'src>, "URL");
     impl_set_01!(
         recurrence_id,
         set_recurrence_id,
@@ -500,13 +1186,14 @@ impl<'src> EventBuilder<'src> {
 
     fn build(self) -> Result<Event<'src>> {
         let Some(uid) = self.uid else {
-            bail!("missing UID on VEVENT");
+            return Err(ParseErrorKind::missing("VEVENT", "UID"));
         };
         Ok(Event {
             class: self.class.unwrap_or_default(),
             created: self.created,
             description: self.description,
             start: self.start,
+            start_timezone_id: self.start_timezone_id,
             geo_location: self.geo,
             last_modified: self.last_modified,
             location: self.location,
@@ -516,16 +1203,1185 @@ impl<'src> EventBuilder<'src> {
             sequence: self.sequence,
             status: self.status,
             summary: self.summary,
-            time_transparency: self.time_transparency.unwrap_or_default(),
+            time_transparency: self.time_transparency,
             uid,
+            url: self.url,
             recurrence_id: self.recurrence_id,
             end: self.end,
+            color: self.color,
             attachments: self.attachments,
             attendees: self.attendees,
             categories: self.categories,
             comments: self.comments,
             contacts: self.contacts,
+            conferences: self.conferences,
             exception_dates: self.exception_dates,
+            recurrence_rules: self.recurrence_rules,
+            recurrence_dates: self.recurrence_dates,
+            structured_locations: self.structured_locations,
+            alarms: self.alarms,
+            extensions: self.extensions,
         })
     }
 }
+
+#[derive(Default)]
+struct AlarmBuilder<'src> {
+    action: Option<Cow<'src, str>>,
+    trigger: Option<AlarmTrigger>,
+    description: Option<AnnotatedText<'src>>,
+    summary: Option<AnnotatedText<'src>>,
+    duration: Option<Duration>,
+    repeat: Option<u32>,
+    attendees: Vec<Attendee<'src>>,
+    attachments: Vec<Attachment<'src>>,
+    uid: Option<Cow<'src, str>>,
+    acknowledged: Option<DateTime>,
+    related_to: Vec<RelatedTo<'src>>,
+    extensions: Vec<ExtensionProperty<'src>>,
+}
+
+impl<'src> AlarmBuilder<'src> {
+    impl_set_1!(action, set_action, Cow<'src, str>, "ACTION");
+    impl_set_1!(trigger, set_trigger, AlarmTrigger, "TRIGGER");
+    impl_set_01!(
+        description,
+        set_description,
+        AnnotatedText<'src>,
+        "DESCRIPTION"
+    );
+    impl_set_01!(summary, set_summary, AnnotatedText<'src>, "SUMMARY");
+    impl_set_01!(duration, set_duration, Duration, "DURATION");
+    impl_set_01!(repeat, set_repeat, u32, "REPEAT");
+    impl_set_01!(uid, set_uid, Cow<'src, str>, "UID");
+
+    impl_set_01!(acknowledged, set_acknowledged, DateTime, "ACKNOWLEDGED");
+
+    fn build(self) -> Result<Alarm<'src>> {
+        let Some(action) = self.action else {
+            return Err(ParseErrorKind::missing("VALARM", "ACTION"));
+        };
+        let Some(trigger) = self.trigger else {
+            return Err(ParseErrorKind::missing("VALARM", "TRIGGER"));
+        };
+        if self.duration.is_some() != self.repeat.is_some() {
+            bail!("expected both or neither of DURATION and REPEAT in VALARM");
+        }
+        Ok(Alarm {
+            action,
+            trigger,
+            description: self.description,
+            summary: self.summary,
+            duration: self.duration,
+            repeat: self.repeat,
+            attendees: self.attendees,
+            attachments: self.attachments,
+            uid: self.uid,
+            acknowledged: self.acknowledged,
+            related_to: self.related_to,
+            extensions: self.extensions,
+        })
+    }
+}
+
+#[derive(Default)]
+struct TimeZoneBuilder<'src> {
+    id: Option<Cow<'src, str>>,
+    last_modified: Option<DateTime>,
+    url: Option<Cow<'src, str>>,
+    observances: Vec<Observance<'src>>,
+}
+
+impl<'src> TimeZoneBuilder<'src> {
+    impl_set_1!(id, set_id, Cow<'src, str>, "TZID");
+    impl_set_01!(last_modified, set_last_modified, DateTime, "LAST-MODIFIED");
+    impl_set_01!(url, set_url, Cow<'src, str>, "TZURL");
+
+    fn build(self) -> Result<TimeZone<'src>> {
+        let Some(id) = self.id else {
+            return Err(ParseErrorKind::missing("VTIMEZONE", "TZID"));
+        };
+        if self.observances.is_empty() {
+            return Err(ParseErrorKind::missing("VTIMEZONE", "STANDARD or DAYLIGHT"));
+        }
+        Ok(TimeZone {
+            id,
+            last_modified: self.last_modified,
+            url: self.url,
+            observances: self.observances,
+        })
+    }
+}
+
+#[derive(Default)]
+struct ObservanceBuilder<'src> {
+    start: Option<DateTime>,
+    offset_from: Option<UtcOffset>,
+    offset_to: Option<UtcOffset>,
+    recurrence_rules: Vec<Recur>,
+    recurrence_dates: Vec<DateTime>,
+    names: Vec<AnnotatedText<'src>>,
+    comments: Vec<Comment<'src>>,
+}
+
+impl<'src> ObservanceBuilder<'src> {
+    impl_set_1!(start, set_start, DateTime, "DTSTART");
+    impl_set_1!(offset_from, set_offset_from, UtcOffset, "TZOFFSETFROM");
+    impl_set_1!(offset_to, set_offset_to, UtcOffset, "TZOFFSETTO");
+
+    fn build(self, kind: ObservanceKind) -> Result<Observance<'src>> {
+        let component = match kind {
+            ObservanceKind::Standard => "STANDARD",
+            ObservanceKind::Daylight => "DAYLIGHT",
+        };
+        let missing = |property| ParseErrorKind::missing(component, property);
+        let start = self.start.ok_or_else(|| missing("DTSTART"))?;
+        let offset_from = self.offset_from.ok_or_else(|| missing("TZOFFSETFROM"))?;
+        let offset_to = self.offset_to.ok_or_else(|| missing("TZOFFSETTO"))?;
+        Ok(Observance {
+            kind,
+            start,
+            offset_from,
+            offset_to,
+            recurrence_rules: self.recurrence_rules,
+            recurrence_dates: self.recurrence_dates,
+            names: self.names,
+            comments: self.comments,
+        })
+    }
+}
+
+#[derive(Default)]
+struct StructuredLocationBuilder<'src> {
+    uid: Option<Cow<'src, str>>,
+    name: Option<Cow<'src, str>>,
+    description: Option<AnnotatedText<'src>>,
+    location_type: Option<Cow<'src, str>>,
+    geo_location: Option<GeoLocation>,
+    url: Option<Cow<'src, str>>,
+}
+
+impl<'src> StructuredLocationBuilder<'src> {
+    impl_set_1!(uid, set_uid, Cow<'src, str>, "UID");
+    impl_set_01!(name, set_name, Cow<'src, str>, "NAME");
+    impl_set_01!(
+        description,
+        set_description,
+        AnnotatedText<'src>,
+        "DESCRIPTION"
+    );
+    impl_set_01!(
+        location_type,
+        set_location_type,
+        Cow<'src, str>,
+        "LOCATION-TYPE"
+    );
+    impl_set_01!(geo_location, set_geo_location, GeoLocation, "GEO");
+    impl_set_01!(url, set_url, Cow<'src, str>, "URL");
+
+    fn build(self) -> Result<StructuredLocation<'src>> {
+        let Some(uid) = self.uid else {
+            return Err(ParseErrorKind::missing("VLOCATION", "UID"));
+        };
+        Ok(StructuredLocation {
+            uid,
+            name: self.name,
+            description: self.description,
+            location_type: self.location_type,
+            geo_location: self.geo_location,
+            url: self.url,
+        })
+    }
+}
+
+#[derive(Default)]
+struct JournalBuilder<'src> {
+    uid: Option<Cow<'src, str>>,
+    timestamp: Option<DateTime>,
+    start: Option<DateOrDateTime>,
+    start_timezone_id: Option<TimeZoneIdentifier<'src>>,
+    summary: Option<AnnotatedText<'src>>,
+    descriptions: Vec<AnnotatedText<'src>>,
+    categories: Vec<Categories<'src>>,
+    status: Option<JournalStatus>,
+    extensions: Vec<ExtensionProperty<'src>>,
+}
+
+impl<'src> JournalBuilder<'src> {
+    impl_set_1!(uid, set_uid, Cow<'src, str>, "UID");
+    impl_set_01!(timestamp, set_timestamp, DateTime, "DTSTAMP");
+    impl_set_01!(start, set_start, DateOrDateTime, "DTSTART");
+    impl_set_01!(summary, set_summary, AnnotatedText<'src>, "SUMMARY");
+    impl_set_01!(status, set_status, JournalStatus, "STATUS");
+
+    fn build(self) -> Result<Journal<'src>> {
+        let Some(uid) = self.uid else {
+            return Err(ParseErrorKind::missing("VJOURNAL", "UID"));
+        };
+        Ok(Journal {
+            uid,
+            timestamp: self.timestamp,
+            start: self.start,
+            start_timezone_id: self.start_timezone_id,
+            summary: self.summary,
+            descriptions: self.descriptions,
+            categories: self.categories,
+            status: self.status,
+            extensions: self.extensions,
+        })
+    }
+}
+
+#[derive(Default)]
+struct TodoBuilder<'src> {
+    completed: Option<DateTime>,
+    created: Option<DateTime>,
+    description: Option<AnnotatedText<'src>>,
+    start: Option<DateOrDateTime>,
+    start_timezone_id: Option<TimeZoneIdentifier<'src>>,
+    due: Option<DateOrDateTime>,
+    due_timezone_id: Option<TimeZoneIdentifier<'src>>,
+    duration: Option<Duration>,
+    last_modified: Option<DateTime>,
+    percent_complete: Option<u8>,
+    priority: Option<Priority>,
+    timestamp: Option<DateTime>,
+    sequence: Option<u64>,
+    summary: Option<AnnotatedText<'src>>,
+    status: Option<TodoStatus>,
+    uid: Option<Cow<'src, str>>,
+}
+
+impl<'src> TodoBuilder<'src> {
+    impl_set_01!(completed, set_completed, DateTime, "COMPLETED");
+
+    impl_set_01!(created, set_created, DateTime, "CREATED");
+
+    impl_set_01!(
+        description,
+        set_description,
+        AnnotatedText<'src>,
+        "DESCRIPTION"
+    );
+    impl_set_01!(start, set_start, DateOrDateTime, "DTSTART");
+    impl_set_01!(due, set_due, DateOrDateTime, "DUE");
+    impl_set_01!(duration, set_duration, Duration, "DURATION");
+    impl_set_01!(last_modified, set_last_modified, DateTime, "LAST-MODIFIED");
+    impl_set_01!(
+        percent_complete,
+        set_percent_complete,
+        u8,
+        "PERCENT-COMPLETE"
+    );
+    impl_set_01!(priority, set_priority, Priority, "PRIORITY");
+    impl_set_01!(timestamp, set_timestamp, DateTime, "DTSTAMP");
+    impl_set_01!(sequence, set_sequence, u64, "SEQUENCE");
+    impl_set_01!(status, set_status, TodoStatus, "STATUS");
+    impl_set_01!(summary, set_summary, AnnotatedText<'src>, "SUMMARY");
+    impl_set_1!(uid, set_uid, Cow<'src, str>, "UID");
+
+    fn build(self) -> Result<Todo<'src>> {
+        let Some(uid) = self.uid else {
+            return Err(ParseErrorKind::missing("VTODO", "UID"));
+        };
+        check_due_duration(self.due.is_some(), self.duration.is_some())?;
+        Ok(Todo {
+            completed: self.completed,
+            created: self.created,
+            last_modified: self.last_modified,
+            description: self.description,
+            start: self.start,
+            start_timezone_id: self.start_timezone_id,
+            due: self.due,
+            due_timezone_id: self.due_timezone_id,
+            duration: self.duration,
+            percent_complete: self.percent_complete,
+            priority: self.priority,
+            timestamp: self.timestamp,
+            sequence: self.sequence,
+            status: self.status,
+            summary: self.summary,
+            uid,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{
+        AlarmTrigger, Attachment,
+        params::{AlarmTriggerRelationship, ScheduleAgent, ScheduleForceSend, ScheduleStatus},
+        parser::{Lexer, Line, ParseOptions, parse_attachment, parse_trigger},
+        test_util,
+        types::{Data, DateOrDateTime, DateTime, VecOne},
+    };
+
+    fn trigger(line: &str) -> anyhow::Result<AlarmTrigger> {
+        parse_trigger(Line::parse(line).unwrap())
+    }
+
+    #[test]
+    fn relative_trigger() {
+        let AlarmTrigger::Relative { related, duration } = trigger("TRIGGER:-PT15M").unwrap()
+        else {
+            panic!("expected a relative trigger");
+        };
+        assert_eq!(related, AlarmTriggerRelationship::Start);
+        assert_eq!(duration.to_string(), "-PT15M");
+
+        let AlarmTrigger::Relative { related, duration } =
+            trigger("TRIGGER;RELATED=END:PT5M").unwrap()
+        else {
+            panic!("expected a relative trigger");
+        };
+        assert_eq!(related, AlarmTriggerRelationship::End);
+        assert_eq!(duration.to_string(), "PT5M");
+
+        assert!(trigger("TRIGGER;RELATED=MIDDLE:PT5M").is_err());
+        assert!(trigger("TRIGGER:20250101T000000Z").is_err());
+    }
+
+    #[test]
+    fn absolute_trigger() {
+        let AlarmTrigger::Absolute(at) =
+            trigger("TRIGGER;VALUE=DATE-TIME:20250101T000000Z").unwrap()
+        else {
+            panic!("expected an absolute trigger");
+        };
+        assert_eq!(at.to_string(), "20250101T000000Z");
+
+        // must be UTC
+        assert!(trigger("TRIGGER;VALUE=DATE-TIME:20250101T000000").is_err());
+        assert!(trigger("TRIGGER;VALUE=DATE-TIME;RELATED=END:20250101T000000Z").is_err());
+    }
+
+    fn attach(line: &str, options: ParseOptions) -> anyhow::Result<Attachment<'_>> {
+        let mut parser = Lexer::with_options("", options);
+        parse_attachment(&mut parser, Line::parse(line).unwrap())
+    }
+
+    #[test]
+    fn attachment_limit() {
+        // "hello world"
+        let line = "ATTACH;ENCODING=BASE64;VALUE=BINARY:aGVsbG8gd29ybGQ=";
+        let limit = |max| ParseOptions::default().max_attachment_bytes(max);
+        let attachment = attach(line, ParseOptions::default()).unwrap();
+        assert_eq!(attachment.data, Data::Blob(b"hello world".to_vec()));
+        assert!(attach(line, limit(11)).is_ok());
+        let error = attach(line, limit(10)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "attachment of 11 bytes is larger than the limit of 10"
+        );
+        // "hello worl", padded with `==`
+        let padded = "ATTACH;ENCODING=BASE64;VALUE=BINARY:aGVsbG8gd29ybA==";
+        assert!(attach(padded, limit(10)).is_ok());
+
+        // links aren't limited
+        let link = "ATTACH:https://example.com/large.pdf";
+        assert!(attach(link, limit(0)).is_ok());
+    }
+
+    #[test]
+    fn attachment_uri() {
+        let link = "ATTACH;VALUE=URI:https://example.com/large.pdf";
+        let attachment = attach(link, ParseOptions::default()).unwrap();
+        assert!(matches!(attachment.data, Data::Uri(_)));
+
+        // Apple writes alarm sound names, which aren't URIs
+        let sound = "ATTACH;VALUE=URI:Chord";
+        assert!(attach(sound, ParseOptions::default()).is_err());
+        let attachment = attach(sound, ParseOptions::default().lenient()).unwrap();
+        assert_eq!(attachment.data, Data::Raw("Chord".into()));
+    }
+
+    #[test]
+    fn version() {
+        let calendar = |version: &str| {
+            format!(
+                "BEGIN:VCALENDAR\r\n\
+                PRODID:-//Example Corp.//CalDAV Client//EN\r\n\
+                VERSION:{version}\r\n\
+                END:VCALENDAR\r\n"
+            )
+        };
+        let lenient = crate::ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+
+        assert_eq!(crate::parse(&calendar("2.0")).unwrap()[0].version, "2.0");
+        assert_eq!(crate::parse(&calendar("2")).unwrap()[0].version, "2");
+        assert!(crate::parse(&calendar("2.0;2.0")).is_ok());
+        assert!(crate::parse(&calendar("2.x")).is_err());
+        assert!(crate::parse(&calendar("1.0;2.0")).is_ok());
+        assert!(crate::parse(&calendar("2.0;3.1")).is_ok());
+        assert!(crate::parse(&calendar("2.1;3.0")).is_err());
+
+        let old = calendar("1.0");
+        assert!(crate::parse(&old).is_err());
+        let parsed = crate::parse_with(&old, lenient.clone()).unwrap();
+        assert_eq!(parsed.calendars.len(), 1);
+        assert_eq!(
+            parsed.warnings,
+            [crate::Warning {
+                line: 3,
+                message: "unsupported VERSION 1.0: only 2.0 is supported".into(),
+            }]
+        );
+
+        let current = calendar("2.0");
+        let parsed = crate::parse_with(&current, lenient).unwrap();
+        assert!(parsed.warnings.is_empty());
+
+        // written back as read
+        let range = calendar("1.0;2.0");
+        let range = crate::parse(&range).unwrap().remove(0);
+        assert!(range.to_ics_string().contains("\r\nVERSION:1.0;2.0\r\n"));
+    }
+
+    #[test]
+    fn default_options() {
+        let input = test_util::event(
+            "DTSTART;TZID=Europe/Paris:20240710T140000\r\n\
+            SUMMARY:Lunch\r\n\
+            X-VENDOR-FLAG:yes\r\n",
+        );
+        let parsed = crate::parse_with(&input, Default::default()).unwrap();
+        assert_eq!(parsed.calendars, crate::parse(&input).unwrap());
+        assert!(parsed.warnings.is_empty());
+
+        let options = crate::ParseOptions::default().lenient().keep_unknown(false);
+        assert!(options.lenient);
+        assert!(!options.keep_unknown);
+        assert!(options.parse_timezones);
+        let parsed = crate::parse_with(&input, options).unwrap();
+        assert!(parsed.calendars[0].events[0].extensions.is_empty());
+    }
+
+    #[test]
+    fn alarm_acknowledged() {
+        let input = test_util::event(
+            "BEGIN:VALARM\r\n\
+            UID:E157A1FC-06BB-4495-933E-4E99F79A0649\r\n\
+            ACTION:DISPLAY\r\n\
+            TRIGGER;RELATED=END:-PT15M\r\n\
+            DESCRIPTION:Event reminder\r\n\
+            ACKNOWLEDGED:20250704T094500Z\r\n\
+            END:VALARM\r\n",
+        );
+        let event = test_util::parse_event(&input);
+        let alarm = &event.alarms[0];
+        assert_eq!(
+            alarm.uid.as_deref(),
+            Some("E157A1FC-06BB-4495-933E-4E99F79A0649")
+        );
+        assert_eq!(alarm.action, "DISPLAY");
+        assert_eq!(
+            alarm.trigger,
+            AlarmTrigger::Relative {
+                related: AlarmTriggerRelationship::End,
+                duration: "-PT15M".parse().unwrap(),
+            }
+        );
+        assert_eq!(
+            alarm.acknowledged,
+            Some(DateTime::parse("20250704T094500Z").unwrap().1)
+        );
+
+        let output = crate::parse(&input).unwrap()[0].to_ics_string();
+        assert_eq!(crate::parse(&output).unwrap()[0].events[0], event);
+
+        let input = test_util::event(
+            "BEGIN:VALARM\r\n\
+            ACTION:DISPLAY\r\n\
+            TRIGGER:-PT15M\r\n\
+            ACKNOWLEDGED:20250704T094500\r\n\
+            END:VALARM\r\n",
+        );
+        let parsed = crate::parse_with(&input, Default::default()).unwrap();
+        assert!(parsed.calendars[0].events[0].alarms.is_empty());
+        assert_eq!(parsed.warnings.len(), 1);
+    }
+
+    #[test]
+    fn alarm_repeat() {
+        let input = test_util::event(
+            "BEGIN:VALARM\r\n\
+            ACTION:EMAIL\r\n\
+            TRIGGER:-P2D\r\n\
+            SUMMARY:Reminder\r\n\
+            DESCRIPTION:The dentist is in two days\r\n\
+            ATTENDEE:mailto:jane@example.com\r\n\
+            ATTACH;FMTTYPE=application/pdf:https://example.com/directions.pdf\r\n\
+            DURATION:PT1H\r\n\
+            REPEAT:2\r\n\
+            END:VALARM\r\n",
+        );
+        let event = test_util::parse_event(&input);
+        let alarm = &event.alarms[0];
+        assert_eq!(alarm.duration, Some("PT1H".parse().unwrap()));
+        assert_eq!(alarm.repeat, Some(2));
+        assert_eq!(alarm.attendees.len(), 1);
+        assert_eq!(
+            alarm.attachments[0].media_type().as_deref(),
+            Some("application/pdf")
+        );
+
+        let output = crate::parse(&input).unwrap()[0].to_ics_string();
+        assert!(output.contains("REPEAT:2\r\n"));
+        assert_eq!(crate::parse(&output).unwrap()[0].events[0], event);
+
+        // repeats need both, and alarms need a trigger, but either only skips the alarm
+        for (from, to) in [("REPEAT:2\r\n", ""), ("TRIGGER:-P2D\r\n", "")] {
+            let input = input.replace(from, to);
+            let parsed = crate::parse_with(&input, Default::default()).unwrap();
+            assert!(parsed.calendars[0].events[0].alarms.is_empty());
+            assert!(parsed.warnings[0].message.starts_with("skipped VALARM: "));
+        }
+    }
+
+    #[test]
+    fn alarm_snooze() {
+        let input = test_util::event(
+            "BEGIN:VALARM\r\n\
+            UID:original\r\n\
+            ACTION:DISPLAY\r\n\
+            TRIGGER:-PT15M\r\n\
+            ACKNOWLEDGED:20250704T094500Z\r\n\
+            END:VALARM\r\n\
+            BEGIN:VALARM\r\n\
+            UID:snooze\r\n\
+            RELATED-TO;RELTYPE=SNOOZE:original\r\n\
+            ACTION:DISPLAY\r\n\
+            TRIGGER;VALUE=DATE-TIME:20250704T095000Z\r\n\
+            END:VALARM\r\n",
+        );
+        let event = test_util::parse_event(&input);
+        assert_eq!(event.alarms[0].is_snooze_of(), None);
+        assert_eq!(event.alarms[1].is_snooze_of(), Some("original"));
+
+        let output = crate::parse(&input).unwrap()[0].to_ics_string();
+        assert!(output.contains("RELATED-TO;RELTYPE=SNOOZE:original\r\n"));
+        assert_eq!(crate::parse(&output).unwrap()[0].events[0], event);
+    }
+
+    #[test]
+    fn todo_status() {
+        let todo_input = |status: &str| {
+            test_util::calendar(&format!(
+                "BEGIN:VTODO\r\n\
+                UID:1\r\n\
+                STATUS:{status}\r\n\
+                END:VTODO\r\n"
+            ))
+        };
+        let input = todo_input("COMPLETED");
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        assert_eq!(calendar.todos[0].status, Some(crate::TodoStatus::Completed));
+        let output = calendar.to_ics_string();
+        assert!(output.contains("STATUS:COMPLETED\r\n"));
+
+        let input = todo_input("CONFIRMED");
+        let error = crate::parse(&input).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 6: unexpected status CONFIRMED in VTODO"
+        );
+
+        // lenient parsing skips the to-do instead
+        let options = crate::ParseOptions::default().lenient();
+        let parsed = crate::parse_with(&input, options).unwrap();
+        assert!(parsed.calendars[0].todos.is_empty());
+        assert_eq!(
+            parsed.warnings[0].to_string(),
+            "line 6: skipped VTODO: unexpected status CONFIRMED in VTODO"
+        );
+    }
+
+    #[test]
+    fn todo_timezones() {
+        let input = test_util::calendar(
+            "BEGIN:VTODO\r\n\
+            UID:1\r\n\
+            DTSTART;TZID=Europe/Berlin:20250704T090000\r\n\
+            DUE;TZID=America/New_York:20250705T170000\r\n\
+            END:VTODO\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let todo = &calendar.todos[0];
+        let start_id = todo.start_timezone_id.as_ref().map(|id| id.id());
+        assert_eq!(start_id, Some("Europe/Berlin"));
+        let due_id = todo.due_timezone_id.as_ref().map(|id| id.id());
+        assert_eq!(due_id, Some("America/New_York"));
+
+        let output = calendar.to_ics_string();
+        assert!(output.contains("DTSTART;TZID=Europe/Berlin:20250704T090000\r\n"));
+        assert!(output.contains("DUE;TZID=America/New_York:20250705T170000\r\n"));
+        assert_eq!(crate::parse(&output).unwrap()[0].todos[0], *todo);
+    }
+
+    #[test]
+    fn journal() {
+        let input = test_util::calendar(
+            "BEGIN:VJOURNAL\r\n\
+            UID:journal@example.com\r\n\
+            DTSTAMP:20250704T180000Z\r\n\
+            DTSTART;TZID=Europe/Berlin:20250704T090000\r\n\
+            SUMMARY:Staff meeting minutes\r\n\
+            DESCRIPTION:1. Staff meeting: Participants include Joe and Lisa\r\n\
+            DESCRIPTION:2. Budget: nothing to report\r\n\
+            CATEGORIES:MEETING\r\n\
+            STATUS:FINAL\r\n\
+            X-MINUTES-BY:Lisa\r\n\
+            END:VJOURNAL\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let journal = &calendar.journals[0];
+        assert_eq!(journal.uid, "journal@example.com");
+        assert_eq!(journal.status, Some(crate::JournalStatus::Final));
+        let start_id = journal.start_timezone_id.as_ref().map(|id| id.id());
+        assert_eq!(start_id, Some("Europe/Berlin"));
+        assert_eq!(journal.extensions[0].value, "Lisa");
+        let descriptions: Vec<_> = journal
+            .descriptions
+            .iter()
+            .map(|description| &*description.text)
+            .collect();
+        assert_eq!(
+            descriptions,
+            [
+                "1. Staff meeting: Participants include Joe and Lisa",
+                "2. Budget: nothing to report"
+            ]
+        );
+
+        let output = calendar.to_ics_string();
+        assert!(output.contains("DTSTART;TZID=Europe/Berlin:20250704T090000\r\n"));
+        assert_eq!(crate::parse(&output).unwrap(), [calendar]);
+
+        // a journal without a `UID` is skipped in lenient mode
+        let input = input.replace("UID:journal@example.com\r\n", "");
+        assert!(crate::parse(&input).is_err());
+        let options = crate::ParseOptions::default().lenient();
+        let parsed = crate::parse_with(&input, options).unwrap();
+        assert!(parsed.calendars[0].journals.is_empty());
+        assert_eq!(
+            parsed.warnings[0].to_string(),
+            "line 13: skipped VJOURNAL: missing UID on VJOURNAL"
+        );
+    }
+
+    #[test]
+    fn url() {
+        let input = test_util::event("URL;VALUE=URI:https://example.com/fireworks\r\n");
+        let event = test_util::parse_event(&input);
+        let url = event.url.as_ref().map(ToString::to_string);
+        assert_eq!(url.as_deref(), Some("https://example.com/fireworks"));
+
+        let output = crate::parse(&input).unwrap()[0].to_ics_string();
+        assert!(output.contains("URL:https://example.com/fireworks\r\n"));
+        assert_eq!(crate::parse(&output).unwrap()[0].events[0], event);
+    }
+
+    #[test]
+    fn strict_names() {
+        let input = test_util::event("X-ÜBER:yes\r\n");
+        let event = test_util::parse_event(&input);
+        assert_eq!(event.extensions.len(), 1);
+
+        let options = crate::ParseOptions::default().strict_names(true);
+        let error = crate::parse_with(&input, options).unwrap_err();
+        assert_eq!(error.to_string(), "line 7: X-ÜBER is not a valid name");
+
+        let input = test_util::event("ATTENDEE;X-ÉTAT=1:mailto:a@example.com\r\n");
+        test_util::parse_event(&input);
+        let options = crate::ParseOptions::default().strict_names(true);
+        assert!(crate::parse_with(&input, options).is_err());
+    }
+
+    #[test]
+    fn fractional_seconds() {
+        let input = test_util::event(
+            "CREATED:20250704T100000.500Z\r\n\
+            LAST-MODIFIED:20250704T100000.25\r\n",
+        );
+        let error = crate::parse(&input).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 7: fractional seconds are not allowed in CREATED: `20250704T100000.500Z`"
+        );
+
+        let lenient = crate::ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let mut parsed = crate::parse_with(&input, lenient.clone()).unwrap();
+        // two fractions, and LAST-MODIFIED isn't UTC
+        assert_eq!(parsed.warnings.len(), 3);
+        let calendar = parsed.calendars.remove(0);
+        let event = &calendar.events[0];
+        let created = event.created.unwrap();
+        assert_eq!(created.to_string(), "20250704T100000Z");
+        assert!(created.time.utc);
+        let last_modified = event.last_modified.unwrap();
+        assert_eq!(last_modified.to_string(), "20250704T100000");
+        assert!(!last_modified.time.utc);
+
+        // written back without the fraction
+        let written = calendar.to_ics_string();
+        assert!(written.contains("\r\nCREATED:20250704T100000Z\r\n"));
+        // the local LAST-MODIFIED is written as it is
+        let reparsed = crate::parse_with(&written, lenient.clone()).unwrap();
+        assert_eq!(reparsed.calendars[0].events[0].created, Some(created));
+
+        let garbage = test_util::event("CREATED:20250704T100000.5x\r\n");
+        assert!(crate::parse_with(&garbage, lenient).is_err());
+    }
+
+    #[test]
+    fn negative_zero_offset() {
+        // as written by Outlook
+        let input = test_util::calendar(
+            "BEGIN:VTIMEZONE\r\n\
+            TZID:GMT Standard Time\r\n\
+            BEGIN:STANDARD\r\n\
+            DTSTART:16011028T020000\r\n\
+            TZOFFSETFROM:+0100\r\n\
+            TZOFFSETTO:-0000\r\n\
+            END:STANDARD\r\n\
+            END:VTIMEZONE\r\n",
+        );
+        let error = crate::parse(&input).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 9: `-0000` is not a valid UTC offset in TZOFFSETTO"
+        );
+
+        let lenient = crate::ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let parsed = crate::parse_with(&input, lenient).unwrap();
+        assert_eq!(parsed.warnings.len(), 1);
+        let observance = &parsed.calendars[0].timezones[0].observances[0];
+        assert_eq!(observance.offset_to.to_string(), "+0000");
+    }
+
+    #[test]
+    fn utc_timestamps() {
+        let input =
+            test_util::event("").replace("DTSTAMP:20111215T112233Z", "DTSTAMP:20111215T112233");
+        assert_eq!(
+            crate::parse(&input).unwrap_err().to_string(),
+            "line 6: expected UTC time for DTSTAMP, found `20111215T112233`"
+        );
+
+        let lenient = crate::ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let parsed = crate::parse_with(&input, lenient).unwrap();
+        assert_eq!(parsed.warnings.len(), 1);
+        let timestamp = parsed.calendars[0].events[0].timestamp.unwrap();
+        assert!(!timestamp.time.utc);
+    }
+
+    #[test]
+    fn explicit_date_time_value() {
+        let input = test_util::event("LAST-MODIFIED;VALUE=DATE-TIME:20250102T000000Z\r\n");
+        let input = input.replace(
+            "DTSTAMP:20111215T112233Z",
+            "DTSTAMP;VALUE=DATE-TIME:20250101T000000Z",
+        );
+        let event = test_util::parse_event(&input);
+        assert_eq!(event.timestamp.unwrap().to_string(), "20250101T000000Z");
+        assert_eq!(event.last_modified.unwrap().to_string(), "20250102T000000Z");
+
+        let input = test_util::event("CREATED;VALUE=DATE:20250101\r\n");
+        assert_eq!(
+            crate::parse(&input).unwrap_err().to_string(),
+            "line 7: unexpected VALUE param DATE in CREATED"
+        );
+    }
+
+    #[test]
+    fn non_gregorian_scale() {
+        let input = test_util::calendar(
+            "CALSCALE:CHINESE\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:1\r\n\
+            DTSTAMP:20240101T000000Z\r\n\
+            DTSTART;VALUE=DATE:47211330\r\n\
+            DTEND;VALUE=DATE:47220101\r\n\
+            EXDATE:47210230T090000\r\n\
+            END:VEVENT\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        assert_eq!(calendar.cal_scale, crate::CalScale::Other("CHINESE".into()));
+        let event = &calendar.events[0];
+        assert_eq!(event.start, Some(DateOrDateTime::Raw("47211330".into())));
+        assert!(event.is_all_day());
+        assert_eq!(event.duration(), None);
+        assert_eq!(calendar.utc_interval(event).unwrap(), None);
+        assert_eq!(calendar.to_ics_string(), input);
+
+        // the same dates aren't valid Gregorian ones
+        let gregorian = input.replace("CALSCALE:CHINESE\r\n", "");
+        assert!(crate::parse(&gregorian).is_err());
+        let gregorian = input.replace("CHINESE", "GREGORIAN");
+        assert!(crate::parse(&gregorian).is_err());
+    }
+
+    #[test]
+    fn default_timezone() {
+        let input = test_util::calendar(
+            "X-WR-CALNAME:Work\r\n\
+            X-WR-TIMEZONE:America/Los_Angeles\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        assert_eq!(
+            calendar.default_timezone.as_deref(),
+            Some("America/Los_Angeles")
+        );
+        assert!(
+            calendar
+                .to_ics_string()
+                .contains("\r\nX-WR-TIMEZONE:America/Los_Angeles\r\n")
+        );
+
+        let input = input.replace("X-WR-TIMEZONE:America/Los_Angeles\r\n", "");
+        assert_eq!(crate::parse(&input).unwrap()[0].default_timezone, None);
+    }
+
+    #[test]
+    fn skip_timezones_and_extensions() {
+        let input = test_util::calendar(
+            "BEGIN:VTIMEZONE\r\n\
+            TZID:Fixed\r\n\
+            BEGIN:STANDARD\r\n\
+            TZOFFSETFROM:+0100\r\n\
+            TZOFFSETTO:+0100\r\n\
+            DTSTART:19700101T000000\r\n\
+            END:STANDARD\r\n\
+            END:VTIMEZONE\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:event@example.com\r\n\
+            DTSTART;TZID=Fixed:20240101T090000\r\n\
+            X-EXAMPLE-COLOUR:blue\r\n\
+            END:VEVENT\r\n",
+        );
+        let full = crate::parse(&input).unwrap().remove(0);
+        assert_eq!(full.timezones.len(), 1);
+        assert_eq!(full.events[0].extensions.len(), 1);
+
+        let options = crate::ParseOptions {
+            parse_timezones: false,
+            ..Default::default()
+        };
+        let mut fast = crate::parse_with(&input, options)
+            .unwrap()
+            .calendars
+            .remove(0);
+        assert!(fast.timezones.is_empty());
+        assert_eq!(fast.events, full.events);
+        // otherwise the same
+        fast.timezones = full.timezones.clone();
+        assert_eq!(fast, full);
+
+        let options = crate::ParseOptions {
+            keep_unknown: false,
+            ..Default::default()
+        };
+        let calendar = crate::parse_with(&input, options)
+            .unwrap()
+            .calendars
+            .remove(0);
+        assert!(calendar.events[0].extensions.is_empty());
+    }
+
+    #[test]
+    fn multiple_calendars() {
+        let calendar = |prod_id: &str| {
+            format!(
+                "BEGIN:VCALENDAR\r\n\
+                PRODID:{prod_id}\r\n\
+                VERSION:2.0\r\n\
+                END:VCALENDAR\r\n"
+            )
+        };
+        let input = calendar("first") + &calendar("second");
+        let calendars = crate::parse(&input).unwrap();
+        assert_eq!(calendars.len(), 2);
+        assert_eq!(calendars[0].prod_id, "first");
+        assert_eq!(calendars[1].prod_id, "second");
+
+        let first = crate::parse_first(&input).unwrap().unwrap();
+        assert_eq!(first.prod_id, "first");
+        assert!(crate::parse_first("").unwrap().is_none());
+
+        // the first calendar is returned without reading the broken second one
+        let unterminated = calendar("first") + "BEGIN:VCALENDAR\r\nPRODID:second\r\n";
+        assert!(crate::parse_first(&unterminated).is_ok());
+        let error = crate::parse(&unterminated).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 6: unexpected EOF: `BEGIN:VCALENDAR` on line 5 has no `END:VCALENDAR`"
+        );
+        assert_eq!(error.calendars, 1);
+        assert_eq!(&unterminated[error.offset..], "PRODID:second\r\n");
+
+        // the innermost component that isn't closed is named
+        let unterminated =
+            calendar("first").replace("END:VCALENDAR\r\n", "BEGIN:VEVENT\r\nUID:1\r\n");
+        let error = crate::parse(&unterminated).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 5: unexpected EOF: `BEGIN:VEVENT` on line 4 has no `END:VEVENT`"
+        );
+        assert_eq!(error.calendars, 0);
+    }
+
+    #[test]
+    fn unknown_components() {
+        use crate::{ParseOptions, SkippedComponent, UnknownComponents};
+
+        // VTODOs are supported, so free/busy information stands in for unsupported components
+        let input = test_util::calendar(
+            "BEGIN:VTODO\r\n\
+            UID:todo@example.com\r\n\
+            DTSTAMP:20240101T000000Z\r\n\
+            END:VTODO\r\n\
+            BEGIN:VFREEBUSY\r\n\
+            UID:free-busy@example.com\r\n\
+            COMMENT:A long comment\r\n  folded over two lines\r\n\
+            END:VFREEBUSY\r\n",
+        );
+        let options = |unknown_components| ParseOptions {
+            unknown_components,
+            ..Default::default()
+        };
+
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        assert_eq!(calendar.todos.len(), 1);
+        assert!(calendar.skipped_components.is_empty());
+
+        let error = crate::parse_with(&input, options(UnknownComponents::Error)).unwrap_err();
+        assert_eq!(error.to_string(), "line 8: unsupported component VFREEBUSY");
+
+        let parsed = crate::parse_with(&input, options(UnknownComponents::Collect)).unwrap();
+        assert_eq!(
+            parsed.calendars[0].skipped_components,
+            [SkippedComponent {
+                name: "VFREEBUSY".into(),
+                lines: 8..=12,
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_error_kinds() {
+        use crate::ParseErrorKind;
+
+        let input = test_util::event("").replace("UID:20111215T112233Z-1234@example.com\r\n", "");
+        let error = crate::parse(&input).unwrap_err();
+        assert!(
+            matches!(
+                &error.kind,
+                ParseErrorKind::MissingRequired { component, property }
+                    if component == "VEVENT" && property == "UID"
+            ),
+            "{error:?}"
+        );
+        assert_eq!(error.to_string(), "line 6: missing UID on VEVENT");
+
+        let input = test_util::event("SUMMARY:One\r\nSUMMARY:Two\r\n");
+        let error = crate::parse(&input).unwrap_err();
+        assert!(
+            matches!(&error.kind, ParseErrorKind::Duplicate { property } if property == "SUMMARY"),
+            "{error:?}"
+        );
+
+        let input = test_util::calendar(
+            "BEGIN:VFREEBUSY\r\n\
+            END:VFREEBUSY\r\n",
+        );
+        let options = crate::ParseOptions {
+            unknown_components: crate::UnknownComponents::Error,
+            ..Default::default()
+        };
+        let error = crate::parse_with(&input, options).unwrap_err();
+        assert!(
+            matches!(
+                &error.kind,
+                ParseErrorKind::Unsupported { component } if component == "VFREEBUSY"
+            ),
+            "{error:?}"
+        );
+        assert_eq!(error.line, 4);
+
+        let input = test_util::event("DTSTART:2024-01-01\r\n");
+        let error = crate::parse(&input).unwrap_err();
+        assert!(
+            matches!(error.kind, ParseErrorKind::BadValue(_)),
+            "{error:?}"
+        );
+        let error = crate::parse("not a calendar").unwrap_err();
+        assert!(
+            matches!(error.kind, ParseErrorKind::BadValue(_)),
+            "{error:?}"
+        );
+        assert_eq!(error.line, 1);
+    }
+
+    #[test]
+    fn organizer_without_mailto() {
+        let input = test_util::event("ORGANIZER:mailto:a@b.com\r\n");
+        let event = test_util::parse_event(&input);
+        assert_eq!(event.organizer.unwrap().value.to_string(), "mailto:a@b.com");
+
+        let input = test_util::event("ORGANIZER:a@b.com\r\n");
+        let error = crate::parse(&input).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 7: ORGANIZER address without mailto: `a@b.com`"
+        );
+
+        let lenient = crate::ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let mut parsed = crate::parse_with(&input, lenient.clone()).unwrap();
+        assert_eq!(parsed.warnings.len(), 1);
+        let event = parsed.calendars.remove(0).events.remove(0);
+        assert_eq!(event.organizer.unwrap().value.to_string(), "mailto:a@b.com");
+
+        // only addresses are fixed up
+        let input = test_util::event("ORGANIZER:not a uri\r\n");
+        let error = crate::parse_with(&input, lenient).unwrap_err();
+        assert_eq!(error.to_string(), "line 7: invalid ORGANIZER address");
+    }
+
+    #[test]
+    fn attachment_media_type() {
+        let input =
+            test_util::event("ATTACH;FMTTYPE=Application/PDF:https://example.com/a.pdf\r\n");
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let attachment = &calendar.events[0].attachments[0];
+        assert_eq!(attachment.media_type().as_deref(), Some("application/pdf"));
+        // written as it was read
+        let output = calendar.to_ics_string();
+        assert!(output.contains("ATTACH;FMTTYPE=Application/PDF:https://example.com/a.pdf\r\n"));
+
+        let input = test_util::event("ATTACH:https://example.com/a.pdf\r\n");
+        assert_eq!(
+            test_util::parse_event(&input).attachments[0].media_type(),
+            None
+        );
+        for fmt_type in ["pdf", "application/", "application/p@f"] {
+            let input = test_util::event(&format!(
+                "ATTACH;FMTTYPE={fmt_type}:https://example.com\r\n"
+            ));
+            let error = crate::parse(&input).unwrap_err();
+            assert_eq!(error.to_string(), "line 7: invalid ATTACH", "{fmt_type}");
+            // lenient parsing keeps the attachment without its media type
+            let lenient = crate::ParseOptions {
+                lenient: true,
+                ..Default::default()
+            };
+            let parsed = crate::parse_with(&input, lenient).unwrap();
+            let event = &parsed.calendars[0].events[0];
+            assert_eq!(event.attachments[0].media_type(), None);
+            assert_eq!(
+                parsed.warnings[0].message, "invalid FMTTYPE param",
+                "{fmt_type}"
+            );
+        }
+    }
+
+    #[test]
+    fn error_sources() {
+        use std::error::Error;
+
+        let input = test_util::event("ORGANIZER:not a uri\r\n");
+        let error = crate::parse(&input).unwrap_err();
+        assert_eq!(error.to_string(), "line 7: invalid ORGANIZER address");
+        assert!(error.source().unwrap().is::<crate::URIError>());
+
+        let input = test_util::event("ATTENDEE;SENT-BY=\"not a uri\":mailto:a@example.com\r\n");
+        let error = crate::parse(&input).unwrap_err();
+        assert_eq!(error.to_string(), "line 7: invalid ATTENDEE");
+        let param_error = error.source().unwrap();
+        assert_eq!(param_error.to_string(), "invalid SENT-BY param");
+        assert!(param_error.source().unwrap().is::<crate::URIError>());
+
+        let input = test_util::event("ATTACH;ENCODING=BASE64;VALUE=BINARY:@@@\r\n");
+        let error = crate::parse(&input).unwrap_err();
+        assert_eq!(error.to_string(), "line 7: invalid ATTACH");
+        let data_error = error.source().unwrap();
+        assert!(data_error.source().unwrap().is::<crate::DecodeError>());
+    }
+
+    #[test]
+    fn errors_name_property() {
+        use std::error::Error;
+
+        let input = test_util::event("GEO:north;west\r\n");
+        let error = crate::parse(&input).unwrap_err();
+        assert_eq!(error.to_string(), "line 7: invalid GEO");
+        // the cause is kept
+        assert!(error.source().is_some());
+
+        let input = test_util::event("PRIORITY:xyz\r\n");
+        let error = crate::parse(&input).unwrap_err();
+        assert_eq!(error.to_string(), "line 7: invalid PRIORITY");
+        // errors that already say what's wrong are unchanged
+        let input = test_util::event("GEO:1;2\r\nGEO:1;2\r\n");
+        let error = crate::parse(&input).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 8: expected at most 1 GEO, found at least 2"
+        );
+    }
+
+    #[test]
+    fn scheduling_params() {
+        let input = test_util::event(
+            "ORGANIZER;SCHEDULE-FORCE-SEND=REQUEST:mailto:boss@example.com\r\n\
+            ATTENDEE;SCHEDULE-AGENT=CLIENT;SCHEDULE-STATUS=2.0:mailto:x\r\n\
+            ATTENDEE;SCHEDULE-STATUS=\"3.7,5.1.1\":mailto:y\r\n",
+        );
+        let event = test_util::parse_event(&input);
+        let organizer = event.organizer.as_ref().unwrap();
+        assert_eq!(
+            organizer.schedule_force_send,
+            Some(ScheduleForceSend::Request)
+        );
+        assert_eq!(
+            event.attendees[0].schedule_agent,
+            Some(ScheduleAgent::Client)
+        );
+        assert_eq!(
+            event.attendees[0].schedule_status,
+            Some(ScheduleStatus(VecOne::new(Cow::Borrowed("2.0"))))
+        );
+        assert_eq!(event.attendees[1].schedule_agent, None);
+        let codes = &event.attendees[1].schedule_status.as_ref().unwrap().0;
+        assert_eq!(codes.first, "3.7");
+        assert_eq!(codes.rest, ["5.1.1"]);
+
+        let output = crate::parse(&input).unwrap()[0].to_ics_string();
+        assert!(
+            output.contains("ORGANIZER;SCHEDULE-FORCE-SEND=REQUEST:mailto:boss@example.com\r\n")
+        );
+        assert!(
+            output.contains("ATTENDEE;SCHEDULE-AGENT=CLIENT;SCHEDULE-STATUS=\"2.0\":mailto:x\r\n")
+        );
+        assert!(output.contains("ATTENDEE;SCHEDULE-STATUS=\"3.7,5.1.1\":mailto:y\r\n"));
+
+        let input = test_util::event("ATTENDEE;SCHEDULE-STATUS=2:mailto:x\r\n");
+        assert!(crate::parse(&input).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! Options controlling how input is parsed
+
+use std::fmt;
+
+use crate::Calendar;
+
+/// Options for [`parse_with`](crate::parse_with).
+///
+/// The default options give the same results as [`parse`](crate::parse). Options can be
+/// changed by setting fields, or with the chained methods:
+///
+/// ```
+/// # use icalendar::{ParseOptions, UnknownComponents};
+/// let options = ParseOptions::default()
+///     .lenient()
+///     .max_attachment_bytes(1 << 20)
+///     .unknown_components(UnknownComponents::Collect);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Parse `VTIMEZONE`s into [`Calendar::timezones`]. If `false` they are skipped, which is
+    /// faster when only the times as written are needed. Defaults to `true`.
+    pub parse_timezones: bool,
+    /// Keep extension (`X-`) properties of events in
+    /// [`Event::extensions`](crate::Event::extensions), and likewise for journals. Defaults
+    /// to `true`.
+    pub keep_unknown: bool,
+    /// Accept some invalid input, recording a warning instead of failing.
+    ///
+    /// Currently this covers an unsupported `VERSION`, fractional seconds (which are dropped)
+    /// or local times in timestamps such as `DTSTAMP`, an `ORGANIZER` without `mailto:`,
+    /// a `-0000` UTC offset (read as `+0000`), and an `ATTACH` that isn't a valid URI (kept as
+    /// written). A `VTODO` or `VJOURNAL` that can't be parsed is skipped.
+    pub lenient: bool,
+    /// What to do with components inside a `VCALENDAR` that aren't supported (e.g. `VFREEBUSY`)
+    pub unknown_components: UnknownComponents,
+    /// Fail on inline (`ENCODING=BASE64`) attachments larger than this many bytes once
+    /// decoded. Defaults to no limit.
+    pub max_attachment_bytes: Option<usize>,
+    /// Only accept ASCII letters, digits and `-` in property and parameter names, as RFC 5545
+    /// requires. By default any Unicode letter or digit is accepted. Defaults to `false`.
+    pub strict_names: bool,
+}
+
+impl ParseOptions {
+    /// Set [`lenient`](Self::lenient) to `true`
+    pub fn lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    /// Set [`parse_timezones`](Self::parse_timezones)
+    pub fn parse_timezones(mut self, parse_timezones: bool) -> Self {
+        self.parse_timezones = parse_timezones;
+        self
+    }
+
+    /// Set [`keep_unknown`](Self::keep_unknown)
+    pub fn keep_unknown(mut self, keep_unknown: bool) -> Self {
+        self.keep_unknown = keep_unknown;
+        self
+    }
+
+    /// Set [`unknown_components`](Self::unknown_components)
+    pub fn unknown_components(mut self, unknown_components: UnknownComponents) -> Self {
+        self.unknown_components = unknown_components;
+        self
+    }
+
+    /// Set [`max_attachment_bytes`](Self::max_attachment_bytes) to `max`
+    pub fn max_attachment_bytes(mut self, max: usize) -> Self {
+        self.max_attachment_bytes = Some(max);
+        self
+    }
+
+    /// Set [`strict_names`](Self::strict_names)
+    pub fn strict_names(mut self, strict_names: bool) -> Self {
+        self.strict_names = strict_names;
+        self
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            parse_timezones: true,
+            keep_unknown: true,
+            lenient: false,
+            unknown_components: UnknownComponents::default(),
+            max_attachment_bytes: None,
+            strict_names: false,
+        }
+    }
+}
+
+/// How [`parse_with`](crate::parse_with) handles unsupported components in a calendar
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum UnknownComponents {
+    /// Skip them without a trace
+    #[default]
+    Skip,
+    /// Fail to parse the calendar
+    Error,
+    /// Skip them, recording where they were in [`Calendar::skipped_components`]
+    Collect,
+}
+
+/// The output of [`parse_with`](crate::parse_with)
+#[derive(Debug)]
+pub struct Parsed<'src> {
+    pub calendars: Vec<Calendar<'src>>,
+    /// Problems that were ignored because of [`ParseOptions::lenient`]
+    pub warnings: Vec<Warning>,
+}
+
+/// A problem that was ignored because of [`ParseOptions::lenient`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// The line of the input it was found on, counting from 1
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
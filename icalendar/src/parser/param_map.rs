@@ -1,18 +1,18 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::borrow::Cow;
 
-use anyhow::bail;
+use anyhow::{Context, bail};
 
 use crate::{
     Result,
     params::ParseParam,
     parser::helpers::{param_value, split_once_outside_quotes, try_split_once},
-    types::{Name, VecOne, XName},
+    types::{Name, VecOne},
 };
 
 #[derive(Debug, Default, PartialEq)]
+/// The parameters of a line, in the order they first appear
 pub struct ParamMap<'src> {
-    iana: HashMap<Cow<'src, str>, VecOne<Cow<'src, str>>>,
-    extend: HashMap<XName<'src>, VecOne<Cow<'src, str>>>,
+    params: Vec<(Name<'src>, VecOne<Cow<'src, str>>)>,
 }
 
 impl<'src> ParamMap<'src> {
@@ -27,7 +27,7 @@ impl<'src> ParamMap<'src> {
         let mut values = vec![];
         let mut input = rest;
         while !input.is_empty() {
-            let (next_param, i) = split_once_outside_quotes(input, ',');
+            let (next_param, i) = split_once_outside_quotes(input, ',')?;
             // we're pretty lax here but it will work on well-formed input and not do anything too stupid
             // on malformed input
 
@@ -40,23 +40,7 @@ impl<'src> ParamMap<'src> {
     }
 
     pub fn push(&mut self, name: Name<'src>, value: Cow<'src, str>) -> &mut Self {
-        match name {
-            Name::XName(xname) => {
-                if let Some(v) = self.extend.get_mut(&xname) {
-                    v.push(value);
-                } else {
-                    self.extend.insert(xname, VecOne::new(value));
-                }
-            }
-            Name::Iana(cow) => {
-                if let Some(v) = self.iana.get_mut(&cow) {
-                    v.push(value);
-                } else {
-                    self.iana.insert(cow, VecOne::new(value));
-                }
-            }
-        }
-        self
+        self.add_values(name, [value])
     }
 
     pub fn with_push(mut self, name: Name<'src>, value: Cow<'src, str>) -> Self {
@@ -73,25 +57,13 @@ impl<'src> ParamMap<'src> {
         let Some(first) = values.next() else {
             return self;
         };
-        match name {
-            Name::XName(xname) => {
-                let v = if let Some(v) = self.extend.get_mut(&xname) {
-                    v.push(first);
-                    v
-                } else {
-                    self.extend.entry(xname).or_insert(VecOne::new(first))
-                };
-                v.extend(values);
-            }
-            Name::Iana(cow) => {
-                let v = if let Some(v) = self.iana.get_mut(&cow) {
-                    v.push(first);
-                    v
-                } else {
-                    self.iana.entry(cow).or_insert(VecOne::new(first))
-                };
-                v.extend(values);
-            }
+        if let Some((_, v)) = self.params.iter_mut().find(|(n, _)| *n == name) {
+            v.push(first);
+            v.extend(values);
+        } else {
+            let mut v = VecOne::new(first);
+            v.extend(values);
+            self.params.push((name, v));
         }
         self
     }
@@ -107,20 +79,33 @@ impl<'src> ParamMap<'src> {
     }
 
     pub fn take(&mut self, key: &Name<'src>) -> Option<VecOne<Cow<'src, str>>> {
-        match key {
-            Name::XName(xname) => self.extend.remove(xname),
-            Name::Iana(cow) => self.iana.remove(cow),
-        }
+        let idx = self.params.iter().position(|(n, _)| n == key)?;
+        Some(self.params.remove(idx).1)
     }
 
-    pub fn take_ty<T: ParseParam<'src>>(&mut self) -> Result<Option<T>> {
+    pub(crate) fn take_ty<T: ParseParam<'src>>(&mut self) -> Result<Option<T>> {
         let Some(value) = self.take(&T::PARAM_NAME) else {
             return Ok(None);
         };
-        T::parse_value(value).map(Some)
+        T::parse_value(value)
+            .map(Some)
+            .with_context(|| format!("invalid {} param", T::PARAM_NAME))
+    }
+
+    /// Consume the map, returning all parameters
+    pub fn into_params(self) -> impl Iterator<Item = (Name<'src>, VecOne<Cow<'src, str>>)> {
+        self.params.into_iter()
+    }
+
+    /// All parameters, in order
+    pub fn iter(&self) -> impl Iterator<Item = (&Name<'src>, &VecOne<Cow<'src, str>>)> {
+        self.params.iter().map(|(name, values)| (name, values))
     }
 
     pub fn iana(&self) -> impl Iterator<Item = (&Cow<'src, str>, &VecOne<Cow<'src, str>>)> {
-        self.iana.iter()
+        self.iter().filter_map(|(name, values)| match name {
+            Name::Iana(name) => Some((name, values)),
+            Name::XName(_) => None,
+        })
     }
 }
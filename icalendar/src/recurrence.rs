@@ -0,0 +1,726 @@
+//! Expanding recurring events into their instances
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    iter,
+};
+
+use crate::{
+    Calendar, Event, EventEnd, RecurrenceId, TimeZone, TimeZoneError,
+    params::{Range, TimeZoneIdentifier},
+    types::{Date, DateOrDateTime, DateTime, Time, VecOne},
+};
+
+impl<'src> Calendar<'src> {
+    /// The events of this calendar grouped by `UID`.
+    ///
+    /// Events with a `RECURRENCE-ID` become overrides of the instance they replace, the others
+    /// the master of their series. If several events claim the same place, the last one wins.
+    pub fn event_series(&self) -> HashMap<&str, EventSeries<'_, 'src>> {
+        let mut output: HashMap<&str, EventSeries<'_, 'src>> = HashMap::new();
+        for event in &self.events {
+            let series = output.entry(&event.uid).or_insert_with(|| EventSeries {
+                master: None,
+                overrides: BTreeMap::new(),
+            });
+            match &event.recurrence_id {
+                Some(recurrence_id) => {
+                    series.overrides.insert(recurrence_id.value.clone(), event);
+                }
+                None => series.master = Some(event),
+            }
+        }
+        output
+    }
+
+    /// [Normalize](Event::normalize_recurrence_dates) the `RDATE`s and `EXDATE`s of every
+    /// event using this calendar's timezones.
+    pub fn normalize_recurrence_dates(&mut self) -> Result<(), TimeZoneError> {
+        for event in &mut self.events {
+            event.normalize_recurrence_dates(&self.timezones)?;
+        }
+        Ok(())
+    }
+}
+
+/// A recurring event along with the instances that were changed individually
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventSeries<'a, 'src> {
+    /// The event without a `RECURRENCE-ID`, if there is one
+    pub master: Option<&'a Event<'src>>,
+    /// The changed instances, by the instance they replace (their `RECURRENCE-ID`)
+    pub overrides: BTreeMap<DateOrDateTime, &'a Event<'src>>,
+}
+
+impl<'src> EventSeries<'_, 'src> {
+    /// An event for each instance of the series starting in `window`, in chronological order.
+    ///
+    /// Like [`Event::instance_events`] on the master, except that overridden instances are
+    /// replaced by their override, which is included if it starts in `window` wherever the
+    /// instance it replaces was.
+    ///
+    /// An override with `RANGE=THISANDFUTURE` also replaces every later instance (and
+    /// `THISANDPRIOR` every earlier one), each moved by as much as the override moved its own
+    /// instance. Where several ranges cover an instance, the override nearest to it applies.
+    pub fn instance_events(&self, window: (DateTime, DateTime)) -> Vec<Event<'src>> {
+        let ranges: Vec<_> = self
+            .overrides
+            .iter()
+            .filter_map(|(recurrence_id, event)| {
+                let range = event.recurrence_id.as_ref()?.range.clone()?;
+                let override_seconds = to_date_time(recurrence_id)?.to_seconds();
+                let shift = to_date_time(event.start.as_ref()?)?.to_seconds() - override_seconds;
+                Some((range, override_seconds, shift, *event))
+            })
+            .collect();
+
+        let (from, to) = (window.0.to_seconds(), window.1.to_seconds());
+        // instances can be moved into the window from outside it
+        let max_shift = ranges
+            .iter()
+            .map(|(_, _, shift, _)| shift.abs())
+            .max()
+            .unwrap_or(0);
+        let expanded = (
+            DateTime::from_seconds(from - max_shift, window.0.time.utc),
+            DateTime::from_seconds(to + max_shift, window.1.time.utc),
+        );
+
+        let mut output = match self.master {
+            Some(master) => master.instance_events(expanded),
+            None => vec![],
+        };
+        output.retain(|instance| {
+            let recurrence_id = instance.recurrence_id.as_ref().map(|id| &id.value);
+            !recurrence_id.is_some_and(|id| self.overrides.contains_key(id))
+        });
+        for instance in &mut output {
+            let Some(recurrence_id) = instance.recurrence_id.clone() else {
+                continue;
+            };
+            let Some(seconds) = to_date_time(&recurrence_id.value).map(DateTime::to_seconds) else {
+                continue;
+            };
+            let covering = ranges
+                .iter()
+                .filter(|(range, override_seconds, _, _)| match range {
+                    Range::ThisAndFuture => *override_seconds < seconds,
+                    Range::ThisAndPrior => *override_seconds > seconds,
+                })
+                .min_by_key(|(_, override_seconds, _, _)| override_seconds.abs_diff(seconds));
+            if let Some((_, _, _, event)) = covering {
+                *instance = moved_override(event, recurrence_id);
+            }
+        }
+
+        output.extend(self.overrides.values().map(|event| (*event).clone()));
+        let start_seconds =
+            |event: &Event<'_>| Some(to_date_time(event.start.as_ref()?)?.to_seconds());
+        output
+            .retain(|event| start_seconds(event).is_some_and(|start| (from..to).contains(&start)));
+        output.sort_by_key(start_seconds);
+        output
+    }
+}
+
+/// A copy of the range override `event` replacing the instance `recurrence_id`, moved by
+/// as much as `event` moved the instance it replaces
+fn moved_override<'src>(event: &Event<'src>, recurrence_id: RecurrenceId<'src>) -> Event<'src> {
+    let mut output = event.clone();
+    let (Some(start), Some(override_id)) = (&event.start, &event.recurrence_id) else {
+        return output;
+    };
+    let (Some(instance), Some(replaced)) = (
+        to_date_time(&recurrence_id.value),
+        to_date_time(&override_id.value),
+    ) else {
+        return output;
+    };
+    // how far this instance is from the one the override replaces
+    let offset = instance.to_seconds() - replaced.to_seconds();
+    output.start = Some(shift_by(start, offset));
+    if let Some(EventEnd::DateTime { value: end, .. }) = &mut output.end {
+        *end = shift_by(end, offset);
+    }
+    output.recurrence_id = Some(recurrence_id);
+    output
+}
+
+impl<'src> Event<'src> {
+    /// The start times of the instances of this event from `window.0` (inclusive) to
+    /// `window.1` (exclusive), in chronological order.
+    ///
+    /// The instances are `DTSTART`, the occurrences of every `RRULE` and the `RDATE`s, minus
+    /// the `EXDATE`s. Instances of all-day events start at midnight. Times are compared as
+    /// written, ignoring any `TZID`, so `window` should be in the same timezone as the event.
+    ///
+    /// `RDATE`s and `EXDATE`s in a different timezone to `DTSTART` (including UTC) will not
+    /// match up with the other instances: call
+    /// [`normalize_recurrence_dates`](Self::normalize_recurrence_dates) first to convert them.
+    pub fn instances(&self, window: (DateTime, DateTime)) -> Vec<DateTime> {
+        let Some(start) = &self.start else {
+            return vec![];
+        };
+        let Some(first) = to_date_time(start) else {
+            return vec![];
+        };
+        let from = window.0.to_seconds();
+        let to = window.1.to_seconds();
+
+        let mut output = vec![first];
+        for rule in &self.recurrence_rules {
+            let occurrences = rule.occurrences(start.clone());
+            for occurrence in occurrences.filter_map(|occurrence| to_date_time(&occurrence)) {
+                if occurrence.to_seconds() >= to {
+                    break;
+                }
+                output.push(occurrence);
+            }
+        }
+        output.extend(
+            self.recurrence_dates
+                .iter()
+                .flat_map(|dates| &dates.values)
+                .filter_map(to_date_time),
+        );
+
+        let excluded = self.excluded_seconds();
+        output.retain(|instance| {
+            let seconds = instance.to_seconds();
+            (from..to).contains(&seconds) && !excluded.contains(&seconds)
+        });
+        output.sort_by_key(|instance| instance.to_seconds());
+        output.dedup_by_key(|instance| instance.to_seconds());
+        output
+    }
+
+    /// The start of the first [instance](Self::instances) strictly after `after`, if any.
+    ///
+    /// Recurrence rules are only expanded as far as needed, so this is cheap even for
+    /// long-running series.
+    pub fn next_occurrence_after(&self, after: DateTime) -> Option<DateTime> {
+        let start = self.start.as_ref()?;
+        let first = to_date_time(start)?;
+        let after = after.to_seconds();
+        let excluded = self.excluded_seconds();
+        let is_next = |instance: &DateTime| {
+            let seconds = instance.to_seconds();
+            seconds > after && !excluded.contains(&seconds)
+        };
+
+        let rules = self.recurrence_rules.iter().filter_map(|rule| {
+            rule.occurrences(start.clone())
+                .filter_map(|occurrence| to_date_time(&occurrence))
+                .find(|occurrence| is_next(occurrence))
+        });
+        let dates = self
+            .recurrence_dates
+            .iter()
+            .flat_map(|dates| &dates.values)
+            .filter_map(to_date_time)
+            .filter(|date| is_next(date));
+        Some(first)
+            .filter(|start| is_next(start))
+            .into_iter()
+            .chain(rules)
+            .chain(dates)
+            .min_by_key(|instance| instance.to_seconds())
+    }
+
+    /// A copy of this event for each of its [`instances`](Self::instances) in `window`.
+    ///
+    /// Each copy starts at its instance, has its `RECURRENCE-ID` set to it and its `DTEND`
+    /// moved by the same amount as the start. Recurrence properties (`RRULE`, `RDATE` and
+    /// `EXDATE`) are removed from the copies.
+    pub fn instance_events(&self, window: (DateTime, DateTime)) -> Vec<Event<'src>> {
+        let Some(start) = &self.start else {
+            return vec![];
+        };
+        let Some(start_seconds) = to_date_time(start).map(DateTime::to_seconds) else {
+            return vec![];
+        };
+
+        self.instances(window)
+            .into_iter()
+            .map(|instance| {
+                let value = if start.is_date_time() {
+                    DateOrDateTime::DateTime(instance)
+                } else {
+                    DateOrDateTime::Date(instance.date)
+                };
+                let shift = instance.to_seconds() - start_seconds;
+
+                let mut event = self.clone();
+                event.start = Some(value.clone());
+                event.recurrence_id = Some(RecurrenceId {
+                    range: None,
+                    timezone_id: self.start_timezone_id.clone(),
+                    value,
+                });
+                if let Some(EventEnd::DateTime { value: end, .. }) = &mut event.end {
+                    *end = shift_by(end, shift);
+                }
+                event.recurrence_rules.clear();
+                event.recurrence_dates.clear();
+                event.exception_dates.clear();
+                event
+            })
+            .collect()
+    }
+
+    /// Rewrite the `RDATE`s and `EXDATE`s in the timezone of `DTSTART`, so that they compare
+    /// correctly with the other [instances](Self::instances).
+    ///
+    /// Times in UTC or with a `TZID` are converted to UTC if `DTSTART` is in UTC, or to local
+    /// time in the `DTSTART` timezone if it has a `TZID`. Floating times, dates, and all
+    /// values of events with a floating or all-day `DTSTART` are left as written, so floating
+    /// times are treated as being in the `DTSTART` timezone. Every `TZID` involved must be
+    /// defined in `timezones`.
+    pub fn normalize_recurrence_dates(
+        &mut self,
+        timezones: &[TimeZone<'_>],
+    ) -> Result<(), TimeZoneError> {
+        let Some(DateOrDateTime::DateTime(start)) = &self.start else {
+            return Ok(());
+        };
+        let target = if start.time.utc {
+            None
+        } else if let Some(id) = &self.start_timezone_id {
+            Some((id.clone(), find_timezone(timezones, id)?))
+        } else {
+            return Ok(());
+        };
+
+        for dates in &mut self.exception_dates {
+            normalize_dates(
+                &mut dates.timezone_id,
+                &mut dates.values,
+                &target,
+                timezones,
+            )?;
+        }
+        for dates in &mut self.recurrence_dates {
+            normalize_dates(
+                &mut dates.timezone_id,
+                &mut dates.values,
+                &target,
+                timezones,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The `EXDATE`s, as seconds
+    fn excluded_seconds(&self) -> Vec<i64> {
+        self.exception_dates
+            .iter()
+            .flat_map(|dates| &dates.values)
+            .filter_map(to_date_time)
+            .map(DateTime::to_seconds)
+            .collect()
+    }
+}
+
+/// Convert the values of an `RDATE` or `EXDATE` to UTC (if `target` is `None`) or to local
+/// time in the `target` timezone
+fn normalize_dates<'src>(
+    timezone_id: &mut Option<TimeZoneIdentifier<'src>>,
+    values: &mut VecOne<DateOrDateTime>,
+    target: &Option<(TimeZoneIdentifier<'src>, &TimeZone<'_>)>,
+    timezones: &[TimeZone<'_>],
+) -> Result<(), TimeZoneError> {
+    let source = match timezone_id {
+        Some(id) => Some(find_timezone(timezones, id)?),
+        None => None,
+    };
+    let mut converted = false;
+    for value in iter::once(&mut values.first).chain(&mut values.rest) {
+        let DateOrDateTime::DateTime(date_time) = value else {
+            continue;
+        };
+        let utc = match source {
+            _ if date_time.time.utc => *date_time,
+            Some(source) => source.to_utc(*date_time),
+            // floating
+            None => continue,
+        };
+        *date_time = match target {
+            Some((_, target)) => target.to_local(utc),
+            None => utc,
+        };
+        converted = true;
+    }
+    if converted {
+        *timezone_id = target.as_ref().map(|(id, _)| id.clone());
+    }
+    Ok(())
+}
+
+fn find_timezone<'a, 'tz>(
+    timezones: &'a [TimeZone<'tz>],
+    id: &TimeZoneIdentifier<'_>,
+) -> Result<&'a TimeZone<'tz>, TimeZoneError> {
+    timezones
+        .iter()
+        .find(|timezone| timezone.id == id.id())
+        .ok_or_else(|| TimeZoneError::Unknown(id.id().to_string()))
+}
+
+/// Dates are treated as midnight at the start of the day. `None` for a raw value.
+fn to_date_time(value: &DateOrDateTime) -> Option<DateTime> {
+    match value {
+        DateOrDateTime::Date(date) => Some(DateTime {
+            date: *date,
+            time: Time {
+                hour: 0,
+                minute: 0,
+                second: 0,
+                utc: false,
+            },
+        }),
+        DateOrDateTime::DateTime(date_time) => Some(*date_time),
+        DateOrDateTime::Raw(_) => None,
+    }
+}
+
+/// Raw values are left as they are
+fn shift_by(value: &DateOrDateTime, seconds: i64) -> DateOrDateTime {
+    match value {
+        DateOrDateTime::Date(date) => {
+            DateOrDateTime::Date(Date::from_days(date.to_days() + seconds.div_euclid(86_400)))
+        }
+        DateOrDateTime::DateTime(date_time) => DateOrDateTime::DateTime(DateTime::from_seconds(
+            date_time.to_seconds() + seconds,
+            date_time.time.utc,
+        )),
+        DateOrDateTime::Raw(_) => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EventEnd, test_util,
+        types::{Date, DateOrDateTime, DateTime, Time},
+    };
+
+    fn local(month: u8, day: u8, hour: u8) -> DateTime {
+        DateTime {
+            date: Date {
+                full_year: 2024,
+                month,
+                day,
+            },
+            time: Time {
+                hour,
+                minute: 0,
+                second: 0,
+                utc: false,
+            },
+        }
+    }
+
+    fn weekly() -> String {
+        test_util::calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:weekly@example.com\r\n\
+            DTSTAMP:20240101T000000Z\r\n\
+            DTSTART:20240101T090000\r\n\
+            DTEND:20240101T093000\r\n\
+            RRULE:FREQ=WEEKLY;COUNT=5\r\n\
+            EXDATE:20240115T090000\r\n\
+            RDATE:20240103T090000\r\n\
+            END:VEVENT\r\n",
+        )
+    }
+
+    #[test]
+    fn weekly_with_exception() {
+        let input = weekly();
+        let event = crate::parse(&input).unwrap().remove(0).events.remove(0);
+        assert_eq!(
+            event.instances((local(1, 1, 0), local(12, 31, 0))),
+            [
+                local(1, 1, 9),
+                local(1, 3, 9),
+                local(1, 8, 9),
+                local(1, 22, 9),
+                local(1, 29, 9)
+            ]
+        );
+
+        // clipped to the window
+        assert_eq!(
+            event.instances((local(1, 2, 0), local(1, 22, 9))),
+            [local(1, 3, 9), local(1, 8, 9)]
+        );
+    }
+
+    #[test]
+    fn instance_events() {
+        let input = weekly();
+        let event = crate::parse(&input).unwrap().remove(0).events.remove(0);
+        let instances = event.instance_events((local(1, 8, 0), local(1, 9, 0)));
+        assert_eq!(instances.len(), 1);
+
+        let instance = &instances[0];
+        let start = DateOrDateTime::DateTime(local(1, 8, 9));
+        assert_eq!(instance.start, Some(start.clone()));
+        assert_eq!(instance.recurrence_id.as_ref().unwrap().value, start);
+        let Some(EventEnd::DateTime { value: end, .. }) = &instance.end else {
+            panic!("expected DTEND");
+        };
+        assert_eq!(end.to_string(), "20240108T093000");
+        assert!(instance.recurrence_rules.is_empty());
+        assert!(instance.exception_dates.is_empty());
+    }
+
+    #[test]
+    fn series_with_moved_instance() {
+        let input = weekly().replace(
+            "END:VEVENT\r\nEND:VCALENDAR",
+            "END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:weekly@example.com\r\n\
+            DTSTAMP:20240101T000000Z\r\n\
+            RECURRENCE-ID:20240108T090000\r\n\
+            DTSTART:20240109T140000\r\n\
+            DTEND:20240109T150000\r\n\
+            SUMMARY:Moved\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let series = calendar.event_series();
+        assert_eq!(series.len(), 1);
+        let series = &series["weekly@example.com"];
+        assert_eq!(series.master, Some(&calendar.events[0]));
+        assert_eq!(
+            series.overrides.keys().collect::<Vec<_>>(),
+            [&DateOrDateTime::DateTime(local(1, 8, 9))]
+        );
+
+        let instances = series.instance_events((local(1, 1, 0), local(1, 20, 0)));
+        let starts: Vec<_> = instances.iter().map(|event| event.start.clone()).collect();
+        assert_eq!(
+            starts,
+            [
+                Some(DateOrDateTime::DateTime(local(1, 1, 9))),
+                Some(DateOrDateTime::DateTime(local(1, 3, 9))),
+                Some(DateOrDateTime::DateTime(local(1, 9, 14))),
+            ]
+        );
+        assert_eq!(instances[2].summary.as_ref().unwrap().text, "Moved");
+
+        // the override is left out when moved out of the window, even if its instance is in it
+        let instances = series.instance_events((local(1, 8, 0), local(1, 9, 0)));
+        assert!(instances.is_empty());
+    }
+
+    #[test]
+    fn this_and_future() {
+        let input = weekly().replace(
+            "END:VEVENT\r\nEND:VCALENDAR",
+            "END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:weekly@example.com\r\n\
+            DTSTAMP:20240101T000000Z\r\n\
+            RECURRENCE-ID;RANGE=THISANDFUTURE:20240108T090000\r\n\
+            DTSTART:20240108T140000\r\n\
+            DTEND:20240108T150000\r\n\
+            SUMMARY:Afternoons\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let series = &calendar.event_series()["weekly@example.com"];
+
+        let instances = series.instance_events((local(1, 1, 0), local(12, 31, 0)));
+        let times: Vec<_> = instances
+            .iter()
+            .map(|event| {
+                let Some(EventEnd::DateTime { value: end, .. }) = &event.end else {
+                    panic!("expected DTEND");
+                };
+                (event.start.as_ref().unwrap().to_string(), end.to_string())
+            })
+            .collect();
+        assert_eq!(
+            times,
+            [
+                ("20240101T090000".into(), "20240101T093000".into()),
+                ("20240103T090000".into(), "20240103T093000".into()),
+                ("20240108T140000".into(), "20240108T150000".into()),
+                // 15th is excluded
+                ("20240122T140000".into(), "20240122T150000".into()),
+                ("20240129T140000".into(), "20240129T150000".into()),
+            ]
+        );
+        assert_eq!(instances[0].summary, None);
+        assert_eq!(instances[3].summary.as_ref().unwrap().text, "Afternoons");
+        assert_eq!(
+            instances[3].recurrence_id.as_ref().unwrap().value,
+            DateOrDateTime::DateTime(local(1, 22, 9))
+        );
+
+        // instances are moved into the window
+        let instances = series.instance_events((local(1, 29, 12), local(1, 30, 0)));
+        assert_eq!(instances.len(), 1);
+        assert_eq!(
+            instances[0].start,
+            Some(DateOrDateTime::DateTime(local(1, 29, 14)))
+        );
+    }
+
+    #[test]
+    fn zoned_recurrence_ids() {
+        let input = weekly()
+            .replace("DTSTART:", "DTSTART;TZID=Europe/Paris:")
+            .replace(
+                "END:VEVENT\r\nEND:VCALENDAR",
+                "END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:weekly@example.com\r\n\
+            DTSTAMP:20240101T000000Z\r\n\
+            RECURRENCE-ID;TZID=Europe/Paris;RANGE=THISANDFUTURE:20240108T090000\r\n\
+            DTSTART;TZID=Europe/Paris:20240108T140000\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR",
+            );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let series = &calendar.event_series()["weekly@example.com"];
+        let instances = series.instance_events((local(1, 1, 0), local(12, 31, 0)));
+        // the first is an instance of the master, the last one moved by the override
+        for instance in [&instances[0], instances.last().unwrap()] {
+            let recurrence_id = instance.recurrence_id.as_ref().unwrap();
+            assert_eq!(recurrence_id.timezone_id, instance.start_timezone_id);
+            assert_eq!(
+                recurrence_id.timezone_id.as_ref().unwrap().id(),
+                "Europe/Paris"
+            );
+            assert_eq!(recurrence_id.range, None);
+        }
+    }
+
+    const PARIS: &str = "BEGIN:VTIMEZONE\r\n\
+        TZID:Europe/Paris\r\n\
+        BEGIN:DAYLIGHT\r\n\
+        TZOFFSETFROM:+0100\r\n\
+        TZOFFSETTO:+0200\r\n\
+        TZNAME:CEST\r\n\
+        DTSTART:19700329T020000\r\n\
+        RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\r\n\
+        END:DAYLIGHT\r\n\
+        BEGIN:STANDARD\r\n\
+        TZOFFSETFROM:+0200\r\n\
+        TZOFFSETTO:+0100\r\n\
+        TZNAME:CET\r\n\
+        DTSTART:19701025T030000\r\n\
+        RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU\r\n\
+        END:STANDARD\r\n\
+        END:VTIMEZONE\r\n";
+
+    #[test]
+    fn exception_in_other_timezone() {
+        let input = test_util::calendar(&format!(
+            "{PARIS}\
+            BEGIN:VEVENT\r\n\
+            UID:utc@example.com\r\n\
+            DTSTAMP:20240101T000000Z\r\n\
+            DTSTART:20240101T080000Z\r\n\
+            RRULE:FREQ=WEEKLY;COUNT=3\r\n\
+            EXDATE;TZID=Europe/Paris:20240108T090000\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:paris@example.com\r\n\
+            DTSTAMP:20240101T000000Z\r\n\
+            DTSTART;TZID=Europe/Paris:20240701T090000\r\n\
+            RRULE:FREQ=WEEKLY;COUNT=3\r\n\
+            EXDATE:20240708T070000Z\r\n\
+            END:VEVENT\r\n"
+        ));
+        let mut calendar = crate::parse(&input).unwrap().remove(0);
+        let utc = |month, day, hour| DateTime {
+            time: Time {
+                utc: true,
+                ..local(month, day, hour).time
+            },
+            ..local(month, day, hour)
+        };
+        let window = (utc(1, 1, 0), utc(12, 31, 0));
+        // compared as written, 09:00 doesn't match 08:00
+        assert_eq!(calendar.events[0].instances(window).len(), 3);
+        assert_eq!(calendar.events[1].instances(window).len(), 3);
+
+        calendar.normalize_recurrence_dates().unwrap();
+        assert_eq!(
+            calendar.events[0].instances(window),
+            [utc(1, 1, 8), utc(1, 15, 8)]
+        );
+        assert_eq!(calendar.events[0].exception_dates[0].timezone_id, None);
+
+        let paris = &calendar.events[1];
+        assert_eq!(
+            paris.exception_dates[0].values.first,
+            DateOrDateTime::DateTime(local(7, 8, 9))
+        );
+        assert_eq!(
+            paris.exception_dates[0].timezone_id,
+            paris.start_timezone_id
+        );
+        let window = (local(1, 1, 0), local(12, 31, 0));
+        assert_eq!(paris.instances(window), [local(7, 1, 9), local(7, 15, 9)]);
+
+        // the timezone must be defined
+        calendar.timezones.clear();
+        assert!(calendar.normalize_recurrence_dates().is_err());
+    }
+
+    #[test]
+    fn next_occurrence_after() {
+        let daily = weekly()
+            .replace("FREQ=WEEKLY;COUNT=5", "FREQ=DAILY")
+            .replace("EXDATE:20240115T090000", "EXDATE:20240111T090000");
+        let event = crate::parse(&daily).unwrap().remove(0).events.remove(0);
+        // tomorrow's occurrence
+        assert_eq!(
+            event.next_occurrence_after(local(1, 8, 12)),
+            Some(local(1, 9, 9))
+        );
+        // strictly after
+        assert_eq!(
+            event.next_occurrence_after(local(1, 9, 9)),
+            Some(local(1, 10, 9))
+        );
+        // skipping exceptions
+        assert_eq!(
+            event.next_occurrence_after(local(1, 10, 9)),
+            Some(local(1, 12, 9))
+        );
+
+        let input = weekly();
+        let event = crate::parse(&input).unwrap().remove(0).events.remove(0);
+        assert_eq!(
+            event.next_occurrence_after(local(1, 1, 12)),
+            Some(local(1, 3, 9))
+        );
+        assert_eq!(event.next_occurrence_after(local(1, 29, 9)), None);
+
+        let mut single = event.clone();
+        single.recurrence_rules.clear();
+        single.recurrence_dates.clear();
+        assert_eq!(
+            single.next_occurrence_after(local(1, 1, 8)),
+            Some(local(1, 1, 9))
+        );
+        assert_eq!(single.next_occurrence_after(local(1, 1, 9)), None);
+
+        // a rule that never matches ends rather than searching forever
+        let never = weekly().replace(
+            "FREQ=WEEKLY;COUNT=5",
+            "FREQ=MINUTELY;BYMONTH=2;BYMONTHDAY=30",
+        );
+        let event = crate::parse(&never).unwrap().remove(0).events.remove(0);
+        assert_eq!(event.next_occurrence_after(local(1, 3, 9)), None);
+    }
+}
@@ -0,0 +1,109 @@
+//! A human-readable overview of a calendar
+
+use std::fmt::Write;
+
+use crate::{Calendar, Event, types::DateOrDateTime};
+
+impl Calendar<'_> {
+    /// A human-readable overview of the calendar, for inspecting it rather than exchanging it.
+    ///
+    /// This lists the calendar's name, how many events it has and the dates they start
+    /// between, then each event's start and summary in chronological order (see
+    /// [`sort_events`](Self::sort_events)). The format may change.
+    pub fn summary_report(&self) -> String {
+        let mut events: Vec<&Event<'_>> = self.events.iter().collect();
+        events.sort_by(|ev1, ev2| ev1.cmp_start(ev2));
+        let mut dates = events
+            .iter()
+            .filter_map(|event| event.start.as_ref()?.date());
+
+        let mut report = String::new();
+        let name = self.name.as_deref().unwrap_or("(unnamed calendar)");
+        // writing to a `String` cannot fail
+        writeln!(report, "{name}").unwrap();
+        let count = match events.len() {
+            1 => "1 event".to_string(),
+            n => format!("{n} events"),
+        };
+        match (dates.next(), dates.next_back()) {
+            (Some(first), Some(last)) if first != last => {
+                writeln!(report, "{count}, from {first:?} to {last:?}").unwrap();
+            }
+            (Some(first), _) => writeln!(report, "{count}, on {first:?}").unwrap(),
+            (None, _) => writeln!(report, "{count}").unwrap(),
+        }
+        for event in events {
+            let summary = match &event.summary {
+                Some(summary) => &*summary.text,
+                None => "(no summary)",
+            };
+            match &event.start {
+                Some(start) => {
+                    write!(report, "- {}", fmt_start(start)).unwrap();
+                    if let Some(id) = &event.start_timezone_id {
+                        write!(report, " {}", id.id()).unwrap();
+                    }
+                    writeln!(report, ": {summary}").unwrap();
+                }
+                None => writeln!(report, "- (no start): {summary}").unwrap(),
+            }
+        }
+        report
+    }
+}
+
+/// e.g. `2025-07-04` or `2025-07-04 09:30 UTC`
+fn fmt_start(start: &DateOrDateTime) -> String {
+    match start {
+        DateOrDateTime::Date(date) => format!("{date:?}"),
+        DateOrDateTime::DateTime(date_time) => {
+            let time = date_time.time;
+            let utc = if time.utc { " UTC" } else { "" };
+            format!(
+                "{:?} {:02}:{:02}{utc}",
+                date_time.date, time.hour, time.minute
+            )
+        }
+        DateOrDateTime::Raw(value) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util;
+
+    #[test]
+    fn summary_report() {
+        let input = test_util::calendar(
+            "X-WR-CALNAME:Work\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:1\r\n\
+            DTSTART;TZID=Europe/London:20250704T093000\r\n\
+            SUMMARY:Retrospective\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:2\r\n\
+            SUMMARY:Someday\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:3\r\n\
+            DTSTART;VALUE=DATE:20250701\r\n\
+            SUMMARY:Quarter starts\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:4\r\n\
+            DTSTART:20250702T150000Z\r\n\
+            END:VEVENT\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        assert_eq!(
+            calendar.summary_report(),
+            "Work\n\
+            4 events, from 2025-07-01 to 2025-07-04\n\
+            - 2025-07-01: Quarter starts\n\
+            - 2025-07-02 15:00 UTC: (no summary)\n\
+            - 2025-07-04 09:30 Europe/London: Retrospective\n\
+            - (no start): Someday\n"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Checking calendars against the rules of their iTIP `METHOD` (RFC 5546)
+
+use std::fmt;
+
+use crate::{Calendar, Event, EventStatus};
+
+/// A way in which a calendar breaks the rules of its iTIP `METHOD`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    /// The `UID` of the offending event, or `None` if the issue is with the calendar itself
+    pub uid: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.uid {
+            Some(uid) => write!(f, "event `{uid}`: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl Calendar<'_> {
+    /// Check the events of this calendar have the properties its `METHOD` requires.
+    ///
+    /// Calendars without a `METHOD` are not scheduling messages so always pass. Only `VEVENT`s
+    /// are checked, and only the presence and number of properties, not their values (except
+    /// for `STATUS` in a `CANCEL`).
+    pub fn validate_scheduling(&self) -> Vec<ValidationIssue> {
+        let Some(method) = &self.method else {
+            return vec![];
+        };
+        let Some(rules) = MethodRules::for_method(method) else {
+            return vec![ValidationIssue {
+                uid: None,
+                message: format!("unknown METHOD {method}"),
+            }];
+        };
+
+        let mut issues = vec![];
+        if self.events.is_empty() {
+            issues.push(ValidationIssue {
+                uid: None,
+                message: format!("METHOD:{method} requires at least one VEVENT"),
+            });
+        }
+        for event in &self.events {
+            for message in rules.check(event) {
+                issues.push(ValidationIssue {
+                    uid: Some(event.uid.to_string()),
+                    message: format!("METHOD:{method} {message}"),
+                });
+            }
+        }
+        issues
+    }
+}
+
+/// How many `ATTENDEE`s a method allows
+enum Attendees {
+    None,
+    Any,
+    One,
+    AtLeastOne,
+}
+
+/// The `VEVENT` requirements of a method (RFC 5546 section 3.2)
+///
+/// All methods require `DTSTAMP`, `ORGANIZER` and `UID`.
+struct MethodRules {
+    attendees: Attendees,
+    start: bool,
+    summary: bool,
+    sequence: bool,
+    recurrence_id_allowed: bool,
+    cancel: bool,
+}
+
+impl MethodRules {
+    fn for_method(method: &str) -> Option<Self> {
+        let rules = MethodRules {
+            attendees: Attendees::Any,
+            start: false,
+            summary: false,
+            sequence: false,
+            recurrence_id_allowed: true,
+            cancel: false,
+        };
+        Some(match method {
+            "PUBLISH" => MethodRules {
+                attendees: Attendees::None,
+                start: true,
+                summary: true,
+                ..rules
+            },
+            "REQUEST" => MethodRules {
+                attendees: Attendees::AtLeastOne,
+                start: true,
+                summary: true,
+                ..rules
+            },
+            "REPLY" => MethodRules {
+                attendees: Attendees::One,
+                ..rules
+            },
+            "ADD" => MethodRules {
+                attendees: Attendees::AtLeastOne,
+                start: true,
+                summary: true,
+                sequence: true,
+                recurrence_id_allowed: false,
+                ..rules
+            },
+            "CANCEL" => MethodRules {
+                sequence: true,
+                cancel: true,
+                ..rules
+            },
+            "REFRESH" => MethodRules {
+                attendees: Attendees::One,
+                ..rules
+            },
+            "COUNTER" => MethodRules {
+                start: true,
+                summary: true,
+                ..rules
+            },
+            "DECLINECOUNTER" => rules,
+            _ => return None,
+        })
+    }
+
+    /// The ways `event` breaks these rules
+    fn check(&self, event: &Event<'_>) -> Vec<&'static str> {
+        let mut messages = vec![];
+        if event.timestamp.is_none() {
+            messages.push("requires DTSTAMP");
+        }
+        if event.organizer.is_none() {
+            messages.push("requires ORGANIZER");
+        }
+        if self.start && event.start.is_none() {
+            messages.push("requires DTSTART");
+        }
+        if self.summary && event.summary.is_none() {
+            messages.push("requires SUMMARY");
+        }
+        if self.sequence && event.sequence.is_none() {
+            messages.push("requires SEQUENCE");
+        }
+        if !self.recurrence_id_allowed && event.recurrence_id.is_some() {
+            messages.push("does not allow RECURRENCE-ID");
+        }
+        if self.cancel
+            && event
+                .status
+                .as_ref()
+                .is_some_and(|status| *status != EventStatus::Cancelled)
+        {
+            messages.push("requires STATUS to be CANCELLED if present");
+        }
+        match (&self.attendees, event.attendees.len()) {
+            (Attendees::None, 1..) => messages.push("does not allow ATTENDEE"),
+            (Attendees::One, count) if count != 1 => {
+                messages.push("requires exactly one ATTENDEE (the one replying)")
+            }
+            (Attendees::AtLeastOne, 0) => messages.push("requires at least one ATTENDEE"),
+            _ => (),
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util;
+
+    fn calendar_input(method: &str, attendees: &str) -> String {
+        test_util::calendar(&format!(
+            "METHOD:{method}\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:meeting@example.com\r\n\
+            DTSTAMP:20240101T000000Z\r\n\
+            DTSTART:20240102T090000Z\r\n\
+            SUMMARY:Planning\r\n\
+            ORGANIZER:mailto:boss@example.com\r\n\
+            {attendees}\
+            END:VEVENT\r\n"
+        ))
+    }
+
+    #[test]
+    fn request_requires_attendees() {
+        let input = calendar_input("REQUEST", "");
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let issues = calendar.validate_scheduling();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].uid.as_deref(), Some("meeting@example.com"));
+        assert_eq!(
+            issues[0].to_string(),
+            "event `meeting@example.com`: METHOD:REQUEST requires at least one ATTENDEE"
+        );
+
+        let input = calendar_input("REQUEST", "ATTENDEE:mailto:a@example.com\r\n");
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        assert_eq!(calendar.validate_scheduling(), []);
+    }
+
+    #[test]
+    fn reply_requires_one_attendee() {
+        let input = calendar_input(
+            "REPLY",
+            "ATTENDEE:mailto:a@example.com\r\nATTENDEE:mailto:b@example.com\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let issues = calendar.validate_scheduling();
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("exactly one ATTENDEE"));
+
+        let input = calendar_input("NOT-A-METHOD", "");
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        assert_eq!(calendar.validate_scheduling()[0].uid, None);
+    }
+}
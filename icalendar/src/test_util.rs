@@ -0,0 +1,33 @@
+//! Helpers shared by the unit tests
+
+/// A calendar with `body` (any more calendar properties, then its components) after its
+/// `PRODID` and `VERSION`
+pub fn calendar(body: &str) -> String {
+    format!(
+        "BEGIN:VCALENDAR\r\n\
+        PRODID:-//Example Corp.//CalDAV Client//EN\r\n\
+        VERSION:2.0\r\n\
+        {body}\
+        END:VCALENDAR\r\n"
+    )
+}
+
+/// A calendar with one event with a `UID` and `DTSTAMP`, and then `props`
+pub fn event(props: &str) -> String {
+    calendar(&format!(
+        "BEGIN:VEVENT\r\n\
+        UID:20111215T112233Z-1234@example.com\r\n\
+        DTSTAMP:20111215T112233Z\r\n\
+        {props}\
+        END:VEVENT\r\n"
+    ))
+}
+
+/// The first event of the first calendar in `input`
+///
+/// # Panics
+///
+/// If `input` doesn't parse or has no events.
+pub fn parse_event(input: &str) -> crate::Event<'_> {
+    crate::parse(input).unwrap().remove(0).events.remove(0)
+}
@@ -0,0 +1,256 @@
+//! Working with timezone definitions
+
+use std::borrow::Cow;
+
+use thiserror::Error;
+
+use crate::{
+    ObservanceKind, TimeZone,
+    types::{DateOrDateTime, DateTime, Duration, UtcOffset},
+};
+
+#[cfg(feature = "tzdb")]
+mod database;
+
+/// A timezone that couldn't be used
+#[derive(Debug, Error)]
+pub enum TimeZoneError {
+    /// A `TZID` with no definition
+    #[error("unknown timezone {0}")]
+    Unknown(String),
+    /// A timezone that couldn't be included in an export, see
+    /// [`Event::to_calendar_string`](crate::Event::to_calendar_string)
+    #[error("cannot include timezone {id}")]
+    Export {
+        id: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// A [`Raw`](crate::types::DateOrDateTime::Raw) value, which has no time to convert
+    #[error("cannot convert `{0}` to UTC, it isn't a Gregorian date")]
+    NotGregorian(String),
+}
+
+/// A change of UTC offset in a timezone
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Transition {
+    /// When the change happens (in UTC)
+    pub at: DateTime,
+    /// The offset from UTC after the change
+    pub offset: UtcOffset,
+    pub kind: ObservanceKind,
+}
+
+impl TimeZone<'_> {
+    /// The transitions from `from` (inclusive) to `to` (exclusive), in chronological order.
+    ///
+    /// `from` and `to` are treated as UTC times.
+    pub fn transitions_between(&self, from: DateTime, to: DateTime) -> Vec<Transition> {
+        let from = from.to_seconds();
+        let to = to.to_seconds();
+        let mut output = vec![];
+        for observance in &self.observances {
+            // Onsets are given in the local time before the transition.
+            let offset_from = observance.offset_from.as_seconds();
+            // Returns false once we are past `to`
+            let mut add_onset = |onset: DateTime| {
+                let at = onset.to_seconds() - offset_from;
+                if at >= to {
+                    return false;
+                }
+                if at >= from {
+                    output.push(Transition {
+                        at: DateTime::from_seconds(at, true),
+                        offset: observance.offset_to,
+                        kind: observance.kind,
+                    });
+                }
+                true
+            };
+
+            add_onset(observance.start);
+            for date in &observance.recurrence_dates {
+                add_onset(*date);
+            }
+            for rule in &observance.recurrence_rules {
+                // `UNTIL` is in UTC
+                let onsets = rule
+                    .occurrences(DateOrDateTime::DateTime(observance.start))
+                    .with_to_utc(|onset| {
+                        DateTime::from_seconds(onset.to_seconds() - offset_from, true)
+                    });
+                for onset in onsets {
+                    let DateOrDateTime::DateTime(onset) = onset else {
+                        unreachable!("occurrences have the same type as start");
+                    };
+                    if !add_onset(onset) {
+                        break;
+                    }
+                }
+            }
+        }
+        output.sort_by_key(|transition| transition.at);
+        output.dedup();
+        output
+    }
+
+    /// The offset from UTC in effect at `at` (a UTC time), or `None` if there are no
+    /// observances.
+    ///
+    /// Before the first onset the `offset_from` of the earliest observance applies.
+    pub fn offset_at(&self, at: DateTime) -> Option<UtcOffset> {
+        let seconds = at.to_seconds();
+        // rules repeat at least yearly, so the last onset is less than a (leap) year before
+        let year_before = DateTime::from_seconds(seconds - 366 * 86_400, true);
+        let after = DateTime::from_seconds(seconds + 1, true);
+        if let Some(transition) = self.transitions_between(year_before, after).last() {
+            return Some(transition.offset);
+        }
+        // no onsets in the last year: use the last observance that started, if any
+        let started = self
+            .observances
+            .iter()
+            .filter(|observance| {
+                observance.start.to_seconds() - observance.offset_from.as_seconds() <= seconds
+            })
+            .max_by_key(|observance| observance.start.to_seconds());
+        match started {
+            Some(observance) => Some(observance.offset_to),
+            None => self
+                .observances
+                .iter()
+                .min_by_key(|observance| observance.start.to_seconds())
+                .map(|observance| observance.offset_from),
+        }
+    }
+
+    /// Convert `local`, a time written in this timezone, to UTC.
+    ///
+    /// As in RFC 5545, local times skipped by a transition use the offset from before the
+    /// gap (so are moved forward), and ambiguous ones resolve to the first of the two. Times
+    /// are returned unchanged if there are no observances.
+    pub fn to_utc(&self, local: DateTime) -> DateTime {
+        let seconds = local.to_seconds();
+        let offset_near = |shift: i64| {
+            self.offset_at(DateTime::from_seconds(seconds + shift, true))
+                .map_or(0, UtcOffset::as_seconds)
+        };
+        // transitions are more than a day apart, so these are the offsets either side of any
+        // transition around `local`
+        let before = offset_near(-86_400);
+        let after = offset_near(86_400);
+        let offset = [before, after]
+            .into_iter()
+            .find(|&offset| offset_near(-offset) == offset)
+            .unwrap_or(before);
+        DateTime::from_seconds(seconds - offset, true)
+    }
+
+    /// Convert `utc` to the local time in this timezone
+    pub fn to_local(&self, utc: DateTime) -> DateTime {
+        let offset = self.offset_at(utc).map_or(0, UtcOffset::as_seconds);
+        DateTime::from_seconds(utc.to_seconds() + offset, false)
+    }
+
+    /// Move `local`, a time written in this timezone, by `duration`.
+    ///
+    /// Days and weeks are added to the local time, so keep the time of day even if a day is
+    /// 23 or 25 hours long because of a transition. Hours, minutes and seconds are then added
+    /// as elapsed time. Returns `None` if the result is outside years 0 to 9999.
+    pub fn add_duration(&self, local: DateTime, duration: &Duration) -> Option<DateTime> {
+        let (days, seconds) = duration.split_days();
+        let local = local.checked_add_seconds(days * 86_400)?;
+        let utc = self.to_utc(local).checked_add_seconds(seconds)?;
+        Some(self.to_local(utc))
+    }
+}
+
+/// The definition of timezone `id`, from `timezones` or else (with the `tzdb` feature) the
+/// bundled timezone database
+pub(crate) fn resolve<'a>(
+    timezones: &'a [TimeZone<'a>],
+    id: &str,
+) -> Option<Cow<'a, TimeZone<'a>>> {
+    if let Some(timezone) = timezones.iter().find(|timezone| timezone.id == id) {
+        return Some(Cow::Borrowed(timezone));
+    }
+    #[cfg(feature = "tzdb")]
+    if let Ok(timezone) = TimeZone::from_tzdb(id) {
+        return Some(Cow::Owned(timezone));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ObservanceKind, test_util,
+        types::{Date, DateTime, Time, UtcOffset},
+    };
+
+    fn utc(full_year: u16, month: u8, day: u8, hour: u8) -> DateTime {
+        DateTime {
+            date: Date {
+                full_year,
+                month,
+                day,
+            },
+            time: Time {
+                hour,
+                minute: 0,
+                second: 0,
+                utc: true,
+            },
+        }
+    }
+
+    #[test]
+    fn us_eastern_transitions() {
+        let input = test_util::calendar(
+            "BEGIN:VTIMEZONE\r\n\
+            TZID:America/New_York\r\n\
+            BEGIN:DAYLIGHT\r\n\
+            TZOFFSETFROM:-0500\r\n\
+            TZOFFSETTO:-0400\r\n\
+            TZNAME:EDT\r\n\
+            DTSTART:20070311T020000\r\n\
+            RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\r\n\
+            END:DAYLIGHT\r\n\
+            BEGIN:STANDARD\r\n\
+            TZOFFSETFROM:-0400\r\n\
+            TZOFFSETTO:-0500\r\n\
+            TZNAME:EST\r\n\
+            DTSTART:20071104T020000\r\n\
+            RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\r\n\
+            END:STANDARD\r\n\
+            END:VTIMEZONE\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let timezone = &calendar.timezones[0];
+        assert_eq!(timezone.id, "America/New_York");
+
+        let transitions = timezone.transitions_between(utc(2024, 1, 1, 0), utc(2025, 1, 1, 0));
+        assert_eq!(transitions.len(), 2);
+
+        assert_eq!(transitions[0].at, utc(2024, 3, 10, 7));
+        assert_eq!(transitions[0].kind, ObservanceKind::Daylight);
+        assert_eq!(transitions[0].offset, "-0400".parse::<UtcOffset>().unwrap());
+
+        assert_eq!(transitions[1].at, utc(2024, 11, 3, 6));
+        assert_eq!(transitions[1].kind, ObservanceKind::Standard);
+        assert_eq!(transitions[1].offset, "-0500".parse::<UtcOffset>().unwrap());
+
+        let local = |month, day, hour| DateTime {
+            time: Time {
+                utc: false,
+                ..utc(2024, month, day, hour).time
+            },
+            ..utc(2024, month, day, hour)
+        };
+        assert_eq!(timezone.to_utc(local(1, 15, 9)), utc(2024, 1, 15, 14));
+        assert_eq!(timezone.to_utc(local(7, 15, 9)), utc(2024, 7, 15, 13));
+        assert_eq!(timezone.to_local(utc(2024, 7, 15, 13)), local(7, 15, 9));
+        // 02:30 doesn't exist on the day DST starts
+        assert_eq!(timezone.to_utc(local(3, 10, 2)), utc(2024, 3, 10, 7));
+    }
+}
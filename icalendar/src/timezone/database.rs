@@ -0,0 +1,196 @@
+//! Timezone definitions from the bundled timezone database
+
+use std::borrow::Cow;
+
+use anyhow::bail;
+use tz::{
+    LocalTimeType,
+    timezone::{RuleDay, TransitionRule},
+};
+
+use crate::{
+    AnnotatedText, Observance, ObservanceKind, Result, TimeZone,
+    types::{Date, DateOrDateTime, DateTime, Recur, Time, UtcOffset},
+};
+
+impl TimeZone<'static> {
+    /// The definition of timezone `id` (e.g. `Europe/Paris`) from the bundled timezone database.
+    ///
+    /// Only the current rules are included, so the definition may be wrong for times before
+    /// the last change of rules.
+    pub fn from_tzdb(id: &str) -> Result<Self> {
+        let Some(timezone) = tzdb::tz_by_name(id) else {
+            bail!("timezone {id} not found in the timezone database");
+        };
+        let observances = match timezone.extra_rule() {
+            Some(TransitionRule::Alternate(rule)) => vec![
+                rule_observance(
+                    ObservanceKind::Daylight,
+                    rule.std(),
+                    rule.dst(),
+                    rule.dst_start(),
+                    rule.dst_start_time(),
+                )?,
+                rule_observance(
+                    ObservanceKind::Standard,
+                    rule.dst(),
+                    rule.std(),
+                    rule.dst_end(),
+                    rule.dst_end_time(),
+                )?,
+            ],
+            Some(TransitionRule::Fixed(local_time)) => vec![fixed_observance(local_time)],
+            None => {
+                // the local time after the last transition applies forever
+                let index = timezone
+                    .transitions()
+                    .last()
+                    .map_or(0, |transition| transition.local_time_type_index());
+                vec![fixed_observance(&timezone.local_time_types()[index])]
+            }
+        };
+        Ok(TimeZone {
+            id: Cow::Owned(id.to_owned()),
+            last_modified: None,
+            url: None,
+            observances,
+        })
+    }
+}
+
+fn fixed_observance(local_time: &LocalTimeType) -> Observance<'static> {
+    let offset = UtcOffset::from_seconds(local_time.ut_offset().into());
+    Observance {
+        kind: ObservanceKind::Standard,
+        start: DateTime {
+            date: Date {
+                full_year: 1970,
+                month: 1,
+                day: 1,
+            },
+            time: Time {
+                hour: 0,
+                minute: 0,
+                second: 0,
+                utc: false,
+            },
+        },
+        offset_from: offset,
+        offset_to: offset,
+        recurrence_rules: vec![],
+        recurrence_dates: vec![],
+        names: observance_names(local_time),
+        comments: vec![],
+    }
+}
+
+/// An observance starting every year on `day` at `time` (seconds after midnight, local time
+/// in `from`)
+fn rule_observance(
+    kind: ObservanceKind,
+    from: &LocalTimeType,
+    to: &LocalTimeType,
+    day: &RuleDay,
+    time: i32,
+) -> Result<Observance<'static>> {
+    const WEEKDAYS: [&str; 7] = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
+
+    let RuleDay::MonthWeekDay(day) = day else {
+        bail!("only transition rules of the form month/week/day are supported");
+    };
+    if !(0..86_400).contains(&time) {
+        bail!("transition times outside 00:00 - 24:00 are not supported");
+    }
+    // week 5 means the last week of the month
+    let week = if day.week() == 5 {
+        -1
+    } else {
+        i16::from(day.week())
+    };
+    let rule: Recur = format!(
+        "FREQ=YEARLY;BYMONTH={};BYDAY={week}{}",
+        day.month(),
+        WEEKDAYS[usize::from(day.week_day())]
+    )
+    .parse()?;
+
+    // `as`: all values are < 86400 so fit
+    let first = DateTime {
+        date: Date {
+            full_year: 1970,
+            month: 1,
+            day: 1,
+        },
+        time: Time {
+            hour: (time / 3600) as u8,
+            minute: (time % 3600 / 60) as u8,
+            second: (time % 60) as u8,
+            utc: false,
+        },
+    };
+    let Some(DateOrDateTime::DateTime(start)) =
+        rule.occurrences(DateOrDateTime::DateTime(first)).next()
+    else {
+        unreachable!("yearly rules without an end always have occurrences");
+    };
+
+    Ok(Observance {
+        kind,
+        start,
+        offset_from: UtcOffset::from_seconds(from.ut_offset().into()),
+        offset_to: UtcOffset::from_seconds(to.ut_offset().into()),
+        recurrence_rules: vec![rule],
+        recurrence_dates: vec![],
+        names: observance_names(to),
+        comments: vec![],
+    })
+}
+
+fn observance_names(local_time: &LocalTimeType) -> Vec<AnnotatedText<'static>> {
+    let name = local_time.time_zone_designation();
+    if name.is_empty() {
+        return vec![];
+    }
+    vec![AnnotatedText {
+        lang: None,
+        altrep: None,
+        text: Cow::Owned(name.to_owned()),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        TimeZone,
+        types::{Date, DateTime, Time, UtcOffset},
+    };
+
+    fn utc(full_year: u16, month: u8, day: u8, hour: u8) -> DateTime {
+        DateTime {
+            date: Date {
+                full_year,
+                month,
+                day,
+            },
+            time: Time {
+                hour,
+                minute: 0,
+                second: 0,
+                utc: true,
+            },
+        }
+    }
+
+    #[test]
+    fn from_tzdb() {
+        let timezone = TimeZone::from_tzdb("Europe/Paris").unwrap();
+        let transitions = timezone.transitions_between(utc(2024, 1, 1, 0), utc(2025, 1, 1, 0));
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(transitions[0].at, utc(2024, 3, 31, 1));
+        assert_eq!(transitions[0].offset, "+0200".parse::<UtcOffset>().unwrap());
+        assert_eq!(transitions[1].at, utc(2024, 10, 27, 1));
+        assert_eq!(transitions[1].offset, "+0100".parse::<UtcOffset>().unwrap());
+
+        assert!(TimeZone::from_tzdb("Mars/Olympus_Mons").is_err());
+    }
+}
@@ -7,12 +7,17 @@ use crate::{Result, values::Uri};
 
 /// Data represents bytes that are eather stored in base64 locally, or
 /// supplied through a URI that must be accessed
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Data<'src> {
     Uri(Uri<'src>),
     /// Currently data is eagerly parsed.
     ///
     /// If you have a use-case for lazy parsing please raise an issue.
     Blob(Vec<u8>),
+    /// A URI value that isn't a valid URI, as written. Only produced in lenient mode, e.g. for
+    /// the alarm sound names (`Chord`) Apple writes.
+    Raw(Cow<'src, str>),
 }
 
 impl<'src> fmt::Debug for Data<'src> {
@@ -20,6 +25,7 @@ impl<'src> fmt::Debug for Data<'src> {
         match self {
             Data::Uri(uri) => write!(f, "{uri:?}"),
             Data::Blob(_) => f.write_str("Blob"),
+            Data::Raw(raw) => write!(f, "{raw:?}"),
         }
     }
 }
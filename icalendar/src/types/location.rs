@@ -1,14 +1,63 @@
 use core::fmt;
-use std::str::FromStr;
+use std::{borrow::Cow, str::FromStr};
 
 use anyhow::bail;
 
-#[derive(Debug)]
+use crate::Result;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GeoLocation {
     pub latitude: f64,
     pub longitude: f64,
 }
 
+impl GeoLocation {
+    /// Parse a `geo:` URI (RFC 5870), e.g. `geo:37.386013,-122.082932`.
+    ///
+    /// Altitude and URI parameters are ignored.
+    pub fn from_geo_uri(input: &str) -> Result<Self> {
+        let Some(coords) = input
+            .get(..4)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("geo:"))
+            .map(|_| &input[4..])
+        else {
+            bail!("expected `geo:` URI");
+        };
+        let coords = coords.split_once(';').map_or(coords, |(coords, _)| coords);
+        let mut parts = coords.split(',');
+        let (Some(latitude), Some(longitude)) = (parts.next(), parts.next()) else {
+            bail!("expected `,`");
+        };
+        Ok(Self {
+            latitude: latitude.parse()?,
+            longitude: longitude.parse()?,
+        })
+    }
+
+    /// Display as `latitude;longitude` with `digits` decimal places
+    pub fn display_precision(&self, digits: usize) -> impl fmt::Display {
+        struct WithPrecision(GeoLocation, usize);
+
+        impl fmt::Display for WithPrecision {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{:.*}", self.1, self.0)
+            }
+        }
+
+        WithPrecision(*self, digits)
+    }
+}
+
+/// Where something takes place, aggregated from the various ways a location can be given.
+///
+/// See [`Event::place`](crate::Event::place).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Place<'a> {
+    pub name: Option<Cow<'a, str>>,
+    pub coordinates: Option<GeoLocation>,
+}
+
 impl FromStr for GeoLocation {
     type Err = anyhow::Error;
 
@@ -23,10 +72,34 @@ impl FromStr for GeoLocation {
     }
 }
 
+/// Written with 6 decimal places (about 10cm), or the precision given in the format string
+/// (`{:.3}`). See [`GeoLocation::display_precision`].
 impl fmt::Display for GeoLocation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        fmt::Display::fmt(&self.latitude, f)?;
-        f.write_str(";")?;
-        fmt::Display::fmt(&self.longitude, f)
+        let digits = f.precision().unwrap_or(6);
+        write!(f, "{:.digits$};{:.digits$}", self.latitude, self.longitude)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GeoLocation;
+
+    #[test]
+    fn display_precision() {
+        let location = GeoLocation {
+            latitude: 48.853410000001,
+            longitude: -122.0829321,
+        };
+        assert_eq!(location.to_string(), "48.853410;-122.082932");
+        assert_eq!(location.display_precision(2).to_string(), "48.85;-122.08");
+        assert_eq!(location.display_precision(0).to_string(), "49;-122");
+
+        // round trips are stable at 6 digits
+        let written = location.to_string();
+        let parsed: GeoLocation = written.parse().unwrap();
+        assert_eq!(parsed.to_string(), written);
+        let reparsed: GeoLocation = parsed.to_string().parse().unwrap();
+        assert_eq!(reparsed, parsed);
     }
 }
@@ -1,13 +1,14 @@
 //! Types that are contained in either values or params
-use std::{fmt, str::FromStr};
+use std::{borrow::Cow, cmp::Ordering, fmt, str::FromStr};
 
 use anyhow::bail;
+use thiserror::Error;
 
 use crate::{
     Result,
     parser::{
         ParserError,
-        helpers::{_1or2_digit_int, _1to4_digit_int, parse_u32, tag},
+        helpers::{_1or2_digit_int, _1to4_digit_int, all_consuming, parse_u32, tag},
     },
 };
 
@@ -20,7 +21,7 @@ mod name;
 pub use name::{Name, XName};
 
 mod location;
-pub use location::GeoLocation;
+pub use location::{GeoLocation, Place};
 
 mod priority;
 pub use priority::Priority;
@@ -28,6 +29,9 @@ pub use priority::Priority;
 mod data;
 pub use data::Data;
 
+mod utc_offset;
+pub use utc_offset::UtcOffset;
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DateTime {
     pub date: Date,
@@ -41,6 +45,52 @@ impl DateTime {
         let (input, time) = Time::parse(input)?;
         Ok((input, DateTime { date, time }))
     }
+
+    /// Seconds since 1970-01-01T00:00:00, ignoring whether the time is UTC
+    pub(crate) fn to_seconds(self) -> i64 {
+        self.date.to_days() * 86400
+            + i64::from(self.time.hour) * 3600
+            + i64::from(self.time.minute) * 60
+            + i64::from(self.time.second)
+    }
+
+    /// This time moved later by `duration`, or `None` if that is outside years 0 to 9999.
+    ///
+    /// Days are always 24 hours long: timezone transitions aren't taken into account.
+    pub fn checked_add(&self, duration: Duration) -> Option<DateTime> {
+        self.checked_add_seconds(duration.as_seconds())
+    }
+
+    /// This time moved earlier by `duration`, see [`DateTime::checked_add`].
+    pub fn checked_sub(&self, duration: Duration) -> Option<DateTime> {
+        self.checked_add_seconds(duration.as_seconds().checked_neg()?)
+    }
+
+    pub(crate) fn checked_add_seconds(&self, seconds: i64) -> Option<DateTime> {
+        const MIN: i64 = -62_167_219_200; // 0000-01-01T00:00:00
+        const MAX: i64 = 253_402_300_799; // 9999-12-31T23:59:59
+        let seconds = self.to_seconds().checked_add(seconds)?;
+        if !(MIN..=MAX).contains(&seconds) {
+            return None;
+        }
+        Some(DateTime::from_seconds(seconds, self.time.utc))
+    }
+
+    /// Inverse of [`DateTime::to_seconds`]
+    pub(crate) fn from_seconds(seconds: i64, utc: bool) -> Self {
+        let days = seconds.div_euclid(86400);
+        let seconds = seconds.rem_euclid(86400);
+        // `as`: all values are < 86400 so fit
+        DateTime {
+            date: Date::from_days(days),
+            time: Time {
+                hour: (seconds / 3600) as u8,
+                minute: (seconds % 3600 / 60) as u8,
+                second: (seconds % 60) as u8,
+                utc,
+            },
+        }
+    }
 }
 
 impl fmt::Display for DateTime {
@@ -55,6 +105,9 @@ impl fmt::Debug for DateTime {
     }
 }
 
+#[cfg(feature = "serde")]
+impl_serde_str!(DateTime, |input| all_consuming(&input, DateTime::parse));
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Date {
     /// Full year
@@ -74,13 +127,32 @@ impl Date {
         // all ascii so we can use u8,
 
         let (input, full_year) = _1to4_digit_int("year", u16::MIN, u16::MAX)(input)?;
-        let leap_year = full_year % 4 == 0;
-
         let (input, month) = _1or2_digit_int("month", 1, 12)(input)?;
+        let max_day = Date::days_in_month(full_year, month);
+        let (input, day) = _1or2_digit_int("day", 1, max_day)(input)?;
+
+        Ok((
+            input,
+            Self {
+                full_year,
+                month,
+                day,
+            },
+        ))
+    }
+}
 
-        let max_day = match month {
+impl Date {
+    /// Whether `year` is a leap year in the Gregorian calendar
+    pub fn is_leap_year(year: u16) -> bool {
+        year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+    }
+
+    /// Number of days in `month` (1 - 12) of `year`
+    pub fn days_in_month(year: u16, month: u8) -> u8 {
+        match month {
             2 => {
-                if leap_year {
+                if Date::is_leap_year(year) {
                     29
                 } else {
                     28
@@ -89,17 +161,69 @@ impl Date {
             // 30 days hath september april june and november
             4 | 6 | 9 | 11 => 30,
             _ => 31,
+        }
+    }
+
+    /// Number of days in `year`
+    pub fn days_in_year(year: u16) -> u16 {
+        if Date::is_leap_year(year) { 366 } else { 365 }
+    }
+
+    /// Day of the year, starting at 1 for 1st January
+    pub fn day_of_year(&self) -> u16 {
+        let start = Date {
+            full_year: self.full_year,
+            month: 1,
+            day: 1,
         };
-        let (input, day) = _1or2_digit_int("day", 1, max_day)(input)?;
+        // `as`: difference is < 366
+        (self.to_days() - start.to_days()) as u16 + 1
+    }
 
-        Ok((
-            input,
-            Self {
-                full_year,
-                month,
-                day,
-            },
-        ))
+    pub fn weekday(&self) -> recur::WeekDay {
+        use recur::WeekDay::*;
+        // 1970-01-01 was a Thursday
+        match (self.to_days() + 4).rem_euclid(7) {
+            0 => Sunday,
+            1 => Monday,
+            2 => Tuesday,
+            3 => Wednesday,
+            4 => Thursday,
+            5 => Friday,
+            _ => Saturday,
+        }
+    }
+
+    /// Days since 1970-01-01
+    // algorithm from http://howardhinnant.github.io/date_algorithms.html
+    pub(crate) fn to_days(self) -> i64 {
+        let month = i64::from(self.month);
+        let year = i64::from(self.full_year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    /// Inverse of [`Date::to_days`]
+    pub(crate) fn from_days(days: i64) -> Self {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days - era * 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        // `as`: values are in range for valid dates
+        Date {
+            full_year: year as u16,
+            month: month as u8,
+            day: day as u8,
+        }
     }
 }
 
@@ -115,6 +239,9 @@ impl fmt::Debug for Date {
     }
 }
 
+#[cfg(feature = "serde")]
+impl_serde_str!(Date, |input| all_consuming(&input, Date::parse));
+
 // Time
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -174,6 +301,9 @@ impl fmt::Debug for Time {
     }
 }
 
+#[cfg(feature = "serde")]
+impl_serde_str!(Time, |input| all_consuming(&input, Time::parse));
+
 pub(crate) fn time_hour(input: &str) -> Result<(&str, u8), ParserError> {
     let Some((hour, rest)) = input.split_at_checked(2) else {
         return Err(ParserError::expected("2 ascii digits"));
@@ -214,13 +344,66 @@ pub(crate) fn time_second(include_leap: bool, input: &str) -> Result<(&str, u8),
 }
 
 /// Note - ord is not chronological
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum DateOrDateTime {
     Date(Date),
     DateTime(DateTime),
+    /// A value of a calendar with a non-Gregorian [`CalScale`](crate::CalScale), kept as
+    /// written since it can't be interpreted. Values containing a `T` are date-times.
+    ///
+    /// Nothing is calculated from these: events starting at one have no duration, end or
+    /// instances, and can't be converted to UTC.
+    Raw(Cow<'static, str>),
 }
 
 impl DateOrDateTime {
+    /// The date part, or `None` for a [`Raw`](Self::Raw) value
+    pub fn date(&self) -> Option<Date> {
+        match self {
+            DateOrDateTime::Date(date) => Some(*date),
+            DateOrDateTime::DateTime(date_time) => Some(date_time.date),
+            DateOrDateTime::Raw(_) => None,
+        }
+    }
+
+    /// Whether this is a date-time, rather than a date
+    pub fn is_date_time(&self) -> bool {
+        match self {
+            DateOrDateTime::Date(_) => false,
+            DateOrDateTime::DateTime(_) => true,
+            DateOrDateTime::Raw(value) => value.contains('T'),
+        }
+    }
+
+    /// Compare chronologically, treating a date as the start of that day.
+    ///
+    /// Timezones are not taken into account: UTC and local times are compared as-is.
+    /// [`Raw`](Self::Raw) values come after all others, compared as written.
+    pub fn cmp_chronological(&self, other: &Self) -> Ordering {
+        // raw values sort last, as `Err`
+        type Key<'a> = Result<(Date, Option<(u8, u8, u8)>), &'a str>;
+        fn key(value: &DateOrDateTime) -> Key<'_> {
+            match value {
+                DateOrDateTime::Date(date) => Ok((*date, None)),
+                DateOrDateTime::DateTime(DateTime { date, time }) => {
+                    Ok((*date, Some((time.hour, time.minute, time.second))))
+                }
+                DateOrDateTime::Raw(value) => Err(value),
+            }
+        }
+        key(self).cmp(&key(other))
+    }
+
+    /// Parse a value of a calendar that is `gregorian` or not: values of other calendars are
+    /// kept [`Raw`](Self::Raw)
+    pub(crate) fn parse_in(input: &str, gregorian: bool) -> Result<(&str, Self)> {
+        if gregorian {
+            Self::parse(input)
+        } else {
+            Ok(("", DateOrDateTime::Raw(input.to_owned().into())))
+        }
+    }
+
     pub(crate) fn parse(input: &str) -> Result<(&str, Self)> {
         let (input, date) = Date::parse(input)?;
         if matches!(input.chars().next(), Some('T')) {
@@ -238,6 +421,7 @@ impl fmt::Display for DateOrDateTime {
         match self {
             DateOrDateTime::Date(date) => fmt::Display::fmt(date, f),
             DateOrDateTime::DateTime(date_time) => fmt::Display::fmt(date_time, f),
+            DateOrDateTime::Raw(value) => f.write_str(value),
         }
     }
 }
@@ -247,13 +431,29 @@ impl fmt::Debug for DateOrDateTime {
         match self {
             DateOrDateTime::Date(date) => fmt::Debug::fmt(date, f),
             DateOrDateTime::DateTime(date_time) => fmt::Debug::fmt(date_time, f),
+            DateOrDateTime::Raw(value) => write!(f, "Raw({value})"),
         }
     }
 }
 
+impl FromStr for DateOrDateTime {
+    type Err = anyhow::Error;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        all_consuming(input, DateOrDateTime::parse)
+    }
+}
+
+#[cfg(feature = "serde")]
+// anything that isn't a Gregorian value was `Raw`
+impl_serde_str!(DateOrDateTime, |input| match input.parse() {
+    Ok(value) => Ok(value),
+    Err(_) => Ok(DateOrDateTime::Raw(input.into())),
+});
+
 // Duration
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Duration {
     pub negative: bool,
     pub kind: DurationKind,
@@ -266,9 +466,143 @@ impl Duration {
         let (input, kind) = DurationKind::parse(input)?;
         Ok((input, Duration { negative, kind }))
     }
+
+    /// A duration of `seconds`, in days, hours, minutes and seconds
+    pub(crate) fn from_seconds(seconds: i64) -> Self {
+        let negative = seconds < 0;
+        let seconds = seconds.unsigned_abs();
+        // `as`: dates are limited to 4 digit years, so the days always fit
+        Duration {
+            negative,
+            kind: DurationKind::DateTime {
+                days: (seconds / 86_400) as u32,
+                hours: (seconds % 86_400 / 3600) as u32,
+                minutes: (seconds % 3600 / 60) as u32,
+                seconds: (seconds % 60) as u32,
+            },
+        }
+    }
+
+    /// The length of the duration in seconds, negative if the duration is. Days are taken to
+    /// be 24 hours.
+    pub fn as_seconds(&self) -> i64 {
+        let seconds = match self.kind {
+            DurationKind::Weeks(weeks) => i64::from(weeks) * 7 * 86_400,
+            DurationKind::DateTime {
+                days,
+                hours,
+                minutes,
+                seconds,
+            } => {
+                i64::from(days) * 86_400
+                    + i64::from(hours) * 3600
+                    + i64::from(minutes) * 60
+                    + i64::from(seconds)
+            }
+        };
+        if self.negative { -seconds } else { seconds }
+    }
+
+    /// The duration split into whole days (weeks count as 7) and the remaining seconds, both
+    /// negative if the duration is.
+    ///
+    /// RFC 5545 treats days as nominal: they move the time to the same time of day, however
+    /// long the days are. The rest is exact.
+    pub(crate) fn split_days(&self) -> (i64, i64) {
+        let (days, seconds) = match self.kind {
+            DurationKind::Weeks(weeks) => (i64::from(weeks) * 7, 0),
+            DurationKind::DateTime {
+                days,
+                hours,
+                minutes,
+                seconds,
+            } => (
+                i64::from(days),
+                i64::from(hours) * 3600 + i64::from(minutes) * 60 + i64::from(seconds),
+            ),
+        };
+        if self.negative {
+            (-days, -seconds)
+        } else {
+            (days, seconds)
+        }
+    }
+}
+
+/// A [`std::time::Duration`] too long to convert to a [`Duration`]
+#[derive(Debug, Error)]
+#[error("duration of {seconds} seconds is too long")]
+pub struct DurationTooLong {
+    pub seconds: u64,
+}
+
+/// Fails if the duration is more than [`u32::MAX`] days. Fractions of a second are dropped.
+impl TryFrom<std::time::Duration> for Duration {
+    type Error = DurationTooLong;
+    fn try_from(duration: std::time::Duration) -> Result<Self, DurationTooLong> {
+        let seconds = duration.as_secs();
+        if seconds / 86_400 > u64::from(u32::MAX) {
+            return Err(DurationTooLong { seconds });
+        }
+        // `as`: checked above, so any value fits
+        Ok(Duration {
+            negative: false,
+            kind: DurationKind::DateTime {
+                days: (seconds / 86_400) as u32,
+                hours: (seconds % 86_400 / 3600) as u32,
+                minutes: (seconds % 3600 / 60) as u32,
+                seconds: (seconds % 60) as u32,
+            },
+        })
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+        f.write_str("P")?;
+        match self.kind {
+            DurationKind::Weeks(weeks) => write!(f, "{weeks}W"),
+            DurationKind::DateTime {
+                days,
+                hours,
+                minutes,
+                seconds,
+            } => {
+                let has_time = (hours, minutes, seconds) != (0, 0, 0);
+                if days > 0 || !has_time {
+                    write!(f, "{days}D")?;
+                }
+                if has_time {
+                    f.write_str("T")?;
+                    if hours > 0 {
+                        write!(f, "{hours}H")?;
+                    }
+                    // minutes can't be skipped between hours and seconds
+                    if minutes > 0 || (hours > 0 && seconds > 0) {
+                        write!(f, "{minutes}M")?;
+                    }
+                    if seconds > 0 {
+                        write!(f, "{seconds}S")?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
-#[derive(Debug)]
+impl FromStr for Duration {
+    type Err = anyhow::Error;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        all_consuming(input, Duration::parse)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DurationKind {
     Weeks(u32),
     DateTime {
@@ -328,6 +662,18 @@ impl DurationKind {
                 }
                 _ => bail!("expected `W` or `D`"),
             }
+            // the time is optional after days
+            if !input.starts_with('T') {
+                return Ok((
+                    input,
+                    DurationKind::DateTime {
+                        days,
+                        hours,
+                        minutes,
+                        seconds,
+                    },
+                ));
+            }
         }
         let (input, _) = tag("T")(input)?;
         let Some((mut input, num)) = parse_u32(input)? else {
@@ -364,6 +710,8 @@ impl DurationKind {
 
 // Period
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Period {
     Explicit {
         start: DateTime,
@@ -430,6 +778,52 @@ impl Recur {
     }
 }
 
+impl fmt::Display for Recur {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.freq)?;
+        if let Some(end) = self.end.fmt() {
+            write!(f, ";{end}")?;
+        }
+        if let Some(interval) = self.interval {
+            write!(f, ";INTERVAL={}", interval.value())?;
+        }
+        if let Some(by) = &self.by_second {
+            write!(f, ";BYSECOND={}", by.0.display())?;
+        }
+        if let Some(by) = &self.by_minute {
+            write!(f, ";BYMINUTE={}", by.0.display())?;
+        }
+        if let Some(by) = &self.by_hour {
+            write!(f, ";BYHOUR={}", by.0.display())?;
+        }
+        if let Some(by) = &self.by_week_day {
+            write!(f, ";BYDAY={}", by.0.display())?;
+        }
+        if let Some(by) = &self.by_month_day {
+            write!(f, ";BYMONTHDAY={}", by.0.display())?;
+        }
+        if let Some(by) = &self.by_year_day {
+            write!(f, ";BYYEARDAY={}", by.0.display())?;
+        }
+        if let Some(by) = &self.by_week_no {
+            write!(f, ";BYWEEKNO={}", by.0.display())?;
+        }
+        if let Some(by) = &self.by_month {
+            write!(f, ";BYMONTH={}", by.0.display())?;
+        }
+        if let Some(by) = &self.by_set_pos {
+            write!(f, ";BYSETPOS={}", by.0.display())?;
+        }
+        if let Some(week_start) = self.week_start {
+            write!(f, ";WKST={}", week_start.0)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_str!(Recur, |input| input.parse());
+
 impl FromStr for Recur {
     type Err = anyhow::Error;
     fn from_str(input: &str) -> Result<Self, Self::Err> {
@@ -457,7 +851,71 @@ fn split_once(test_ch: char, input: &str) -> (&str, &str) {
 #[cfg(test)]
 mod tests {
 
-    use super::{Date, DateTime, Recur, recur};
+    use super::{Date, DateTime, Duration, Recur, recur};
+
+    #[test]
+    fn date_arithmetic() {
+        let date = Date {
+            full_year: 2000,
+            month: 2,
+            day: 29,
+        };
+        assert_eq!(Date::from_days(date.to_days()), date);
+        assert_eq!(date.day_of_year(), 60);
+        assert_eq!(date.weekday(), recur::WeekDay::Tuesday);
+        assert!(Date::is_leap_year(2000));
+        assert!(!Date::is_leap_year(1900));
+        assert!(Date::parse("19000229").is_err());
+    }
+
+    #[test]
+    fn date_time_arithmetic() {
+        let add = |date_time: &str, duration: &str| {
+            let date_time: DateTime = super::all_consuming(date_time, DateTime::parse).unwrap();
+            let duration: Duration = duration.parse().unwrap();
+            date_time.checked_add(duration).map(|dt| dt.to_string())
+        };
+        // month boundary
+        assert_eq!(
+            add("20240131T230000Z", "PT2H").as_deref(),
+            Some("20240201T010000Z")
+        );
+        // leap day
+        assert_eq!(
+            add("20240228T120000", "P1D").as_deref(),
+            Some("20240229T120000")
+        );
+        assert_eq!(
+            add("20230228T120000", "P1D").as_deref(),
+            Some("20230301T120000")
+        );
+        assert_eq!(
+            add("21000228T120000", "P1D").as_deref(),
+            Some("21000301T120000")
+        );
+        // year boundary
+        assert_eq!(
+            add("20241231T235959", "PT1S").as_deref(),
+            Some("20250101T000000")
+        );
+        assert_eq!(
+            add("20250101T000000", "-P1W").as_deref(),
+            Some("20241225T000000")
+        );
+        assert_eq!(add("99991231T000000", "P1D"), None);
+
+        let start: DateTime = super::all_consuming("20240301T000000Z", DateTime::parse).unwrap();
+        let earlier = start.checked_sub("P1D".parse().unwrap()).unwrap();
+        assert_eq!(earlier.to_string(), "20240229T000000Z");
+        assert_eq!(
+            start.checked_sub(Duration::from_seconds(64_000_000_000)),
+            None
+        );
+
+        let duration = Duration::try_from(std::time::Duration::from_millis(90_061_500)).unwrap();
+        assert_eq!(duration.to_string(), "P1DT1H1M1S");
+        assert!(Duration::try_from(std::time::Duration::MAX).is_err());
+    }
 
     #[test]
     fn format_date() {
@@ -14,6 +14,16 @@ impl<'src> Name<'src> {
         Self::Iana(name)
     }
 
+    /// An extension name without a vendor ID, given without its `X-`.
+    ///
+    /// NOTE: this function does not check the name is valid.
+    pub(crate) const fn x(value: &'static str) -> Self {
+        Self::XName(XName {
+            vendor: None,
+            value: Cow::Borrowed(value),
+        })
+    }
+
     pub(crate) fn x_unchecked(name: impl Into<XName<'src>>) -> Self {
         let name = name.into();
         Self::XName(name)
@@ -39,15 +49,13 @@ impl fmt::Debug for Name<'_> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl_serde_str!(Name<'src>, |input| Name::parse(input));
+
 impl<'a> PartialEq<str> for Name<'a> {
     fn eq(&self, other: &str) -> bool {
         match self {
-            Name::XName(xname) => {
-                let Ok(other) = XName::parse(Cow::Borrowed(other)) else {
-                    return false;
-                };
-                xname == &other
-            }
+            Name::XName(xname) => xname == other,
             Name::Iana(name) => *name == other,
         }
     }
@@ -59,6 +67,18 @@ impl<'a> PartialEq<Name<'a>> for str {
     }
 }
 
+impl<'a, 'b> PartialEq<XName<'b>> for Name<'a> {
+    fn eq(&self, other: &XName<'b>) -> bool {
+        matches!(self, Name::XName(xname) if xname == other)
+    }
+}
+
+impl<'a, 'b> PartialEq<Name<'b>> for XName<'a> {
+    fn eq(&self, other: &Name<'b>) -> bool {
+        other.eq(self)
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct XName<'src> {
     /// 3-character ascii alphanumeric
@@ -86,3 +106,21 @@ impl fmt::Debug for XName<'_> {
         fmt::Display::fmt(self, f)
     }
 }
+
+impl<'a> PartialEq<str> for XName<'a> {
+    fn eq(&self, other: &str) -> bool {
+        matches!(Name::parse(other), Ok(Name::XName(other)) if *self == other)
+    }
+}
+
+impl<'a> PartialEq<XName<'a>> for str {
+    fn eq(&self, other: &XName<'a>) -> bool {
+        other.eq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_str!(XName<'src>, |input| match Name::parse(input)? {
+    Name::XName(xname) => Ok(xname),
+    Name::Iana(name) => anyhow::bail!("expected extension name, found `{name}`"),
+});
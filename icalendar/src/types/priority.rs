@@ -1,6 +1,7 @@
-use std::str::FromStr;
+use std::{cmp::Ordering, str::FromStr};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Priority(u8);
 
 impl Default for Priority {
@@ -18,6 +19,16 @@ impl Priority {
     pub fn value(self) -> u8 {
         self.0
     }
+
+    /// Compare by how important the priorities are, `Greater` meaning `self` is more important.
+    ///
+    /// 1 is the most important and 9 the least, then 0 (undefined) after all the others. The
+    /// derived `Ord` compares the numbers instead.
+    pub fn cmp_importance(&self, other: &Self) -> Ordering {
+        // 0 wraps around to the largest rank
+        let rank = |priority: &Self| priority.0.wrapping_sub(1);
+        rank(other).cmp(&rank(self))
+    }
 }
 
 impl FromStr for Priority {
@@ -27,3 +38,26 @@ impl FromStr for Priority {
         Ok(Self::new(v))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::Priority;
+
+    #[test]
+    fn cmp_importance() {
+        let [one, five, nine, zero] = [1, 5, 9, 0].map(Priority::new);
+        assert_eq!(one.cmp_importance(&five), Ordering::Greater);
+        assert_eq!(five.cmp_importance(&nine), Ordering::Greater);
+        assert_eq!(nine.cmp_importance(&zero), Ordering::Greater);
+        assert_eq!(zero.cmp_importance(&one), Ordering::Less);
+        assert_eq!(five.cmp_importance(&five), Ordering::Equal);
+
+        let mut priorities = vec![zero, nine, one, five];
+        priorities.sort_by(|a, b| b.cmp_importance(a));
+        assert_eq!(priorities, [one, five, nine, zero]);
+        // the derived order is unchanged
+        assert!(zero < one);
+    }
+}
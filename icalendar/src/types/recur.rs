@@ -13,6 +13,10 @@ use crate::{
 
 use super::Recur;
 
+mod expand;
+mod summary;
+pub use expand::Occurrences;
+
 // "SECONDLY" / "MINUTELY" / "HOURLY" / "DAILY" / "WEEKLY" / "MONTHLY" / "YEARLY"
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Freq {
@@ -43,7 +47,7 @@ impl FromStr for Freq {
     }
 }
 
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum End {
     Until(types::DateOrDateTime),
     // TODO use u64?
@@ -62,7 +66,7 @@ impl End {
     }
 
     // Private helper to format `End` in a `Recur`
-    fn fmt(&self) -> Option<impl fmt::Display> {
+    pub(super) fn fmt(&self) -> Option<impl fmt::Display> {
         if matches!(self, End::Forever) {
             return None;
         }
@@ -81,6 +85,20 @@ impl End {
     }
 }
 
+impl fmt::Display for Freq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Secondly => "SECONDLY",
+            Self::Minutely => "MINUTELY",
+            Self::Hourly => "HOURLY",
+            Self::Daily => "DAILY",
+            Self::Weekly => "WEEKLY",
+            Self::Monthly => "MONTHLY",
+            Self::Yearly => "YEARLY",
+        })
+    }
+}
+
 /// Non-zero integer (defaults to 1)
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Interval(u32);
@@ -171,6 +189,15 @@ impl WeekDayNum {
     }
 }
 
+impl fmt::Display for WeekDayNum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(week_num) = self.week_num {
+            write!(f, "{week_num}")?;
+        }
+        fmt::Display::fmt(&self.weekday, f)
+    }
+}
+
 fn ordwk(input: &str) -> Result<(&str, i8), ParserError> {
     let (input, negative) = opt_sign_is_negative(input);
     let (input, week_num) = _1or2_digit_int("ordwk", 1, 53)(input)?;
@@ -212,6 +239,20 @@ impl WeekDay {
     }
 }
 
+impl fmt::Display for WeekDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Sunday => "SU",
+            Self::Monday => "MO",
+            Self::Tuesday => "TU",
+            Self::Wednesday => "WE",
+            Self::Thursday => "TH",
+            Self::Friday => "FR",
+            Self::Saturday => "SA",
+        })
+    }
+}
+
 fn monthdaynum(input: &str) -> Result<(&str, i8), ParserError> {
     let (input, negative) = opt_sign_is_negative(input);
     let (input, num) = _1or2_digit_int("month day", 1, 31)(input)?;
@@ -0,0 +1,873 @@
+//! Expanding a [`Recur`] rule into the occurrences it describes
+
+use std::{cmp::Ordering, iter::Take, ops::Range};
+
+use crate::types::{
+    Date, DateOrDateTime, DateTime, Recur, Time, VecOne,
+    recur::{End, Freq, WeekDay, WeekDayNum},
+};
+
+/// Dates after this can't be represented
+const MAX_DATE: Date = Date {
+    full_year: 9999,
+    month: 12,
+    day: 31,
+};
+
+/// Expansion stops after this many periods in a row without an occurrence, so that rules which
+/// can never match (e.g. `FREQ=MINUTELY;BYMONTH=2;BYMONTHDAY=30`) end.
+///
+/// This is well above the longest gap of a rule that does match, such as the 40 years (about
+/// 15,000 days) between Mondays on the 29th of February for a `DAILY` rule.
+const MAX_EMPTY_PERIODS: u32 = 100_000;
+
+impl Recur {
+    /// The occurrences described by this rule, for a component starting at `start` (its
+    /// `DTSTART`).
+    ///
+    /// Occurrences are returned in chronological order, and have the same form (date or
+    /// date-time, UTC or local) as `start`. Occurrences before `start` are skipped, and `start`
+    /// itself is only returned if it matches the rule. A [`Raw`](DateOrDateTime::Raw) `start`
+    /// has no occurrences.
+    ///
+    /// If the rule has neither `COUNT` nor `UNTIL` the iterator is infinite (well, it runs to
+    /// the year 9999), so `collect`ing it will hang. Outside this crate use
+    /// [`occurrences_until`](Self::occurrences_until) or
+    /// [`occurrences_capped`](Self::occurrences_capped), which always end. It does end after
+    /// 100,000 periods (e.g. minutes for `MINUTELY`) in a row without an occurrence, so rules
+    /// that can never match don't search forever.
+    ///
+    /// Ordinal `BYDAY` values (e.g. `-1SU`) count within the month for `MONTHLY` rules and
+    /// `YEARLY` rules with `BYMONTH`, and within the year for other `YEARLY` rules.
+    ///
+    /// `BYSETPOS` picks from the occurrences in each period (e.g. month for `MONTHLY`) before
+    /// those before `start` are skipped.
+    ///
+    /// `BYWEEKNO` numbers weeks as in ISO 8601 but starting on `WKST`. Without `BYDAY` every
+    /// day of the week matches.
+    pub(crate) fn occurrences(&self, start: DateOrDateTime) -> Occurrences<'_> {
+        Occurrences {
+            rule: self,
+            start,
+            period: 0,
+            pending: vec![],
+            count: 0,
+            empty_periods: 0,
+            bound: None,
+            to_utc: None,
+            done: false,
+        }
+    }
+
+    /// The [occurrences](Self::occurrences) up to and including `until`, which is always
+    /// finite.
+    ///
+    /// `until` is compared like the rule's own `UNTIL`, so a UTC `until` is compared with the
+    /// UTC instant of local occurrences if [`with_to_utc`](Occurrences::with_to_utc) is used.
+    pub fn occurrences_until(
+        &self,
+        start: DateOrDateTime,
+        until: DateOrDateTime,
+    ) -> Occurrences<'_> {
+        Occurrences {
+            bound: Some(until),
+            ..self.occurrences(start)
+        }
+    }
+
+    /// The first `max` [occurrences](Self::occurrences), which is always finite.
+    pub fn occurrences_capped(&self, start: DateOrDateTime, max: usize) -> Take<Occurrences<'_>> {
+        self.occurrences(start).take(max)
+    }
+}
+
+/// Iterator over the occurrences of a recurrence rule, see [`Recur::occurrences`].
+pub struct Occurrences<'a> {
+    rule: &'a Recur,
+    start: DateOrDateTime,
+    /// Index of the next period (year for `YEARLY`, month for `MONTHLY`, etc.) to expand
+    period: i64,
+    /// Occurrences in the current period still to be returned, in reverse order
+    pending: Vec<DateOrDateTime>,
+    /// Occurrences returned so far
+    count: u32,
+    /// Periods in a row that had no occurrences
+    empty_periods: u32,
+    /// The last occurrence to return, see [`Recur::occurrences_until`]
+    bound: Option<DateOrDateTime>,
+    /// Converts local occurrences to UTC, to compare them with a UTC `UNTIL`
+    to_utc: Option<Box<dyn Fn(DateTime) -> DateTime + 'a>>,
+    done: bool,
+}
+
+impl<'a> Occurrences<'a> {
+    /// Convert local occurrences to UTC with `to_utc` when comparing them to a UTC `UNTIL`.
+    ///
+    /// The `UNTIL` of a rule starting at a time with a `TZID` is in UTC. Without this, local
+    /// occurrences are compared to it as if they were UTC.
+    pub fn with_to_utc(mut self, to_utc: impl Fn(DateTime) -> DateTime + 'a) -> Self {
+        self.to_utc = Some(Box::new(to_utc));
+        self
+    }
+}
+
+impl Iterator for Occurrences<'_> {
+    type Item = DateOrDateTime;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            if let Some(next) = self.pending.pop() {
+                let past_end = match &self.rule.end {
+                    End::Until(until) => self.is_after(&next, until),
+                    End::Count(count) => self.count >= *count,
+                    End::Forever => false,
+                } || (self.bound.as_ref())
+                    .is_some_and(|bound| self.is_after(&next, bound));
+                if past_end {
+                    self.done = true;
+                    break;
+                }
+                self.count += 1;
+                return Some(next);
+            }
+
+            match self.expand_period() {
+                Some(mut candidates) => {
+                    candidates.retain(|c| c.cmp_chronological(&self.start) != Ordering::Less);
+                    if candidates.is_empty() {
+                        self.empty_periods += 1;
+                        if self.empty_periods >= MAX_EMPTY_PERIODS {
+                            self.done = true;
+                        }
+                    } else {
+                        self.empty_periods = 0;
+                    }
+                    candidates.reverse();
+                    self.pending = candidates;
+                }
+                None => self.done = true,
+            }
+        }
+        None
+    }
+}
+
+impl Occurrences<'_> {
+    /// Whether `occurrence` is after `until`, comparing UTC instants if `until` is UTC and
+    /// there is a way to convert local times
+    fn is_after(&self, occurrence: &DateOrDateTime, until: &DateOrDateTime) -> bool {
+        if let (DateOrDateTime::DateTime(local), DateOrDateTime::DateTime(until)) =
+            (occurrence, until)
+            && until.time.utc
+            && !local.time.utc
+            && let Some(to_utc) = &self.to_utc
+        {
+            return to_utc(*local).to_seconds() > until.to_seconds();
+        }
+        occurrence.cmp_chronological(until) == Ordering::Greater
+    }
+
+    /// All the occurrences in the next period, or `None` if we ran out of representable dates.
+    fn expand_period(&mut self) -> Option<Vec<DateOrDateTime>> {
+        let rule = self.rule;
+        let step = i64::from(rule.interval.map_or(1, |interval| interval.value())) * self.period;
+        self.period += 1;
+
+        let (start_date, start_time) = match self.start {
+            DateOrDateTime::Date(date) => (date, None),
+            DateOrDateTime::DateTime(DateTime { date, time }) => (date, Some(time)),
+            // can't be interpreted
+            DateOrDateTime::Raw(_) => return None,
+        };
+
+        // The days covered by this period, and for frequencies below daily the time as well.
+        let (days, period_time): (Range<i64>, Option<Time>) = match (rule.freq, start_time) {
+            (Freq::Yearly, _) => {
+                let year = u16::try_from(i64::from(start_date.full_year) + step).ok()?;
+                let first = Date {
+                    full_year: year,
+                    month: 1,
+                    day: 1,
+                };
+                let first = first.to_days();
+                (first..first + i64::from(Date::days_in_year(year)), None)
+            }
+            (Freq::Monthly, _) => {
+                let month =
+                    i64::from(start_date.full_year) * 12 + i64::from(start_date.month) - 1 + step;
+                let year = u16::try_from(month.div_euclid(12)).ok()?;
+                // `as`: value is 1..=12
+                let month = (month.rem_euclid(12) + 1) as u8;
+                let first = Date {
+                    full_year: year,
+                    month,
+                    day: 1,
+                };
+                let first = first.to_days();
+                (
+                    first..first + i64::from(Date::days_in_month(year, month)),
+                    None,
+                )
+            }
+            (Freq::Weekly, _) => {
+                let week_start = rule.week_start.unwrap_or_default().0;
+                let offset = (start_date.weekday() as i64 - week_start as i64).rem_euclid(7);
+                let first = start_date.to_days() - offset + 7 * step;
+                (first..first + 7, None)
+            }
+            // Sub-daily frequencies don't make sense for dates, treat them as daily.
+            (Freq::Daily, _) | (_, None) => {
+                let day = start_date.to_days() + step;
+                (day..day + 1, None)
+            }
+            (Freq::Hourly | Freq::Minutely | Freq::Secondly, Some(time)) => {
+                let unit = match rule.freq {
+                    Freq::Hourly => 3600,
+                    Freq::Minutely => 60,
+                    _ => 1,
+                };
+                let start = DateTime {
+                    date: start_date,
+                    time,
+                };
+                let current = DateTime::from_seconds(start.to_seconds() + step * unit, time.utc);
+                let day = current.date.to_days();
+                (day..day + 1, Some(current.time))
+            }
+        };
+        if days.start > MAX_DATE.to_days() {
+            return None;
+        }
+        if let (Some(period_time), Some(start_time)) = (period_time, start_time) {
+            // Sub-daily periods that can't match because of a `BY*` rule for a larger unit are
+            // skipped a day, hour or minute at a time, rather than one period at a time
+            let date = Date::from_days(days.start);
+            let current = DateTime {
+                date,
+                time: period_time,
+            }
+            .to_seconds();
+            let misses = |by: Option<&VecOne<u8>>, value: u8| {
+                by.is_some_and(|by| !by.into_iter().any(|v| *v == value))
+            };
+            let skip_to = if !self.date_matches(date, start_date) {
+                Some(date.to_days() * 86_400 + 86_400)
+            } else if rule.freq < Freq::Hourly
+                && misses(rule.by_hour.as_ref().map(|by| &by.0), period_time.hour)
+            {
+                Some(current - current.rem_euclid(3600) + 3600)
+            } else if rule.freq < Freq::Minutely
+                && misses(rule.by_minute.as_ref().map(|by| &by.0), period_time.minute)
+            {
+                Some(current - current.rem_euclid(60) + 60)
+            } else {
+                None
+            };
+            if let Some(skip_to) = skip_to {
+                let start = DateTime {
+                    date: start_date,
+                    time: start_time,
+                }
+                .to_seconds();
+                let unit = match rule.freq {
+                    Freq::Hourly => 3600,
+                    Freq::Minutely => 60,
+                    _ => 1,
+                } * i64::from(rule.interval.map_or(1, |interval| interval.value()));
+                // the first period at or after `skip_to`
+                let period = (skip_to - start + unit - 1).div_euclid(unit);
+                self.period = self.period.max(period);
+                return Some(vec![]);
+            }
+        }
+
+        let dates = days
+            .map(Date::from_days)
+            .filter(|date| *date <= MAX_DATE && self.date_matches(*date, start_date));
+
+        let Some(start_time) = start_time else {
+            return Some(self.select_set_pos(dates.map(DateOrDateTime::Date).collect()));
+        };
+
+        let hours = time_values(
+            rule.by_hour.as_ref().map(|by| &by.0),
+            period_time
+                .filter(|_| rule.freq <= Freq::Hourly)
+                .map(|t| t.hour),
+            start_time.hour,
+        );
+        let minutes = time_values(
+            rule.by_minute.as_ref().map(|by| &by.0),
+            period_time
+                .filter(|_| rule.freq <= Freq::Minutely)
+                .map(|t| t.minute),
+            start_time.minute,
+        );
+        let seconds = time_values(
+            rule.by_second.as_ref().map(|by| &by.0),
+            period_time
+                .filter(|_| rule.freq <= Freq::Secondly)
+                .map(|t| t.second),
+            start_time.second,
+        );
+
+        let mut output = vec![];
+        for date in dates {
+            for &hour in &hours {
+                for &minute in &minutes {
+                    for &second in &seconds {
+                        output.push(DateOrDateTime::DateTime(DateTime {
+                            date,
+                            time: Time {
+                                hour,
+                                minute,
+                                second,
+                                utc: start_time.utc,
+                            },
+                        }));
+                    }
+                }
+            }
+        }
+        Some(self.select_set_pos(output))
+    }
+
+    /// Apply `BYSETPOS` to the (chronological) candidates of a period
+    fn select_set_pos(&self, candidates: Vec<DateOrDateTime>) -> Vec<DateOrDateTime> {
+        let Some(by_set_pos) = &self.rule.by_set_pos else {
+            return candidates;
+        };
+        let len = candidates.len();
+        let mut positions: Vec<_> = by_set_pos
+            .0
+            .into_iter()
+            .filter_map(|pos| resolve_negative(*pos, len))
+            .collect();
+        positions.sort();
+        positions.dedup();
+        positions
+            .into_iter()
+            .map(|pos| candidates[pos - 1].clone())
+            .collect()
+    }
+
+    /// Whether `date` satisfies the `BY*` rules that apply to dates, including those implied by
+    /// the start date.
+    fn date_matches(&self, date: Date, start_date: Date) -> bool {
+        let rule = self.rule;
+
+        if let Some(by_month) = &rule.by_month
+            && !by_month.0.into_iter().any(|month| *month == date.month)
+        {
+            return false;
+        }
+        if let Some(by_year_day) = &rule.by_year_day {
+            let days_in_year = Date::days_in_year(date.full_year);
+            let day_of_year = date.day_of_year();
+            if !by_year_day.0.into_iter().any(|day| {
+                resolve_negative(*day, usize::from(days_in_year)) == Some(usize::from(day_of_year))
+            }) {
+                return false;
+            }
+        }
+        if let Some(by_month_day) = &rule.by_month_day {
+            let days_in_month = Date::days_in_month(date.full_year, date.month);
+            if !by_month_day.0.into_iter().any(|day| {
+                resolve_negative(i16::from(*day), usize::from(days_in_month))
+                    == Some(usize::from(date.day))
+            }) {
+                return false;
+            }
+        }
+        if let Some(by_week_no) = &rule.by_week_no {
+            let (week, weeks) = week_number(date, rule.week_start.unwrap_or_default().0);
+            if !by_week_no.0.into_iter().any(|n| {
+                resolve_negative(i16::from(*n), usize::from(weeks)) == Some(usize::from(week))
+            }) {
+                return false;
+            }
+        }
+        if let Some(by_week_day) = &rule.by_week_day
+            && !by_week_day
+                .0
+                .into_iter()
+                .any(|day| self.week_day_matches(date, day))
+        {
+            return false;
+        }
+
+        // Fill in rules implied by the start date
+        let day_rules = rule.by_year_day.is_some()
+            || rule.by_month_day.is_some()
+            || rule.by_week_day.is_some()
+            || rule.by_week_no.is_some();
+        match rule.freq {
+            Freq::Yearly if !day_rules => {
+                (rule.by_month.is_some() || date.month == start_date.month)
+                    && date.day == start_date.day
+            }
+            Freq::Monthly if !day_rules => date.day == start_date.day,
+            Freq::Weekly if rule.by_week_day.is_none() => date.weekday() == start_date.weekday(),
+            _ => true,
+        }
+    }
+
+    fn week_day_matches(&self, date: Date, day: &WeekDayNum) -> bool {
+        if date.weekday() != day.weekday {
+            return false;
+        }
+        let Some(week_num) = day.week_num else {
+            return true;
+        };
+        match self.rule.freq {
+            Freq::Monthly => nth_in_month(date, week_num),
+            Freq::Yearly if self.rule.by_month.is_some() => nth_in_month(date, week_num),
+            Freq::Yearly => nth_in_year(date, week_num),
+            // ordinals are only allowed in MONTHLY and YEARLY rules.
+            _ => true,
+        }
+    }
+}
+
+/// Whether `date` is the `n`th of its weekday in its month (counting from the end if negative)
+fn nth_in_month(date: Date, n: i8) -> bool {
+    let days_in_month = Date::days_in_month(date.full_year, date.month);
+    // `as`: values are <= 5
+    let from_start = ((date.day - 1) / 7 + 1) as i8;
+    let from_end = -(((days_in_month - date.day) / 7 + 1) as i8);
+    n == from_start || n == from_end
+}
+
+/// Whether `date` is the `n`th of its weekday in its year (counting from the end if negative)
+fn nth_in_year(date: Date, n: i8) -> bool {
+    let days_in_year = Date::days_in_year(date.full_year);
+    let day = date.day_of_year();
+    // `as`: values are <= 53
+    let from_start = ((day - 1) / 7 + 1) as i8;
+    let from_end = -(((days_in_year - day) / 7 + 1) as i8);
+    n == from_start || n == from_end
+}
+
+/// The number of the week containing `date` with weeks starting on `week_start`, and how many
+/// weeks its year has.
+///
+/// As in RFC 5545, week 1 is the first with at least 4 days in the year, so the first days of
+/// January can be in the last week of the year before, and the last days of December in week 1
+/// of the year after.
+fn week_number(date: Date, week_start: WeekDay) -> (u16, u16) {
+    // days from the start of the week to `day`
+    let offset = |day: Date| (day.weekday() as i64 - week_start as i64).rem_euclid(7);
+    let first_week = |full_year: u16| {
+        let first_day = Date {
+            full_year,
+            month: 1,
+            day: 1,
+        };
+        let start = first_day.to_days() - offset(first_day);
+        // the week with January 1st is week 1 if at least 4 of its days are in January
+        if offset(first_day) <= 3 {
+            start
+        } else {
+            start + 7
+        }
+    };
+    let week = date.to_days() - offset(date);
+    // a week belongs to the year its 4th day is in
+    let year = Date::from_days(week + 3).full_year;
+    let first = first_week(year);
+    let next = first_week(year + 1);
+    // `as`: there are at most 53 weeks in a year
+    (((week - first) / 7 + 1) as u16, ((next - first) / 7) as u16)
+}
+
+/// Convert a 1-based index that counts from the end if negative into a 1-based index from the
+/// start
+fn resolve_negative(index: i16, len: usize) -> Option<usize> {
+    let index = if index < 0 {
+        len.checked_sub(usize::from(index.unsigned_abs()))? + 1
+    } else {
+        usize::from(index.unsigned_abs())
+    };
+    (1..=len).contains(&index).then_some(index)
+}
+
+/// The values of a time component (hour, minute or second).
+///
+/// If the frequency is smaller than the component, it is fixed by the period and the `BY*` rule
+/// filters it. Otherwise the rule expands it, defaulting to the start time.
+fn time_values(by: Option<&VecOne<u8>>, from_period: Option<u8>, default: u8) -> Vec<u8> {
+    match (from_period, by) {
+        (Some(value), Some(by)) if !by.into_iter().any(|v| *v == value) => vec![],
+        (Some(value), _) => vec![value],
+        (None, Some(by)) => {
+            let mut values: Vec<_> = by.into_iter().copied().collect();
+            values.sort();
+            values.dedup();
+            values
+        }
+        (None, None) => vec![default],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Date, DateOrDateTime, DateTime, Recur, Time};
+
+    fn date(full_year: u16, month: u8, day: u8) -> DateOrDateTime {
+        DateOrDateTime::Date(Date {
+            full_year,
+            month,
+            day,
+        })
+    }
+
+    fn expand(rule: &str, start: DateOrDateTime, max: usize) -> Vec<DateOrDateTime> {
+        let rule = rule.parse::<Recur>().unwrap();
+        rule.occurrences_capped(start, max).collect()
+    }
+
+    #[test]
+    fn capped_forever() {
+        let rule = "FREQ=DAILY".parse::<Recur>().unwrap();
+        let occurrences: Vec<_> = rule.occurrences_capped(date(2024, 1, 1), 1000).collect();
+        assert_eq!(occurrences.len(), 1000);
+        assert_eq!(occurrences[999], date(2026, 9, 26));
+
+        // a count below the cap still ends the rule
+        assert_eq!(
+            expand("FREQ=DAILY;COUNT=3", date(2024, 1, 1), 1000).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn until_bound() {
+        let rule = "FREQ=DAILY".parse::<Recur>().unwrap();
+        let occurrences: Vec<_> = rule
+            .occurrences_until(date(2024, 1, 1), date(2024, 1, 3))
+            .collect();
+        assert_eq!(
+            occurrences,
+            [date(2024, 1, 1), date(2024, 1, 2), date(2024, 1, 3)]
+        );
+
+        // the rule's own end still applies
+        let rule = "FREQ=DAILY;COUNT=2".parse::<Recur>().unwrap();
+        assert_eq!(
+            rule.occurrences_until(date(2024, 1, 1), date(2024, 1, 3))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn daily_count() {
+        let occurrences = expand("FREQ=DAILY;COUNT=3", date(2024, 2, 28), 10);
+        assert_eq!(
+            occurrences,
+            [date(2024, 2, 28), date(2024, 2, 29), date(2024, 3, 1)]
+        );
+    }
+
+    #[test]
+    fn weekly_by_day_until() {
+        // 2024-01-01 is a Monday
+        let occurrences = expand(
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;UNTIL=20240117",
+            date(2024, 1, 1),
+            10,
+        );
+        assert_eq!(
+            occurrences,
+            [
+                date(2024, 1, 1),
+                date(2024, 1, 3),
+                date(2024, 1, 15),
+                date(2024, 1, 17)
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_skips_short_months() {
+        let occurrences = expand("FREQ=MONTHLY", date(2024, 1, 31), 3);
+        assert_eq!(
+            occurrences,
+            [date(2024, 1, 31), date(2024, 3, 31), date(2024, 5, 31)]
+        );
+
+        let occurrences = expand("FREQ=MONTHLY;BYMONTHDAY=-1", date(2024, 1, 31), 3);
+        assert_eq!(
+            occurrences,
+            [date(2024, 1, 31), date(2024, 2, 29), date(2024, 3, 31)]
+        );
+    }
+
+    #[test]
+    fn yearly_by_month_ordinal_day() {
+        let start = DateOrDateTime::DateTime(DateTime {
+            date: Date {
+                full_year: 2007,
+                month: 3,
+                day: 11,
+            },
+            time: Time {
+                hour: 2,
+                minute: 0,
+                second: 0,
+                utc: false,
+            },
+        });
+        let occurrences = expand("FREQ=YEARLY;BYMONTH=3;BYDAY=2SU", start, 3);
+        let dates: Vec<_> = occurrences.iter().map(|o| o.date()).collect();
+        assert_eq!(
+            dates,
+            [date(2007, 3, 11), date(2008, 3, 9), date(2009, 3, 8)].map(|d| d.date())
+        );
+    }
+
+    #[test]
+    fn monthly_ordinal_day() {
+        // January 2024 has 4 Fridays, March has 5
+        let occurrences = expand("FREQ=MONTHLY;BYDAY=-1FR", date(2024, 1, 1), 3);
+        assert_eq!(
+            occurrences,
+            [date(2024, 1, 26), date(2024, 2, 23), date(2024, 3, 29)]
+        );
+
+        let occurrences = expand("FREQ=MONTHLY;BYDAY=2MO", date(2024, 1, 1), 3);
+        assert_eq!(
+            occurrences,
+            [date(2024, 1, 8), date(2024, 2, 12), date(2024, 3, 11)]
+        );
+
+        // months without a 5th Monday are skipped
+        let occurrences = expand("FREQ=MONTHLY;BYDAY=5MO", date(2024, 1, 1), 3);
+        assert_eq!(
+            occurrences,
+            [date(2024, 1, 29), date(2024, 4, 29), date(2024, 7, 29)]
+        );
+    }
+
+    #[test]
+    fn yearly_ordinal_day() {
+        // from RFC 5545: the 20th Monday of the year
+        let occurrences = expand("FREQ=YEARLY;BYDAY=20MO", date(1997, 5, 19), 3);
+        assert_eq!(
+            occurrences,
+            [date(1997, 5, 19), date(1998, 5, 18), date(1999, 5, 17)]
+        );
+
+        // 2024 is a leap year ending on a Tuesday
+        let occurrences = expand("FREQ=YEARLY;BYDAY=-1TU,1TU", date(2024, 1, 1), 3);
+        assert_eq!(
+            occurrences,
+            [date(2024, 1, 2), date(2024, 12, 31), date(2025, 1, 7)]
+        );
+    }
+
+    #[test]
+    fn by_set_pos() {
+        // the last weekday of the month
+        let occurrences = expand(
+            "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+            date(2024, 1, 1),
+            4,
+        );
+        assert_eq!(
+            occurrences,
+            [
+                date(2024, 1, 31),
+                date(2024, 2, 29),
+                date(2024, 3, 29),
+                date(2024, 4, 30)
+            ]
+        );
+
+        // from RFC 5545: the 3rd instance of a Tuesday, Wednesday or Thursday, for 3 months
+        let start = DateOrDateTime::DateTime(DateTime {
+            date: Date {
+                full_year: 1997,
+                month: 9,
+                day: 4,
+            },
+            time: Time {
+                hour: 9,
+                minute: 0,
+                second: 0,
+                utc: false,
+            },
+        });
+        let occurrences: Vec<_> =
+            expand("FREQ=MONTHLY;COUNT=3;BYDAY=TU,WE,TH;BYSETPOS=3", start, 10)
+                .into_iter()
+                .map(|occurrence| occurrence.date().unwrap())
+                .collect();
+        let days = |dates: [(u8, u8); 3]| {
+            dates.map(|(month, day)| Date {
+                full_year: 1997,
+                month,
+                day,
+            })
+        };
+        assert_eq!(occurrences, days([(9, 4), (10, 7), (11, 6)]));
+
+        // positions past the end of a period are skipped
+        let occurrences = expand("FREQ=MONTHLY;BYDAY=MO;BYSETPOS=1,5", date(2024, 1, 1), 3);
+        assert_eq!(
+            occurrences,
+            [date(2024, 1, 1), date(2024, 1, 29), date(2024, 2, 5)]
+        );
+
+        // a period can have more than 2^16 candidates: every second of the day
+        let list = |n: u8| {
+            (0..n)
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let rule = format!(
+            "FREQ=DAILY;BYHOUR={};BYMINUTE={};BYSECOND={};BYSETPOS=-1",
+            list(24),
+            list(60),
+            list(60)
+        );
+        let start = DateOrDateTime::DateTime(DateTime {
+            date: Date {
+                full_year: 2024,
+                month: 1,
+                day: 1,
+            },
+            time: Time {
+                hour: 0,
+                minute: 0,
+                second: 0,
+                utc: true,
+            },
+        });
+        let occurrences = expand(&rule, start, 1);
+        let DateOrDateTime::DateTime(last) = occurrences[0] else {
+            panic!("expected a date-time");
+        };
+        assert_eq!(
+            (last.time.hour, last.time.minute, last.time.second),
+            (23, 59, 59)
+        );
+    }
+
+    #[test]
+    fn by_week_no() {
+        // from RFC 5545: Monday of week number 20
+        let occurrences = expand("FREQ=YEARLY;BYWEEKNO=20;BYDAY=MO", date(1997, 5, 12), 3);
+        assert_eq!(
+            occurrences,
+            [date(1997, 5, 12), date(1998, 5, 11), date(1999, 5, 17)]
+        );
+
+        // every day of the week; week 1 of 2025 starts on 2024-12-30
+        let occurrences = expand("FREQ=YEARLY;BYWEEKNO=1", date(2024, 12, 1), 3);
+        assert_eq!(
+            occurrences,
+            [date(2024, 12, 30), date(2024, 12, 31), date(2025, 1, 1)]
+        );
+
+        // 2020 has 53 weeks, 2021 has 52
+        let occurrences = expand("FREQ=YEARLY;BYWEEKNO=-1;BYDAY=TH", date(2020, 1, 1), 2);
+        assert_eq!(occurrences, [date(2020, 12, 31), date(2021, 12, 30)]);
+
+        // weeks starting on Sunday
+        assert_eq!(
+            expand(
+                "FREQ=YEARLY;BYWEEKNO=1;BYDAY=SU;WKST=SU",
+                date(2024, 12, 1),
+                2
+            ),
+            [date(2024, 12, 29), date(2026, 1, 4)]
+        );
+    }
+
+    #[test]
+    fn until_utc() {
+        let start = DateOrDateTime::DateTime(DateTime {
+            date: Date {
+                full_year: 2024,
+                month: 1,
+                day: 1,
+            },
+            time: Time {
+                hour: 9,
+                minute: 0,
+                second: 0,
+                utc: false,
+            },
+        });
+        // 09:00 local is 08:00 UTC, so the third occurrence is at UNTIL
+        let rule = "FREQ=DAILY;UNTIL=20240103T080000Z"
+            .parse::<Recur>()
+            .unwrap();
+        let occurrences: Vec<_> = rule
+            .occurrences(start.clone())
+            .with_to_utc(|local| DateTime::from_seconds(local.to_seconds() - 3600, true))
+            .collect();
+        assert_eq!(occurrences.len(), 3);
+        // without a timezone, local times are compared as if they were UTC
+        assert_eq!(rule.occurrences(start).count(), 2);
+    }
+
+    #[test]
+    fn never_matches() {
+        let start = DateOrDateTime::DateTime(DateTime {
+            date: Date {
+                full_year: 2024,
+                month: 1,
+                day: 1,
+            },
+            time: Time {
+                hour: 0,
+                minute: 0,
+                second: 0,
+                utc: true,
+            },
+        });
+        // there is no 30th of February
+        for rule in [
+            "FREQ=SECONDLY;BYMONTH=2;BYMONTHDAY=30",
+            "FREQ=MINUTELY;BYMONTH=2;BYMONTHDAY=30",
+            "FREQ=DAILY;BYMONTH=2;BYMONTHDAY=30",
+        ] {
+            assert_eq!(expand(rule, start.clone(), 1), [], "{rule}");
+        }
+
+        // skipping ahead still finds rare matches: the next Monday the 29th of February
+        let occurrences = expand(
+            "FREQ=SECONDLY;BYMONTH=2;BYMONTHDAY=29;BYDAY=MO;BYHOUR=9;BYMINUTE=30;BYSECOND=15",
+            start,
+            1,
+        );
+        let formatted: Vec<_> = occurrences.iter().map(|o| o.to_string()).collect();
+        assert_eq!(formatted, ["20440229T093015Z"]);
+    }
+
+    #[test]
+    fn hourly_by_minute() {
+        let start = DateOrDateTime::DateTime(DateTime {
+            date: Date {
+                full_year: 2024,
+                month: 12,
+                day: 31,
+            },
+            time: Time {
+                hour: 23,
+                minute: 0,
+                second: 0,
+                utc: true,
+            },
+        });
+        let occurrences = expand("FREQ=HOURLY;BYMINUTE=0,30", start, 3);
+        let formatted: Vec<_> = occurrences.iter().map(|o| o.to_string()).collect();
+        assert_eq!(
+            formatted,
+            ["20241231T230000Z", "20241231T233000Z", "20250101T000000Z"]
+        );
+    }
+}
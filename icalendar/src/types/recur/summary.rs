@@ -0,0 +1,164 @@
+//! Describing a [`Recur`] rule in English
+
+use std::fmt::Write;
+
+use crate::types::{
+    Recur,
+    recur::{End, Freq, WeekDay, WeekDayNum},
+};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+impl Recur {
+    /// Whether this rule repeats forever (has neither `COUNT` nor `UNTIL`)
+    pub fn is_infinite(&self) -> bool {
+        matches!(self.end, End::Forever)
+    }
+
+    /// A short description of this rule for display, e.g. "Every 2 weeks on Mon, Wed".
+    ///
+    /// The frequency, interval, `BYDAY`, `BYMONTHDAY`, `BYMONTH` and end are described. Other
+    /// rule parts are left out.
+    pub fn human_summary(&self) -> String {
+        let unit = match self.freq {
+            Freq::Secondly => "second",
+            Freq::Minutely => "minute",
+            Freq::Hourly => "hour",
+            Freq::Daily => "day",
+            Freq::Weekly => "week",
+            Freq::Monthly => "month",
+            Freq::Yearly => "year",
+        };
+        let mut output = match self.interval.map_or(1, |interval| interval.value()) {
+            1 => format!("Every {unit}"),
+            interval => format!("Every {interval} {unit}s"),
+        };
+
+        if let Some(by) = &self.by_week_day {
+            let days: Vec<_> = by.0.into_iter().map(week_day_num).collect();
+            write!(output, " on {}", days.join(", ")).unwrap();
+        }
+        if let Some(by) = &self.by_month_day {
+            let days: Vec<_> = by.0.into_iter().map(|day| month_day(*day)).collect();
+            write!(output, " on the {}", days.join(", ")).unwrap();
+        }
+        if let Some(by) = &self.by_month {
+            let months: Vec<_> =
+                by.0.into_iter()
+                    .map(|month| MONTHS[usize::from(*month) - 1])
+                    .collect();
+            write!(output, " in {}", months.join(", ")).unwrap();
+        }
+
+        match &self.end {
+            End::Forever => (),
+            End::Count(1) => output.push_str(", once"),
+            End::Count(count) => write!(output, ", {count} times").unwrap(),
+            End::Until(until) => match until.date() {
+                Some(date) => write!(
+                    output,
+                    ", until {:04}-{:02}-{:02}",
+                    date.full_year, date.month, date.day
+                )
+                .unwrap(),
+                None => write!(output, ", until {until}").unwrap(),
+            },
+        }
+        output
+    }
+}
+
+/// e.g. "Mon", "the 2nd Tue" or "the last Fri"
+fn week_day_num(day: &WeekDayNum) -> String {
+    let name = match day.weekday {
+        WeekDay::Sunday => "Sun",
+        WeekDay::Monday => "Mon",
+        WeekDay::Tuesday => "Tue",
+        WeekDay::Wednesday => "Wed",
+        WeekDay::Thursday => "Thu",
+        WeekDay::Friday => "Fri",
+        WeekDay::Saturday => "Sat",
+    };
+    match day.week_num {
+        None => name.to_owned(),
+        Some(num) => format!("the {} {name}", position(num.into())),
+    }
+}
+
+/// e.g. "15th" or "last day"
+fn month_day(day: i8) -> String {
+    if day < 0 {
+        format!("{} day", position(day.into()))
+    } else {
+        position(day.into())
+    }
+}
+
+/// An ordinal counting from the start (positive) or end (negative), e.g. "2nd" or "last"
+fn position(num: i16) -> String {
+    match num {
+        -1 => "last".to_owned(),
+        ..0 => format!("{} to last", ordinal(num.unsigned_abs())),
+        _ => ordinal(num.unsigned_abs()),
+    }
+}
+
+fn ordinal(num: u16) -> String {
+    let suffix = match (num % 10, num % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{num}{suffix}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Recur;
+
+    fn summary(rule: &str) -> String {
+        rule.parse::<Recur>().unwrap().human_summary()
+    }
+
+    #[test]
+    fn human_summary() {
+        assert_eq!(
+            summary("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE"),
+            "Every 2 weeks on Mon, Wed"
+        );
+        assert_eq!(summary("FREQ=DAILY;COUNT=5"), "Every day, 5 times");
+        assert_eq!(summary("FREQ=HOURLY;COUNT=1"), "Every hour, once");
+        assert_eq!(
+            summary("FREQ=MONTHLY;BYDAY=-1FR;UNTIL=20241231"),
+            "Every month on the last Fri, until 2024-12-31"
+        );
+        assert_eq!(
+            summary("FREQ=MONTHLY;BYDAY=2TU,-2TH"),
+            "Every month on the 2nd Tue, the 2nd to last Thu"
+        );
+        assert_eq!(
+            summary("FREQ=YEARLY;BYMONTH=3,11;BYMONTHDAY=1,-1;UNTIL=20300101T000000Z"),
+            "Every year on the 1st, last day in Mar, Nov, until 2030-01-01"
+        );
+        assert_eq!(
+            summary("FREQ=MONTHLY;BYMONTHDAY=11,22,23"),
+            "Every month on the 11th, 22nd, 23rd"
+        );
+    }
+
+    #[test]
+    fn is_infinite() {
+        assert!("FREQ=DAILY".parse::<Recur>().unwrap().is_infinite());
+        assert!(!"FREQ=DAILY;COUNT=3".parse::<Recur>().unwrap().is_infinite());
+        assert!(
+            !"FREQ=DAILY;UNTIL=20240101"
+                .parse::<Recur>()
+                .unwrap()
+                .is_infinite()
+        );
+    }
+}
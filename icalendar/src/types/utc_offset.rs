@@ -0,0 +1,83 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::bail;
+
+use crate::types::{time_hour, time_minute, time_second};
+
+/// An offset from UTC, e.g. `-0500`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UtcOffset {
+    pub negative: bool,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl UtcOffset {
+    /// The offset in seconds, negative for offsets west of UTC
+    pub fn as_seconds(self) -> i64 {
+        let seconds =
+            i64::from(self.hour) * 3600 + i64::from(self.minute) * 60 + i64::from(self.second);
+        if self.negative { -seconds } else { seconds }
+    }
+
+    /// Inverse of [`UtcOffset::as_seconds`]. Offsets must be less than a day.
+    pub fn from_seconds(seconds: i64) -> Self {
+        let negative = seconds < 0;
+        let seconds = seconds.unsigned_abs();
+        // `as`: offsets are less than a day
+        UtcOffset {
+            negative,
+            hour: (seconds / 3600) as u8,
+            minute: (seconds % 3600 / 60) as u8,
+            second: (seconds % 60) as u8,
+        }
+    }
+}
+
+impl FromStr for UtcOffset {
+    type Err = anyhow::Error;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let Some((sign, input)) = input.split_at_checked(1) else {
+            bail!("expected `+` or `-`");
+        };
+        let negative = match sign {
+            "+" => false,
+            "-" => true,
+            _ => bail!("expected `+` or `-`"),
+        };
+        let (input, hour) = time_hour(input)?;
+        let (input, minute) = time_minute(input)?;
+        let (input, second) = if input.is_empty() {
+            ("", 0)
+        } else {
+            time_second(false, input)?
+        };
+        if !input.is_empty() {
+            bail!("trailing characters");
+        }
+        if negative && hour == 0 && minute == 0 && second == 0 {
+            bail!("`-0000` is not a valid UTC offset");
+        }
+        Ok(UtcOffset {
+            negative,
+            hour,
+            minute,
+            second,
+        })
+    }
+}
+
+impl fmt::Display for UtcOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.negative { '-' } else { '+' };
+        write!(f, "{sign}{:02}{:02}", self.hour, self.minute)?;
+        if self.second != 0 {
+            write!(f, "{:02}", self.second)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_str!(UtcOffset, |input| input.parse());
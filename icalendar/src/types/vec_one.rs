@@ -1,7 +1,8 @@
 use std::{
     borrow::Cow,
-    fmt,
+    fmt, iter,
     ops::{Index, IndexMut},
+    slice,
 };
 
 use anyhow::bail;
@@ -96,6 +97,15 @@ impl<T> VecOne<T> {
     }
 }
 
+impl<'a, T> IntoIterator for &'a VecOne<T> {
+    type Item = &'a T;
+    type IntoIter = iter::Chain<iter::Once<&'a T>, slice::Iter<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        iter::once(&self.first).chain(self.rest.iter())
+    }
+}
+
 impl<T> Extend<T> for VecOne<T> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         self.rest.extend(iter)
@@ -130,6 +140,35 @@ impl<T: fmt::Debug> fmt::Debug for VecOne<T> {
     }
 }
 
+/// Serialized as a plain (non-empty) sequence
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for VecOne<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = serializer.serialize_seq(Some(self.rest.len() + 1))?;
+        seq.serialize_element(&self.first)?;
+        for item in &self.rest {
+            seq.serialize_element(item)?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for VecOne<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut items = Vec::<T>::deserialize(deserializer)?.into_iter();
+        let Some(first) = items.next() else {
+            return Err(serde::de::Error::invalid_length(0, &"at least 1 element"));
+        };
+        Ok(Self {
+            first,
+            rest: items.collect(),
+        })
+    }
+}
+
 // Internal helpers
 impl<'src> VecOne<Cow<'src, str>> {
     pub(crate) fn start_new(&mut self) {
@@ -152,10 +191,8 @@ impl<'src> VecOne<Cow<'src, str>> {
     }
 
     pub(crate) fn push_to_current(&mut self, ch: char) {
-        match self.current() {
-            Cow::Borrowed(_) => unreachable!(),
-            Cow::Owned(string) => string.push(ch),
-        }
+        // values after the first start out as empty borrowed strings
+        self.current().to_mut().push(ch);
     }
 }
 
@@ -0,0 +1,53 @@
+//! Generating `UID`s for new components
+
+use uuid::Uuid;
+
+/// Generates globally unique `UID`s of the form `{uuid}@{domain}`, as recommended by RFC 7986.
+pub struct Uid;
+
+impl Uid {
+    /// A new random `UID`.
+    pub fn generate(domain: &str) -> String {
+        format!("{}@{domain}", Uuid::new_v4())
+    }
+
+    /// A `UID` derived from `seed`: the same seed and domain always give the same `UID`.
+    ///
+    /// Useful for exports that must keep their `UID`s stable, seeded with e.g. a database id.
+    pub fn generate_from(seed: impl AsRef<[u8]>, domain: &str) -> String {
+        let namespace = Uuid::new_v5(&Uuid::NAMESPACE_DNS, domain.as_bytes());
+        format!("{}@{domain}", Uuid::new_v5(&namespace, seed.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Uid;
+
+    fn check_format(uid: &str) {
+        let (uuid, domain) = uid.split_once('@').unwrap();
+        assert_eq!(domain, "example.com");
+        assert_eq!(uuid.len(), 36);
+        assert!(uuid.chars().all(|ch| ch == '-' || ch.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn generate() {
+        let first = Uid::generate("example.com");
+        let second = Uid::generate("example.com");
+        check_format(&first);
+        check_format(&second);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn generate_from() {
+        let first = Uid::generate_from("event 1", "example.com");
+        check_format(&first);
+        assert_eq!(first, Uid::generate_from("event 1", "example.com"));
+        assert_ne!(first, Uid::generate_from("event 2", "example.com"));
+        // the domain is part of the seed
+        let other_domain = Uid::generate_from("event 1", "example.org");
+        assert_ne!(first.split('@').next(), other_domain.split('@').next());
+    }
+}
@@ -0,0 +1,194 @@
+//! Resolving events to absolute times
+
+use crate::{
+    Calendar, Event, EventEnd, TimeZoneError,
+    params::TimeZoneIdentifier,
+    timezone,
+    types::{DateOrDateTime, DateTime, Time},
+};
+
+/// An event with its start and end in UTC, see [`Calendar::utc_events`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct UtcEvent<'a> {
+    pub uid: &'a str,
+    /// The `SUMMARY` as written
+    pub summary: Option<&'a str>,
+    /// When the event starts and ends, both UTC
+    pub interval: (DateTime, DateTime),
+}
+
+impl Calendar<'_> {
+    /// Every event with a start, with its start and end converted to UTC, ordered by start.
+    ///
+    /// The end is [`Event::effective_end`], so `DURATION` or the default length is used if
+    /// there is no `DTEND`. Times with a `TZID` are converted using the calendar's timezones
+    /// (or the bundled database, with the `tzdb` feature). Floating times and dates are taken
+    /// to be in the calendar's [`default_timezone`](Calendar::default_timezone) if it is
+    /// defined, or else UTC. Recurring events are not expanded: see
+    /// [`EventSeries::instance_events`](crate::EventSeries::instance_events).
+    ///
+    /// Errors if an event refers to a `TZID` that isn't defined.
+    pub fn utc_events(&self) -> Result<Vec<UtcEvent<'_>>, TimeZoneError> {
+        let mut output = vec![];
+        for event in &self.events {
+            let Some(interval) = self.utc_interval(event)? else {
+                continue;
+            };
+            output.push(UtcEvent {
+                uid: &event.uid,
+                summary: event.summary.as_ref().map(|summary| &*summary.text),
+                interval,
+            });
+        }
+        output.sort_by_key(|event| event.interval.0.to_seconds());
+        Ok(output)
+    }
+
+    /// The UTC start and end of `event`, or `None` if it has no start (or its end is out of
+    /// range, or its start or end is [`Raw`](DateOrDateTime::Raw)). See
+    /// [`utc_events`](Self::utc_events).
+    pub fn utc_interval(
+        &self,
+        event: &Event<'_>,
+    ) -> Result<Option<(DateTime, DateTime)>, TimeZoneError> {
+        let (Some(start), Some(end)) = (&event.start, event.effective_end(&self.timezones)) else {
+            return Ok(None);
+        };
+        if matches!(start, DateOrDateTime::Raw(_)) || matches!(end, DateOrDateTime::Raw(_)) {
+            return Ok(None);
+        }
+        // a calculated end is in the timezone of the start
+        let end_timezone_id = match &event.end {
+            Some(EventEnd::DateTime { timezone_id, .. }) => timezone_id.as_ref(),
+            _ => event.start_timezone_id.as_ref(),
+        };
+        Ok(Some((
+            self.to_utc(start.clone(), event.start_timezone_id.as_ref())?,
+            self.to_utc(end, end_timezone_id)?,
+        )))
+    }
+
+    /// Convert `value`, written with `TZID` `timezone_id`, to UTC as for
+    /// [`utc_events`](Self::utc_events). Dates are taken as midnight.
+    ///
+    /// Errors if `timezone_id` isn't defined, or `value` is [`Raw`](DateOrDateTime::Raw).
+    pub fn to_utc(
+        &self,
+        value: DateOrDateTime,
+        timezone_id: Option<&TimeZoneIdentifier<'_>>,
+    ) -> Result<DateTime, TimeZoneError> {
+        let local = match value {
+            DateOrDateTime::Date(date) => DateTime {
+                date,
+                time: Time {
+                    hour: 0,
+                    minute: 0,
+                    second: 0,
+                    utc: false,
+                },
+            },
+            DateOrDateTime::DateTime(date_time) if date_time.time.utc => return Ok(date_time),
+            DateOrDateTime::DateTime(date_time) => date_time,
+            DateOrDateTime::Raw(value) => return Err(TimeZoneError::NotGregorian(value.into())),
+        };
+        let timezone = match timezone_id {
+            Some(id) => match timezone::resolve(&self.timezones, id.id()) {
+                Some(timezone) => Some(timezone),
+                None => return Err(TimeZoneError::Unknown(id.id().to_string())),
+            },
+            None => self
+                .default_timezone
+                .as_deref()
+                .and_then(|id| timezone::resolve(&self.timezones, id)),
+        };
+        Ok(match timezone {
+            Some(timezone) => timezone.to_utc(local),
+            None => DateTime {
+                time: Time {
+                    utc: true,
+                    ..local.time
+                },
+                ..local
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_util, types::DateTime};
+
+    #[test]
+    fn utc_events() {
+        let input = test_util::calendar(
+            "X-WR-TIMEZONE:Fixed\r\n\
+            BEGIN:VTIMEZONE\r\n\
+            TZID:Fixed\r\n\
+            BEGIN:STANDARD\r\n\
+            TZOFFSETFROM:+0100\r\n\
+            TZOFFSETTO:+0100\r\n\
+            DTSTART:19700101T000000\r\n\
+            END:STANDARD\r\n\
+            END:VTIMEZONE\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:utc\r\n\
+            DTSTART:20250701T090000Z\r\n\
+            DURATION:PT1H\r\n\
+            SUMMARY:Stand-up\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:all-day\r\n\
+            DTSTART;VALUE=DATE:20250701\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:zoned\r\n\
+            DTSTART;TZID=Fixed:20250701T090000\r\n\
+            DTEND:20250701T093000Z\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:no-start\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:no-end\r\n\
+            DTSTART;TZID=Fixed:20250701T120000\r\n\
+            END:VEVENT\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let events = calendar.utc_events().unwrap();
+        let intervals: Vec<_> = events
+            .iter()
+            .map(|event| {
+                let (start, end) = event.interval;
+                (event.uid, start.to_string(), end.to_string())
+            })
+            .collect();
+        assert_eq!(
+            intervals,
+            [
+                // midnight to midnight in the default timezone
+                ("all-day", "20250630T230000Z", "20250701T230000Z"),
+                ("zoned", "20250701T080000Z", "20250701T093000Z"),
+                ("utc", "20250701T090000Z", "20250701T100000Z"),
+                ("no-end", "20250701T110000Z", "20250701T110000Z"),
+            ]
+            .map(|(uid, start, end)| (uid, start.to_string(), end.to_string()))
+        );
+        assert_eq!(events[2].summary, Some("Stand-up"));
+
+        // dates and floating times are UTC without a default timezone
+        let input = input.replace("X-WR-TIMEZONE:Fixed\r\n", "");
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let all_day = calendar.utc_events().unwrap().remove(0);
+        assert_eq!(
+            all_day.interval.0,
+            DateTime::parse("20250701T000000Z").unwrap().1
+        );
+
+        let input = input.replace("TZID=Fixed:20250701T120000", "TZID=Nowhere:20250701T120000");
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        assert_eq!(
+            calendar.utc_events().unwrap_err().to_string(),
+            "unknown timezone Nowhere"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Checking calendars have the properties RFC 5545 requires
+
+use std::collections::HashMap;
+
+use crate::{Calendar, ValidationIssue};
+
+impl Calendar<'_> {
+    /// Check every `VEVENT` has the properties RFC 5545 requires.
+    ///
+    /// Parsing only insists on `UID`, so that real-world feeds missing other properties can
+    /// still be read. This checks for `DTSTAMP`, and for `DTSTART` if the calendar has no
+    /// `METHOD`. See [`validate_scheduling`](Self::validate_scheduling) for the extra rules
+    /// of each `METHOD`.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = vec![];
+        for event in &self.events {
+            let mut issue = |message: &str| {
+                issues.push(ValidationIssue {
+                    uid: Some(event.uid.to_string()),
+                    message: message.to_owned(),
+                })
+            };
+            if event.timestamp.is_none() {
+                issue("missing DTSTAMP");
+            }
+            if self.method.is_none() && event.start.is_none() {
+                issue("missing DTSTART, which is required without a METHOD");
+            }
+        }
+        issues
+    }
+
+    /// Every `UID` shared by more than one event, with how many events have it, in order of
+    /// first appearance.
+    ///
+    /// Events with a `RECURRENCE-ID` are overrides of an occurrence of the event with the
+    /// same `UID`, so they aren't counted.
+    pub fn duplicate_uids(&self) -> Vec<(&str, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        // the UIDs in order of first appearance
+        let mut uids = vec![];
+        for event in &self.events {
+            if event.recurrence_id.is_some() {
+                continue;
+            }
+            let count = counts.entry(&event.uid).or_insert_with(|| {
+                uids.push(&*event.uid);
+                0
+            });
+            *count += 1;
+        }
+        uids.into_iter()
+            .map(|uid| (uid, counts[uid]))
+            .filter(|(_, count)| *count > 1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util;
+
+    fn calendar_input(method: &str, event: &str) -> String {
+        test_util::calendar(&format!(
+            "{method}\
+            BEGIN:VEVENT\r\n\
+            UID:event@example.com\r\n\
+            {event}\
+            END:VEVENT\r\n"
+        ))
+    }
+
+    #[test]
+    fn missing_timestamp() {
+        let input = calendar_input("", "DTSTART:20240102T090000Z\r\n");
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let issues = calendar.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].to_string(),
+            "event `event@example.com`: missing DTSTAMP"
+        );
+    }
+
+    #[test]
+    fn missing_start() {
+        let input = calendar_input("", "DTSTAMP:20240101T000000Z\r\n");
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let issues = calendar.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].message,
+            "missing DTSTART, which is required without a METHOD"
+        );
+
+        // scheduling messages can leave it out
+        let input = calendar_input("METHOD:REPLY\r\n", "DTSTAMP:20240101T000000Z\r\n");
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        assert_eq!(calendar.validate(), []);
+    }
+
+    #[test]
+    fn duplicate_uids() {
+        let event =
+            |uid: &str, extra: &str| format!("BEGIN:VEVENT\r\nUID:{uid}\r\n{extra}END:VEVENT\r\n");
+        let input = test_util::calendar(&format!(
+            "{}{}{}{}{}{}{}",
+            event("a", ""),
+            event("b", ""),
+            event("a", ""),
+            event("b", "RECURRENCE-ID:20240102T090000Z\r\n"),
+            event("c", ""),
+            event("a", ""),
+            event("c", ""),
+        ));
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        assert_eq!(calendar.duplicate_uids(), [("a", 3), ("c", 2)]);
+    }
+}
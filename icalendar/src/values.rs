@@ -10,7 +10,7 @@ pub use uriparse::URIError;
 
 use crate::{
     parser::helpers::tag,
-    types::{self, VecOne, time_hour, time_second},
+    types::{self, VecOne},
 };
 
 // BINARY
@@ -61,22 +61,86 @@ pub struct BooleanError(String);
 
 // CAL-ADDRESS
 
-#[derive(Debug)]
-pub struct CalendarUserAddress<'src>(Uri<'src>);
+/// Equality is based on the [normalized](CalendarUserAddress::normalized) form.
+#[derive(Debug, Clone)]
+pub struct CalendarUserAddress<'src> {
+    /// The address as it was written
+    text: Cow<'src, str>,
+    uri: Uri<'src>,
+}
+
+impl CalendarUserAddress<'_> {
+    /// The [normalized](Uri::normalized) address. The domain of `mailto:` addresses is also
+    /// lowercased, but not the local part, which is case sensitive.
+    ///
+    /// The address is borrowed if it is already in its normal form.
+    pub fn normalized(&self) -> Cow<'_, str> {
+        let normalized = self.uri.normalized().to_string();
+        let normalized = match normalized.strip_prefix("mailto:") {
+            Some(rest) => {
+                let end = rest.find(['?', '#']).unwrap_or(rest.len());
+                let (address, suffix) = rest.split_at(end);
+                match address.rsplit_once('@') {
+                    Some((local, domain)) => {
+                        format!("mailto:{local}@{}{suffix}", domain.to_lowercase())
+                    }
+                    None => normalized,
+                }
+            }
+            None => normalized,
+        };
+        if normalized == self.text {
+            Cow::Borrowed(&self.text)
+        } else {
+            Cow::Owned(normalized)
+        }
+    }
+
+    /// The email address of a `mailto:` address as written, or `None` for other schemes.
+    ///
+    /// Nothing is decoded or normalized, see [`normalized`](Self::normalized) for comparing
+    /// addresses.
+    pub fn email(&self) -> Option<&str> {
+        let uri = &self.uri.0;
+        if !uri.scheme().as_str().eq_ignore_ascii_case("mailto") {
+            return None;
+        }
+        // an email address has no `/` so is a single segment
+        match uri.path().segments() {
+            [address] if !address.as_str().is_empty() => Some(address.as_str()),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for CalendarUserAddress<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
 
 impl<'src> TryFrom<Cow<'src, str>> for CalendarUserAddress<'src> {
     type Error = URIError;
     fn try_from(input: Cow<'src, str>) -> Result<Self, Self::Error> {
-        Ok(CalendarUserAddress(Uri::try_from(input)?))
+        let uri = match &input {
+            Cow::Borrowed(input) => Uri::try_from(*input)?,
+            Cow::Owned(input) => Uri(URI::try_from(input.as_str())?.into_owned()),
+        };
+        Ok(CalendarUserAddress { text: input, uri })
     }
 }
 
 impl<'src> fmt::Display for CalendarUserAddress<'src> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(&self.0, f)
+        fmt::Display::fmt(&self.uri, f)
     }
 }
 
+#[cfg(feature = "serde")]
+impl_serde_str!(CalendarUserAddress<'src>, |input| Ok(
+    CalendarUserAddress::try_from(Cow::Owned(input))?
+));
+
 // DATE
 
 pub struct Date {
@@ -318,8 +382,69 @@ impl FromStr for Time {
 
 // URI
 
+#[derive(Clone, PartialEq)]
 pub struct Uri<'src>(uriparse::URI<'src>);
 
+impl Uri<'_> {
+    /// A copy of the URI in a normal form, so that URIs written differently can be compared.
+    ///
+    /// This applies the syntax-based normalization of RFC 3986 section 6.2.2: the scheme and
+    /// host are lowercased, percent-encoded unreserved characters are decoded, the hex digits
+    /// of other percent-encodings are uppercased and `.`/`..` path segments are removed. In
+    /// `mailto:` URIs, percent-encoded characters that are allowed in the address as they are
+    /// (such as `@`) are decoded too, apart from `,` which separates addresses.
+    ///
+    /// Nothing scheme-specific is done otherwise: the case of paths and email addresses is
+    /// kept, default ports are not removed and the query is left as it is.
+    pub fn normalized(&self) -> Uri<'static> {
+        let mut uri = self.0.clone().into_owned();
+        uri.normalize();
+        if uri.scheme().as_str() == "mailto" {
+            let mut decoded = format!("mailto:{}", decode_path_chars(&uri.path().to_string()));
+            if let Some(query) = uri.query() {
+                decoded.push('?');
+                decoded.push_str(query.as_str());
+            }
+            if let Some(fragment) = uri.fragment() {
+                decoded.push('#');
+                decoded.push_str(fragment.as_str());
+            }
+            // only characters allowed in paths are decoded, so this always succeeds
+            if let Ok(decoded) = URI::try_from(decoded.as_str()) {
+                uri = decoded.into_owned();
+            }
+        }
+        Uri(uri)
+    }
+}
+
+/// Decode percent-encoded characters that are valid in a path segment, apart from `,`
+fn decode_path_chars(path: &str) -> String {
+    let mut output = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(idx) = rest.find('%') {
+        output.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+        let decoded = rest
+            .get(1..3)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .map(char::from)
+            .filter(|ch| ch.is_ascii_alphanumeric() || "-._~!$&'()*+;=:@".contains(*ch));
+        match decoded {
+            Some(ch) => {
+                output.push(ch);
+                rest = &rest[3..];
+            }
+            None => {
+                output.push('%');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
 impl<'src> fmt::Debug for Uri<'src> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -349,50 +474,16 @@ impl<'src> fmt::Display for Uri<'src> {
     }
 }
 
-// UTC-OFFSET
-
-pub struct UtcOffset {
-    pub negative: bool,
-    pub hour: u8,
-    pub minute: u8,
-    pub second: u8,
-}
-
-impl FromStr for UtcOffset {
-    type Err = anyhow::Error;
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let Some((sign, input)) = input.split_at_checked(1) else {
-            bail!("expected `+` or `-`");
-        };
-        let negative = match sign {
-            "+" => false,
-            "-" => true,
-            _ => bail!("expected `+` or `-`"),
-        };
-        let (input, hour) = time_hour(input)?;
-        let (input, minute) = time_hour(input)?;
-        let (input, second) = if !input.is_empty() {
-            ("", 0)
-        } else {
-            time_second(false, input)?
-        };
-        if !input.is_empty() {
-            bail!("trailing characters");
-        }
-        Ok(UtcOffset {
-            negative,
-            hour,
-            minute,
-            second,
-        })
-    }
-}
+#[cfg(feature = "serde")]
+impl_serde_str!(Uri<'src>, |input| Ok(Uri::try_from(Cow::Owned(input))?));
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
+
     use crate::types::VecOne;
 
-    use super::Text;
+    use super::{CalendarUserAddress, Text, Uri};
 
     #[test]
     fn text() {
@@ -412,4 +503,65 @@ mod tests {
         assert!(Text::try_from(";").is_err());
         assert!(Text::try_from("\\:").is_err());
     }
+
+    #[test]
+    fn calendar_user_address_normalized() {
+        let bob = CalendarUserAddress::try_from(Cow::Borrowed("mailto:Bob@Example.COM")).unwrap();
+        let bob2 = CalendarUserAddress::try_from(Cow::Borrowed("MAILTO:Bob@example.com")).unwrap();
+        assert_eq!(bob.normalized(), "mailto:Bob@example.com");
+        assert_eq!(bob, bob2);
+        // the local part is case sensitive
+        let bob3 = CalendarUserAddress::try_from(Cow::Borrowed("mailto:bob@example.com")).unwrap();
+        assert_ne!(bob, bob3);
+
+        let alice =
+            CalendarUserAddress::try_from(Cow::Borrowed("mailto:alice@example.com")).unwrap();
+        assert_ne!(bob, alice);
+        assert!(matches!(
+            alice.normalized(),
+            Cow::Borrowed("mailto:alice@example.com")
+        ));
+
+        let web = CalendarUserAddress::try_from(Cow::Borrowed("HTTPS://example.com/Bob")).unwrap();
+        assert_eq!(web.normalized(), "https://example.com/Bob");
+    }
+
+    #[test]
+    fn calendar_user_address_email() {
+        let address = |input| CalendarUserAddress::try_from(Cow::Borrowed(input)).unwrap();
+        assert_eq!(address("mailto:a@b.com").email(), Some("a@b.com"));
+        assert_eq!(
+            address("MAILTO:Jane@Example.com").email(),
+            Some("Jane@Example.com")
+        );
+        assert_eq!(
+            address("mailto:a@b.com?subject=hi").email(),
+            Some("a@b.com")
+        );
+        assert_eq!(address("http://example.com/a@b.com").email(), None);
+        assert_eq!(
+            address("urn:uuid:f81d4fae-7dec-11d0-a765-00a0c91e6bf6").email(),
+            None
+        );
+    }
+
+    #[test]
+    fn uri_normalized() {
+        let encoded = Uri::try_from("MAILTO:Jane%40ex.com").unwrap();
+        let plain = Uri::try_from("mailto:Jane@ex.com").unwrap();
+        assert_ne!(encoded, plain);
+        assert_eq!(encoded.normalized().to_string(), "mailto:Jane@ex.com");
+        assert_eq!(encoded.normalized(), plain.normalized());
+
+        // `,` separates addresses so stays encoded
+        let list = Uri::try_from("mailto:a%2cb@ex.com").unwrap();
+        assert_eq!(list.normalized().to_string(), "mailto:a%2Cb@ex.com");
+
+        let web = Uri::try_from("HTTPS://Example.COM/a/./%7Ebob/../Bob%2f").unwrap();
+        assert_eq!(web.normalized().to_string(), "https://example.com/a/Bob%2F");
+
+        let jane = CalendarUserAddress::try_from(Cow::Borrowed("mailto:Jane%40ex.com")).unwrap();
+        let jane2 = CalendarUserAddress::try_from(Cow::Borrowed("mailto:Jane@EX.com")).unwrap();
+        assert_eq!(jane, jane2);
+    }
 }
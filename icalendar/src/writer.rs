@@ -0,0 +1,1019 @@
+//! Writing calendars in iCalendar format
+
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+    iter,
+};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+
+use crate::{
+    Alarm, AlarmTrigger, AnnotatedText, Attachment, Attendee, CalScale, Calendar, CalendarBuilder,
+    Categories, Class, Comment, Conference, Contact, Event, EventEnd, ExtensionProperty, Journal,
+    Observance, ObservanceKind, Organizer, RelatedTo, Result, StructuredLocation, TimeZone,
+    TimeZoneError, Todo, names,
+    params::{
+        AlarmTriggerRelationship, CommonName, Delegatees, Delegators, GroupOrListMember,
+        ParticipationRole, ParticipationStatus, RelationshipType, RsvpExpectation, SentBy,
+        TimeZoneIdentifier, fmt_param_value,
+    },
+    types::{Data, DateOrDateTime, Name, VecOne},
+    values::CalendarUserAddress,
+};
+
+/// Content lines longer than this (in octets, excluding the line break) are folded
+const MAX_LINE_OCTETS: usize = 75;
+
+/// The order `VEVENT` properties are written in. Properties not listed here (extensions)
+/// come after, sorted by name.
+const EVENT_PROPERTY_ORDER: [Name<'static>; 29] = [
+    names::UID,
+    names::DTSTAMP,
+    names::DTSTART,
+    names::DTEND,
+    names::DURATION,
+    names::RECURRENCE_ID,
+    names::SUMMARY,
+    names::DESCRIPTION,
+    names::LOCATION,
+    names::GEO,
+    names::CREATED,
+    names::LAST_MODIFIED,
+    names::SEQUENCE,
+    names::PRIORITY,
+    names::CLASS,
+    names::STATUS,
+    names::TRANSP,
+    names::COLOR,
+    names::URL,
+    names::ORGANIZER,
+    names::ATTENDEE,
+    names::RRULE,
+    names::RDATE,
+    names::EXDATE,
+    names::CATEGORIES,
+    names::COMMENT,
+    names::CONTACT,
+    names::CONFERENCE,
+    names::ATTACH,
+];
+
+impl Calendar<'_> {
+    /// Write the calendar in iCalendar format, folding long lines.
+    ///
+    /// Properties are written in a fixed order whatever order they were parsed in, so the
+    /// output of equal calendars is identical. Properties with the same name (e.g.
+    /// `ATTENDEE`s) keep their relative order.
+    pub fn write(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        let mut out = FoldingWriter::new(out);
+        for part in self.parts() {
+            part.write(&mut out)?;
+        }
+        Ok(())
+    }
+
+    /// Write the calendar in iCalendar format to an async writer, see [`Calendar::write`].
+    ///
+    /// Each component is written to `out` as soon as it has been formatted, so only one
+    /// component is held in memory at a time rather than the whole calendar.
+    #[cfg(feature = "tokio")]
+    pub async fn write_async<W: tokio::io::AsyncWrite + Unpin>(
+        &self,
+        out: &mut W,
+    ) -> std::io::Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut chunk = String::new();
+        for part in self.parts() {
+            chunk.clear();
+            part.write(&mut FoldingWriter::new(&mut chunk))
+                .expect("writing to a String cannot fail");
+            out.write_all(chunk.as_bytes()).await?;
+        }
+        out.flush().await
+    }
+
+    /// The calendar split into pieces that are written one after another
+    fn parts(&self) -> impl Iterator<Item = Part<'_, '_>> {
+        iter::once(Part::Header(self))
+            .chain(self.timezones.iter().map(Part::TimeZone))
+            .chain(self.events.iter().map(Part::Event))
+            .chain(self.todos.iter().map(Part::Todo))
+            .chain(self.journals.iter().map(Part::Journal))
+            .chain(iter::once(Part::Footer))
+    }
+
+    fn write_header<W: fmt::Write>(&self, out: &mut FoldingWriter<W>) -> fmt::Result {
+        out.begin("VCALENDAR")?;
+        out.property(&Property::new(names::PRODID, &self.prod_id))?;
+        out.property(&Property::new(names::VERSION, &self.version))?;
+        // `GREGORIAN` is the default
+        if self.cal_scale != CalScale::Gregorian {
+            out.property(&Property::new(names::CALSCALE, &self.cal_scale))?;
+        }
+        if let Some(method) = &self.method {
+            out.property(&Property::new(names::METHOD, method))?;
+        }
+        if let Some(timezone) = &self.default_timezone {
+            out.property(&Property::new(names::X_WR_TIMEZONE, timezone))?;
+        }
+        if let Some(name) = &self.name {
+            out.property(&Property::new(names::X_WR_CALNAME, name))?;
+        }
+        Ok(())
+    }
+
+    /// The line numbers (1-based) of the lines of `serialized` longer than the 75 octets
+    /// RFC 5545 recommends folding at, not counting the line break.
+    ///
+    /// Parsing accepts long lines, so this is for checking the output of serializers.
+    /// [`Calendar::write`] always folds.
+    pub fn check_line_lengths(serialized: &str) -> Vec<usize> {
+        serialized
+            .split('\n')
+            .enumerate()
+            .filter(|(_, line)| line.strip_suffix('\r').unwrap_or(line).len() > MAX_LINE_OCTETS)
+            .map(|(idx, _)| idx + 1)
+            .collect()
+    }
+
+    /// The calendar in iCalendar format, see [`Calendar::write`].
+    pub fn to_ics_string(&self) -> String {
+        let mut output = String::new();
+        self.write(&mut output)
+            .expect("writing to a String cannot fail");
+        output
+    }
+}
+
+impl Event<'_> {
+    /// This event on its own in a calendar produced by `prod_id`, in iCalendar format.
+    ///
+    /// Definitions of the timezones the event refers to by `TZID` are included from the
+    /// bundled timezone database. Fails if one isn't in the database, or if the event has a
+    /// `TZID` and the `tzdb` feature is disabled.
+    pub fn to_calendar_string(&self, prod_id: &str) -> Result<String, TimeZoneError> {
+        let mut builder = CalendarBuilder::for_export(prod_id);
+        #[cfg(feature = "tzdb")]
+        builder.add_event_with_timezone(self.clone())?;
+        #[cfg(not(feature = "tzdb"))]
+        {
+            if let Some(id) = self.timezone_ids().next() {
+                return Err(TimeZoneError::Export {
+                    id: id.id().to_string(),
+                    source: "the `tzdb` feature is disabled".into(),
+                });
+            }
+            builder.add_event(self.clone());
+        }
+        let calendar = builder.build().expect("the PRODID was set");
+        Ok(calendar.to_ics_string())
+    }
+
+    fn write<W: fmt::Write>(&self, out: &mut FoldingWriter<W>) -> fmt::Result {
+        out.begin("VEVENT")?;
+        for property in self.properties() {
+            out.property(&property)?;
+        }
+        for location in &self.structured_locations {
+            location.write(out)?;
+        }
+        for alarm in &self.alarms {
+            alarm.write(out)?;
+        }
+        out.end("VEVENT")
+    }
+
+    fn properties(&self) -> Vec<Property> {
+        let mut output = vec![Property::new(names::UID, &self.uid)];
+        if let Some(timestamp) = &self.timestamp {
+            output.push(Property::new(names::DTSTAMP, timestamp));
+        }
+        if let Some(start) = &self.start {
+            output.push(date_or_date_time(
+                names::DTSTART,
+                start,
+                self.start_timezone_id.as_ref(),
+            ));
+        }
+        match &self.end {
+            Some(EventEnd::DateTime { value, timezone_id }) => {
+                output.push(date_or_date_time(names::DTEND, value, timezone_id.as_ref()));
+            }
+            Some(EventEnd::Duration(duration)) => {
+                output.push(Property::new(names::DURATION, duration));
+            }
+            None => (),
+        }
+        if let Some(recurrence_id) = &self.recurrence_id {
+            output.push(
+                date_or_date_time(
+                    names::RECURRENCE_ID,
+                    &recurrence_id.value,
+                    recurrence_id.timezone_id.as_ref(),
+                )
+                .opt_param(recurrence_id.range.as_ref()),
+            );
+        }
+        output.extend(
+            self.summary
+                .as_ref()
+                .map(|text| annotated(names::SUMMARY, text)),
+        );
+        output.extend(
+            self.description
+                .as_ref()
+                .map(|text| annotated(names::DESCRIPTION, text)),
+        );
+        output.extend(
+            self.location
+                .as_ref()
+                .map(|text| annotated(names::LOCATION, text)),
+        );
+        if let Some(geo_location) = &self.geo_location {
+            output.push(Property::new(names::GEO, geo_location));
+        }
+        if let Some(created) = &self.created {
+            output.push(Property::new(names::CREATED, created));
+        }
+        if let Some(last_modified) = &self.last_modified {
+            output.push(Property::new(names::LAST_MODIFIED, last_modified));
+        }
+        if let Some(sequence) = &self.sequence {
+            output.push(Property::new(names::SEQUENCE, sequence));
+        }
+        if let Some(priority) = &self.priority {
+            output.push(Property::new(names::PRIORITY, priority.value()));
+        }
+        // public is the default
+        if self.class != Class::Public {
+            output.push(Property::new(names::CLASS, &self.class));
+        }
+        if let Some(status) = &self.status {
+            output.push(Property::new(names::STATUS, status));
+        }
+        if let Some(time_transparency) = &self.time_transparency {
+            output.push(Property::new(names::TRANSP, time_transparency));
+        }
+        if let Some(color) = &self.color {
+            output.push(Property::new(names::COLOR, color));
+        }
+        if let Some(url) = &self.url {
+            output.push(Property::new(names::URL, url));
+        }
+        output.extend(self.organizer.as_ref().map(organizer));
+        output.extend(self.attendees.iter().map(attendee));
+        output.extend(
+            self.recurrence_rules
+                .iter()
+                .map(|rule| Property::new(names::RRULE, rule)),
+        );
+        output.extend(self.recurrence_dates.iter().map(|dates| {
+            date_or_date_time_list(names::RDATE, &dates.values, dates.timezone_id.as_ref())
+        }));
+        output.extend(self.exception_dates.iter().map(|dates| {
+            date_or_date_time_list(names::EXDATE, &dates.values, dates.timezone_id.as_ref())
+        }));
+        output.extend(self.categories.iter().map(categories));
+        output.extend(self.comments.iter().map(comment));
+        output.extend(self.contacts.iter().map(contact));
+        output.extend(self.conferences.iter().map(conference));
+        output.extend(self.attachments.iter().map(attachment));
+        output.extend(self.extensions.iter().map(extension));
+        sort_properties(&mut output, &EVENT_PROPERTY_ORDER);
+        output
+    }
+}
+
+impl Todo<'_> {
+    fn write<W: fmt::Write>(&self, out: &mut FoldingWriter<W>) -> fmt::Result {
+        out.begin("VTODO")?;
+        out.property(&Property::new(names::UID, &self.uid))?;
+        if let Some(timestamp) = &self.timestamp {
+            out.property(&Property::new(names::DTSTAMP, timestamp))?;
+        }
+        if let Some(start) = &self.start {
+            out.property(&date_or_date_time(
+                names::DTSTART,
+                start,
+                self.start_timezone_id.as_ref(),
+            ))?;
+        }
+        if let Some(due) = &self.due {
+            out.property(&date_or_date_time(
+                names::DUE,
+                due,
+                self.due_timezone_id.as_ref(),
+            ))?;
+        }
+        if let Some(duration) = &self.duration {
+            out.property(&Property::new(names::DURATION, duration))?;
+        }
+        if let Some(completed) = &self.completed {
+            out.property(&Property::new(names::COMPLETED, completed))?;
+        }
+        if let Some(percent_complete) = &self.percent_complete {
+            out.property(&Property::new(names::PERCENT_COMPLETE, percent_complete))?;
+        }
+        if let Some(summary) = &self.summary {
+            out.property(&annotated(names::SUMMARY, summary))?;
+        }
+        if let Some(description) = &self.description {
+            out.property(&annotated(names::DESCRIPTION, description))?;
+        }
+        if let Some(created) = &self.created {
+            out.property(&Property::new(names::CREATED, created))?;
+        }
+        if let Some(last_modified) = &self.last_modified {
+            out.property(&Property::new(names::LAST_MODIFIED, last_modified))?;
+        }
+        if let Some(sequence) = &self.sequence {
+            out.property(&Property::new(names::SEQUENCE, sequence))?;
+        }
+        if let Some(priority) = &self.priority {
+            out.property(&Property::new(names::PRIORITY, priority.value()))?;
+        }
+        if let Some(status) = &self.status {
+            out.property(&Property::new(names::STATUS, status.as_str()))?;
+        }
+        out.end("VTODO")
+    }
+}
+
+impl Journal<'_> {
+    fn write<W: fmt::Write>(&self, out: &mut FoldingWriter<W>) -> fmt::Result {
+        out.begin("VJOURNAL")?;
+        out.property(&Property::new(names::UID, &self.uid))?;
+        if let Some(timestamp) = &self.timestamp {
+            out.property(&Property::new(names::DTSTAMP, timestamp))?;
+        }
+        if let Some(start) = &self.start {
+            out.property(&date_or_date_time(
+                names::DTSTART,
+                start,
+                self.start_timezone_id.as_ref(),
+            ))?;
+        }
+        if let Some(summary) = &self.summary {
+            out.property(&annotated(names::SUMMARY, summary))?;
+        }
+        for description in &self.descriptions {
+            out.property(&annotated(names::DESCRIPTION, description))?;
+        }
+        for values in &self.categories {
+            out.property(&categories(values))?;
+        }
+        if let Some(status) = &self.status {
+            out.property(&Property::new(names::STATUS, status.as_str()))?;
+        }
+        for property in &self.extensions {
+            out.property(&extension(property))?;
+        }
+        out.end("VJOURNAL")
+    }
+}
+
+impl TimeZone<'_> {
+    fn write<W: fmt::Write>(&self, out: &mut FoldingWriter<W>) -> fmt::Result {
+        out.begin("VTIMEZONE")?;
+        out.property(&Property::new(names::TZID, &self.id))?;
+        if let Some(last_modified) = &self.last_modified {
+            out.property(&Property::new(names::LAST_MODIFIED, last_modified))?;
+        }
+        if let Some(url) = &self.url {
+            out.property(&Property::new(names::TZURL, url))?;
+        }
+        for observance in &self.observances {
+            observance.write(out)?;
+        }
+        out.end("VTIMEZONE")
+    }
+}
+
+impl Observance<'_> {
+    fn write<W: fmt::Write>(&self, out: &mut FoldingWriter<W>) -> fmt::Result {
+        let name = match self.kind {
+            ObservanceKind::Standard => "STANDARD",
+            ObservanceKind::Daylight => "DAYLIGHT",
+        };
+        out.begin(name)?;
+        out.property(&Property::new(names::DTSTART, self.start))?;
+        out.property(&Property::new(names::TZOFFSETFROM, self.offset_from))?;
+        out.property(&Property::new(names::TZOFFSETTO, self.offset_to))?;
+        for rule in &self.recurrence_rules {
+            out.property(&Property::new(names::RRULE, rule))?;
+        }
+        for date in &self.recurrence_dates {
+            out.property(&Property::new(names::RDATE, date))?;
+        }
+        for name in &self.names {
+            out.property(&annotated(names::TZNAME, name))?;
+        }
+        for text in &self.comments {
+            out.property(&comment(text))?;
+        }
+        out.end(name)
+    }
+}
+
+impl StructuredLocation<'_> {
+    fn write<W: fmt::Write>(&self, out: &mut FoldingWriter<W>) -> fmt::Result {
+        out.begin("VLOCATION")?;
+        out.property(&Property::new(names::UID, &self.uid))?;
+        if let Some(name) = &self.name {
+            out.property(&Property::new(names::NAME, name))?;
+        }
+        if let Some(description) = &self.description {
+            out.property(&annotated(names::DESCRIPTION, description))?;
+        }
+        if let Some(location_type) = &self.location_type {
+            out.property(&Property::new(names::LOCATION_TYPE, location_type))?;
+        }
+        if let Some(geo_location) = &self.geo_location {
+            out.property(&Property::new(names::GEO, geo_location))?;
+        }
+        if let Some(url) = &self.url {
+            out.property(&Property::new(names::URL, url))?;
+        }
+        out.end("VLOCATION")
+    }
+}
+
+impl Alarm<'_> {
+    fn write<W: fmt::Write>(&self, out: &mut FoldingWriter<W>) -> fmt::Result {
+        out.begin("VALARM")?;
+        if let Some(uid) = &self.uid {
+            out.property(&Property::new(names::UID, uid))?;
+        }
+        out.property(&Property::new(names::ACTION, &self.action))?;
+        out.property(&trigger(&self.trigger))?;
+        if let Some(summary) = &self.summary {
+            out.property(&annotated(names::SUMMARY, summary))?;
+        }
+        if let Some(description) = &self.description {
+            out.property(&annotated(names::DESCRIPTION, description))?;
+        }
+        if let Some(duration) = &self.duration {
+            out.property(&Property::new(names::DURATION, duration))?;
+        }
+        if let Some(repeat) = &self.repeat {
+            out.property(&Property::new(names::REPEAT, repeat))?;
+        }
+        for person in &self.attendees {
+            out.property(&attendee(person))?;
+        }
+        for file in &self.attachments {
+            out.property(&attachment(file))?;
+        }
+        if let Some(acknowledged) = &self.acknowledged {
+            out.property(&Property::new(names::ACKNOWLEDGED, acknowledged))?;
+        }
+        for related in &self.related_to {
+            out.property(&related_to(related))?;
+        }
+        for property in &self.extensions {
+            out.property(&extension(property))?;
+        }
+        out.end("VALARM")
+    }
+}
+
+/// A content line waiting to be written
+struct Property {
+    name: String,
+    /// Formatted as `NAME=value`
+    params: Vec<String>,
+    value: String,
+}
+
+impl Property {
+    fn new(name: impl fmt::Display, value: impl fmt::Display) -> Self {
+        Self {
+            name: name.to_string(),
+            params: vec![],
+            value: value.to_string(),
+        }
+    }
+
+    fn param(mut self, param: impl fmt::Display) -> Self {
+        self.params.push(param.to_string());
+        self
+    }
+
+    fn opt_param(self, param: Option<impl fmt::Display>) -> Self {
+        match param {
+            Some(param) => self.param(param),
+            None => self,
+        }
+    }
+
+    /// Add parameters kept without interpretation
+    fn other_params(mut self, params: &[(Name<'_>, VecOne<Cow<'_, str>>)]) -> Self {
+        for (name, values) in params {
+            let mut param = format!("{name}=");
+            for (idx, value) in values.into_iter().enumerate() {
+                if idx > 0 {
+                    param.push(',');
+                }
+                write!(param, "{}", fmt_param_value(value))
+                    .expect("writing to a String cannot fail");
+            }
+            self = self.param(param);
+        }
+        self
+    }
+}
+
+/// Sort `properties` into `order`, followed by any others by name. The sort is stable.
+fn sort_properties(properties: &mut [Property], order: &[Name<'_>]) {
+    let key = |property: &Property| {
+        let name = &*property.name;
+        let position = order.iter().position(|known| known == name);
+        (position.unwrap_or(order.len()), name.to_owned())
+    };
+    properties.sort_by_cached_key(key);
+}
+
+fn date_or_date_time(
+    name: Name<'static>,
+    value: &DateOrDateTime,
+    timezone_id: Option<&TimeZoneIdentifier>,
+) -> Property {
+    let property = Property::new(name, value).opt_param(timezone_id);
+    if value.is_date_time() {
+        property
+    } else {
+        property.param("VALUE=DATE")
+    }
+}
+
+fn date_or_date_time_list(
+    name: Name<'static>,
+    values: &VecOne<DateOrDateTime>,
+    timezone_id: Option<&TimeZoneIdentifier>,
+) -> Property {
+    // the `VALUE` parameter of the first value applies to the whole list
+    let mut property = date_or_date_time(name, &values.first, timezone_id);
+    property.value = values.display().to_string();
+    property
+}
+
+fn annotated(name: Name<'static>, text: &AnnotatedText<'_>) -> Property {
+    Property::new(name, escape_text(&text.text))
+        .opt_param(text.lang.as_ref())
+        .opt_param(text.altrep.as_ref())
+}
+
+fn comment(comment: &Comment<'_>) -> Property {
+    Property::new(names::COMMENT, escape_text(&comment.value))
+        .opt_param(comment.lang.as_ref())
+        .opt_param(comment.altrep.as_ref())
+}
+
+fn contact(contact: &Contact<'_>) -> Property {
+    Property::new(names::CONTACT, escape_text(&contact.value))
+        .opt_param(contact.lang.as_ref())
+        .opt_param(contact.altrep.as_ref())
+}
+
+fn categories(categories: &Categories<'_>) -> Property {
+    // values are stored unescaped
+    let mut value = String::new();
+    for (idx, category) in categories.values.into_iter().enumerate() {
+        if idx > 0 {
+            value.push(',');
+        }
+        value.push_str(&escape_text(category));
+    }
+    Property::new(names::CATEGORIES, value).opt_param(categories.lang.as_ref())
+}
+
+fn organizer(organizer: &Organizer<'_>) -> Property {
+    Property::new(names::ORGANIZER, &organizer.value)
+        .opt_param(organizer.common_name.clone().map(CommonName))
+        .opt_param(organizer.dir.as_ref())
+        .opt_param(organizer.sent_by.as_ref())
+        .opt_param(organizer.lang.as_ref())
+        .opt_param(organizer.schedule_agent.as_ref())
+        .opt_param(organizer.schedule_force_send.as_ref())
+        .opt_param(organizer.schedule_status.as_ref())
+}
+
+fn attendee(attendee: &Attendee<'_>) -> Property {
+    let mut property = Property::new(names::ATTENDEE, &attendee.value);
+    if attendee.cutype != Default::default() {
+        property = property.param(&attendee.cutype);
+    }
+    property =
+        property.opt_param(address_list(&attendee.group_or_list_members).map(GroupOrListMember));
+    if attendee.role != ParticipationRole::default() {
+        property = property.param(&attendee.role);
+    }
+    if attendee.participation_status != ParticipationStatus::default() {
+        property = property.param(&attendee.participation_status);
+    }
+    if attendee.rsvp != RsvpExpectation::default() {
+        property = property.param(&attendee.rsvp);
+    }
+    property
+        .opt_param(address_list(&attendee.delegated_to).map(Delegatees))
+        .opt_param(address_list(&attendee.delegated_from).map(Delegators))
+        .opt_param(attendee.sent_by.clone().map(SentBy))
+        .opt_param(attendee.common_name.clone().map(CommonName))
+        .opt_param(attendee.dir.as_ref())
+        .opt_param(attendee.lang.as_ref())
+        .opt_param(attendee.schedule_agent.as_ref())
+        .opt_param(attendee.schedule_force_send.as_ref())
+        .opt_param(attendee.schedule_status.as_ref())
+        .other_params(&attendee.other_params)
+}
+
+fn address_list<'src>(
+    addresses: &[CalendarUserAddress<'src>],
+) -> Option<VecOne<CalendarUserAddress<'src>>> {
+    let (first, rest) = addresses.split_first()?;
+    Some(VecOne::from_parts(first.clone(), rest.to_vec()))
+}
+
+fn attachment(attachment: &Attachment<'_>) -> Property {
+    let property = match &attachment.data {
+        Data::Uri(uri) => Property::new(names::ATTACH, uri),
+        Data::Raw(raw) => Property::new(names::ATTACH, raw),
+        Data::Blob(data) => Property::new(names::ATTACH, BASE64_STANDARD.encode(data))
+            .param("ENCODING=BASE64")
+            .param("VALUE=BINARY"),
+    };
+    property.opt_param(attachment.fmt_type.as_ref())
+}
+
+fn conference(conference: &Conference<'_>) -> Property {
+    let mut property = Property::new(names::CONFERENCE, &conference.uri).param("VALUE=URI");
+    if !conference.features.is_empty() {
+        let features: Vec<_> = conference
+            .features
+            .iter()
+            .map(|feature| fmt_param_value(feature).to_string())
+            .collect();
+        property = property.param(format!("FEATURE={}", features.join(",")));
+    }
+    if let Some(label) = &conference.label {
+        property = property.param(format!("LABEL={}", fmt_param_value(label)));
+    }
+    property
+}
+
+fn related_to(related: &RelatedTo<'_>) -> Property {
+    let property = Property::new(names::RELATED_TO, &related.uid);
+    match related.rel_type {
+        RelationshipType::Parent => property,
+        _ => property.param(&related.rel_type),
+    }
+}
+
+fn trigger(trigger: &AlarmTrigger) -> Property {
+    match trigger {
+        AlarmTrigger::Relative { related, duration } => {
+            let property = Property::new(names::TRIGGER, duration);
+            match related {
+                AlarmTriggerRelationship::Start => property,
+                AlarmTriggerRelationship::End => property.param(related),
+            }
+        }
+        AlarmTrigger::Absolute(time) => {
+            Property::new(names::TRIGGER, time).param("VALUE=DATE-TIME")
+        }
+    }
+}
+
+fn extension(extension: &ExtensionProperty<'_>) -> Property {
+    Property::new(&extension.name, &extension.value).other_params(&extension.params)
+}
+
+/// Escape `TEXT` values
+fn escape_text(input: &str) -> Cow<'_, str> {
+    if !input.contains(['\\', ';', ',', '\n']) {
+        return Cow::Borrowed(input);
+    }
+    let mut output = String::with_capacity(input.len() + 4);
+    for ch in input.chars() {
+        match ch {
+            '\\' | ';' | ',' => {
+                output.push('\\');
+                output.push(ch);
+            }
+            '\n' => output.push_str("\\n"),
+            ch => output.push(ch),
+        }
+    }
+    Cow::Owned(output)
+}
+
+/// A piece of a calendar, made of whole content lines
+enum Part<'a, 'src> {
+    /// `BEGIN:VCALENDAR` and the calendar's properties
+    Header(&'a Calendar<'src>),
+    TimeZone(&'a TimeZone<'src>),
+    Event(&'a Event<'src>),
+    Todo(&'a Todo<'src>),
+    Journal(&'a Journal<'src>),
+    /// `END:VCALENDAR`
+    Footer,
+}
+
+impl Part<'_, '_> {
+    fn write<W: fmt::Write>(&self, out: &mut FoldingWriter<W>) -> fmt::Result {
+        match self {
+            Part::Header(calendar) => calendar.write_header(out),
+            Part::TimeZone(timezone) => timezone.write(out),
+            Part::Event(event) => event.write(out),
+            Part::Todo(todo) => todo.write(out),
+            Part::Journal(journal) => journal.write(out),
+            Part::Footer => out.end("VCALENDAR"),
+        }
+    }
+}
+
+/// Writes content lines, folding them so no line is longer than [`MAX_LINE_OCTETS`].
+pub(crate) struct FoldingWriter<W> {
+    inner: W,
+    /// Octets written on the current line
+    line_len: usize,
+}
+
+impl<W: fmt::Write> FoldingWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self { inner, line_len: 0 }
+    }
+
+    fn property(&mut self, property: &Property) -> fmt::Result {
+        self.write_str(&property.name)?;
+        for param in &property.params {
+            write!(self, ";{param}")?;
+        }
+        write!(self, ":{}", property.value)?;
+        self.end_line()
+    }
+
+    fn begin(&mut self, component: &str) -> fmt::Result {
+        write!(self, "BEGIN:{component}")?;
+        self.end_line()
+    }
+
+    fn end(&mut self, component: &str) -> fmt::Result {
+        write!(self, "END:{component}")?;
+        self.end_line()
+    }
+
+    fn end_line(&mut self) -> fmt::Result {
+        self.line_len = 0;
+        self.inner.write_str("\r\n")
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for FoldingWriter<W> {
+    fn write_str(&mut self, input: &str) -> fmt::Result {
+        for ch in input.chars() {
+            // never split a character across lines
+            if self.line_len + ch.len_utf8() > MAX_LINE_OCTETS {
+                self.inner.write_str("\r\n ")?;
+                self.line_len = 1;
+            }
+            self.inner.write_char(ch)?;
+            self.line_len += ch.len_utf8();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use super::FoldingWriter;
+    use crate::{Calendar, test_util};
+
+    #[test]
+    fn fold_long_lines() {
+        let mut output = String::new();
+        let mut writer = FoldingWriter::new(&mut output);
+        write!(writer, "DESCRIPTION:{}", "é".repeat(40)).unwrap();
+        writer.end_line().unwrap();
+
+        let lines: Vec<_> = output.split("\r\n").collect();
+        assert_eq!(lines.len(), 3);
+        // 12 octets of name, then 31 two-octet characters
+        assert_eq!(lines[0].len(), 74);
+        assert_eq!(lines[1], format!(" {}", "é".repeat(9)));
+        assert_eq!(lines[2], "");
+    }
+
+    #[test]
+    fn line_lengths() {
+        let description = "x".repeat(100);
+        let unfolded = test_util::calendar(&format!(
+            "BEGIN:VEVENT\r\n\
+            UID:1\r\n\
+            DESCRIPTION:{description}\r\n\
+            END:VEVENT\r\n"
+        ));
+        assert_eq!(Calendar::check_line_lengths(&unfolded), [6]);
+
+        let calendar = crate::parse(&unfolded).unwrap().remove(0);
+        let folded = calendar.to_ics_string();
+        assert!(Calendar::check_line_lengths(&folded).is_empty());
+
+        // exactly 75 octets is fine
+        let line = format!("DESCRIPTION:{}", "é".repeat(31) + "x");
+        assert_eq!(line.len(), 75);
+        assert!(Calendar::check_line_lengths(&line).is_empty());
+    }
+
+    #[test]
+    fn round_trip() {
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:20111215T112233Z-1234@example.com\r\n\
+            DTSTAMP:20111215T112233Z\r\n\
+            DTSTART;VALUE=DATE:20111217\r\n\
+            DURATION:P1D\r\n\
+            SUMMARY;LANGUAGE=en:Planning\\, with a comma\r\n\
+            ORGANIZER;CN=Jane Doe:mailto:jane@example.com\r\n\
+            ATTENDEE;PARTSTAT=DECLINED;RSVP=TRUE:mailto:john@example.com\r\n\
+            CATEGORIES:MEETING,WORK\\, MAYBE\r\n\
+            RRULE:FREQ=WEEKLY;COUNT=4;BYDAY=SA\r\n\
+            EXDATE;VALUE=DATE:20111224,20111231\r\n\
+            X-VENDOR-FLAG;X-PARAM=\"a,b\":yes\r\n\
+            END:VEVENT\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+        let output = calendar.to_ics_string();
+        assert_eq!(crate::parse(&output).unwrap(), [calendar]);
+    }
+
+    #[test]
+    fn escape_text() {
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:1\r\n\
+            DTSTAMP:20111215T112233Z\r\n\
+            END:VEVENT\r\n",
+        );
+        let mut calendar = crate::parse(&input).unwrap().remove(0);
+        calendar.events[0].summary = Some(crate::AnnotatedText {
+            text: "Planning; with a comma, a \\ and\na newline".into(),
+            ..Default::default()
+        });
+        let output = calendar.to_ics_string();
+        assert!(
+            output.contains("\r\nSUMMARY:Planning\\; with a comma\\, a \\\\ and\\na newline\r\n")
+        );
+        assert_eq!(crate::parse(&output).unwrap(), [calendar]);
+    }
+
+    #[test]
+    fn class_round_trip() {
+        let input = |class: &str| {
+            test_util::calendar(&format!(
+                "BEGIN:VEVENT\r\n\
+                UID:1\r\n\
+                CLASS:{class}\r\n\
+                END:VEVENT\r\n"
+            ))
+        };
+        for class in ["CONFIDENTIAL", "X-MY-VENDOR-SECRET", "SECRET"] {
+            let input = input(class);
+            let calendar = crate::parse(&input).unwrap().remove(0);
+            assert_eq!(calendar.events[0].class.to_string(), class);
+            assert_eq!(calendar.to_ics_string(), input);
+        }
+
+        // the default isn't written
+        let public = input("PUBLIC");
+        let calendar = crate::parse(&public).unwrap().remove(0);
+        assert_eq!(calendar.events[0].class.to_string(), "PUBLIC");
+        assert!(!calendar.to_ics_string().contains("CLASS"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn write_async() {
+        // long lines are folded the same way
+        let input = include_str!("../tests/fixtures/folding.ics");
+        let calendar = crate::parse(input).unwrap().remove(0);
+        let mut output = Vec::new();
+        calendar.write_async(&mut output).await.unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), calendar.to_ics_string());
+    }
+
+    #[test]
+    fn canonical_order() {
+        let calendar = |event: &str| {
+            test_util::calendar(&format!(
+                "BEGIN:VEVENT\r\n\
+                {event}\
+                END:VEVENT\r\n"
+            ))
+        };
+        let first = calendar(
+            "UID:1\r\n\
+            SUMMARY:Lunch\r\n\
+            X-B:b\r\n\
+            ATTENDEE:mailto:a@example.com\r\n\
+            DTSTART:20240102T120000Z\r\n\
+            X-A:a\r\n\
+            ATTENDEE:mailto:b@example.com\r\n\
+            DTSTAMP:20240101T000000Z\r\n",
+        );
+        let second = calendar(
+            "X-A:a\r\n\
+            DTSTAMP:20240101T000000Z\r\n\
+            ATTENDEE:mailto:a@example.com\r\n\
+            X-B:b\r\n\
+            ATTENDEE:mailto:b@example.com\r\n\
+            DTSTART:20240102T120000Z\r\n\
+            SUMMARY:Lunch\r\n\
+            UID:1\r\n",
+        );
+        let first = crate::parse(&first).unwrap().remove(0).to_ics_string();
+        let second = crate::parse(&second).unwrap().remove(0).to_ics_string();
+        assert_eq!(first, second);
+        assert_eq!(
+            first,
+            calendar(
+                "UID:1\r\n\
+                DTSTAMP:20240101T000000Z\r\n\
+                DTSTART:20240102T120000Z\r\n\
+                SUMMARY:Lunch\r\n\
+                ATTENDEE:mailto:a@example.com\r\n\
+                ATTENDEE:mailto:b@example.com\r\n\
+                X-A:a\r\n\
+                X-B:b\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn single_event() {
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:1\r\n\
+            DTSTAMP:20240101T000000Z\r\n\
+            DTSTART:20240102T120000Z\r\n\
+            SUMMARY:Lunch\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:2\r\n\
+            DTSTART;TZID=Europe/Paris:20240710T140000\r\n\
+            END:VEVENT\r\n",
+        );
+        let calendar = crate::parse(&input).unwrap().remove(0);
+
+        let output = calendar.events[0]
+            .to_calendar_string("-//Example Corp.//Planner//EN")
+            .unwrap();
+        let mut reparsed = crate::parse(&output).unwrap();
+        assert_eq!(reparsed.len(), 1);
+        let reparsed = reparsed.remove(0);
+        assert_eq!(reparsed.prod_id, "-//Example Corp.//Planner//EN");
+        assert_eq!(reparsed.events, [calendar.events[0].clone()]);
+        assert!(reparsed.timezones.is_empty());
+
+        let zoned = calendar.events[1].to_calendar_string("-//Example Corp.//Planner//EN");
+        if cfg!(feature = "tzdb") {
+            let zoned = zoned.unwrap();
+            let reparsed = crate::parse(&zoned).unwrap().remove(0);
+            assert_eq!(reparsed.timezones.len(), 1);
+            assert_eq!(reparsed.timezones[0].id, "Europe/Paris");
+            assert_eq!(reparsed.events, [calendar.events[1].clone()]);
+        } else {
+            assert!(zoned.is_err());
+        }
+    }
+
+    #[cfg(feature = "tzdb")]
+    #[test]
+    fn export_with_timezone() {
+        let input = test_util::calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:paris@example.com\r\n\
+            DTSTAMP:20240101T090000Z\r\n\
+            DTSTART;TZID=Europe/Paris:20240710T140000\r\n\
+            DTEND;TZID=Europe/Paris:20240710T150000\r\n\
+            END:VEVENT\r\n",
+        );
+        let event = crate::parse(&input).unwrap().remove(0).events.remove(0);
+
+        let mut builder = crate::CalendarBuilder::for_export("-//Example Corp.//Planner//EN");
+        builder.add_event_with_timezone(event).unwrap();
+        let calendar = builder.build().unwrap();
+        assert_eq!(calendar.timezones.len(), 1);
+
+        let output = calendar.to_ics_string();
+        assert!(output.contains("BEGIN:VTIMEZONE\r\nTZID:Europe/Paris\r\n"));
+        assert!(output.contains("DTSTART;TZID=Europe/Paris:20240710T140000\r\n"));
+
+        let reparsed = crate::parse(&output).unwrap().remove(0);
+        assert_eq!(reparsed, calendar);
+    }
+}
@@ -0,0 +1,244 @@
+//! Parse calendars in the shape exported by major providers, write them out again and check
+//! every line survived, parameters and value included, apart from the properties listed for
+//! that provider.
+//!
+//! The fixtures in `tests/providers` were written by hand to follow each provider's exports:
+//! their property order, folding, extension (`X-`) properties and quirks. They aren't
+//! captured from real accounts, so may miss quirks; anonymised real exports should replace
+//! them. They are parsed in lenient mode, since some providers write invalid calendars. Run
+//! with `--nocapture` to see which properties each provider uses that aren't understood.
+
+use std::{collections::BTreeSet, fs, path::Path};
+
+struct Provider {
+    name: &'static str,
+    /// Properties its exports use that are dropped or changed, as `COMPONENT:PROPERTY`
+    dropped: &'static [&'static str],
+    /// The warnings from parsing in lenient mode
+    warnings: &'static [&'static str],
+}
+
+const PROVIDERS: &[Provider] = &[
+    Provider {
+        name: "google",
+        dropped: &[
+            // calendar-level extensions aren't kept
+            "VCALENDAR:X-WR-CALDESC",
+            "VTIMEZONE:X-LIC-LOCATION",
+        ],
+        warnings: &[],
+    },
+    Provider {
+        name: "icloud",
+        dropped: &["VCALENDAR:X-APPLE-CALENDAR-COLOR"],
+        // alarm sounds are names rather than URIs
+        warnings: &["line 45: `Chord` is not a valid URI"],
+    },
+    Provider {
+        name: "outlook",
+        dropped: &[
+            // `-0000` is read, and written, as `+0000`
+            "DAYLIGHT:TZOFFSETFROM",
+            "STANDARD:TZOFFSETTO",
+            "VCALENDAR:X-CALEND",
+            "VCALENDAR:X-CALSTART",
+            "VCALENDAR:X-MS-OLK-WKHRDAYS",
+            "VCALENDAR:X-MS-OLK-WKHREND",
+            "VCALENDAR:X-MS-OLK-WKHRSTART",
+            "VCALENDAR:X-OWNER",
+            "VCALENDAR:X-PRIMARY-CALENDAR",
+            "VCALENDAR:X-WR-RELCALID",
+        ],
+        warnings: &[
+            "line 20: `-0000` is not a valid UTC offset in TZOFFSETTO",
+            "line 25: `-0000` is not a valid UTC offset in TZOFFSETFROM",
+        ],
+    },
+    Provider {
+        name: "nextcloud",
+        dropped: &[
+            "VCALENDAR:REFRESH-INTERVAL",
+            "VCALENDAR:X-APPLE-CALENDAR-COLOR",
+            "VCALENDAR:X-PUBLISHED-TTL",
+            "VTODO:X-OC-HIDESUBTASKS",
+        ],
+        warnings: &[],
+    },
+];
+
+/// Lines giving a property its default value, which aren't written out
+const DEFAULTS: &[&str] = &["CALSCALE:GREGORIAN", "CLASS:PUBLIC"];
+
+/// Parameters giving their default value, which aren't written out
+const DEFAULT_PARAMS: &[(&str, &str)] = &[
+    ("ATTACH", "VALUE=URI"),
+    ("ATTENDEE", "CUTYPE=INDIVIDUAL"),
+    ("ATTENDEE", "PARTSTAT=NEEDS-ACTION"),
+    ("ATTENDEE", "ROLE=REQ-PARTICIPANT"),
+    ("ATTENDEE", "RSVP=FALSE"),
+    ("TRIGGER", "RELATED=START"),
+    ("URL", "VALUE=URI"),
+];
+
+/// Split `input` at each `separator` outside double quotes
+fn split_unquoted(input: &str, separator: char) -> impl Iterator<Item = &str> {
+    let mut quoted = false;
+    input.split(move |c| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        c == separator && !quoted
+    })
+}
+
+/// A content line in a form that doesn't depend on how it was written: the name and
+/// parameter names uppercased, parameters sorted and unquoted, and default parameters
+/// removed. Values are compared as written, except that the parts of a recurrence rule can
+/// come in any order, and durations have several spellings (`P0DT0H10M0S` is `PT10M`).
+fn normalize(line: &str) -> (String, String) {
+    let mut quoted = false;
+    let value_start = line
+        .find(|c| {
+            if c == '"' {
+                quoted = !quoted;
+            }
+            c == ':' && !quoted
+        })
+        .unwrap_or(line.len());
+    let (head, value) = (
+        &line[..value_start],
+        line.get(value_start + 1..).unwrap_or(""),
+    );
+    let mut parts = split_unquoted(head, ';');
+    let name = parts.next().unwrap_or("").to_ascii_uppercase();
+    let mut params: Vec<String> = parts
+        .map(|param| {
+            let (param_name, values) = param.split_once('=').unwrap_or((param, ""));
+            format!(
+                "{}={}",
+                param_name.to_ascii_uppercase(),
+                values.replace('"', "")
+            )
+        })
+        .filter(|param| !DEFAULT_PARAMS.contains(&(&*name, &**param)))
+        .collect();
+    params.sort();
+    let value = match &*name {
+        "RRULE" => {
+            let mut parts: Vec<_> = value.split(';').collect();
+            parts.sort();
+            parts.join(";")
+        }
+        "DURATION" | "TRIGGER" => match value.parse::<icalendar::types::Duration>() {
+            Ok(duration) => duration.to_string(),
+            Err(_) => value.to_owned(),
+        },
+        _ => value.to_owned(),
+    };
+    let mut normalized = name.clone();
+    for param in params {
+        normalized.push(';');
+        normalized.push_str(&param);
+    }
+    normalized.push(':');
+    normalized.push_str(&value);
+    (name, normalized)
+}
+
+/// The properties in `input` as `COMPONENT:PROPERTY` (e.g. `VEVENT:SUMMARY`), with their
+/// [normalized](normalize) lines
+fn properties(input: &str) -> Vec<(String, String)> {
+    let mut components = vec![];
+    let mut properties = vec![];
+    for line in icalendar::unfold(input) {
+        if DEFAULTS
+            .iter()
+            .any(|default| line.eq_ignore_ascii_case(default))
+        {
+            continue;
+        }
+        let (name, normalized) = normalize(&line);
+        match &*name {
+            "BEGIN" => components.push(normalized["BEGIN:".len()..].to_ascii_uppercase()),
+            "END" => {
+                components.pop();
+            }
+            _ => {
+                let component = components.last().map_or("", String::as_str);
+                properties.push((format!("{component}:{name}"), normalized));
+            }
+        }
+    }
+    properties
+}
+
+fn check_provider(provider: &Provider) -> anyhow::Result<()> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/providers")
+        .join(format!("{}.ics", provider.name));
+    let input = fs::read_to_string(path)?;
+    let options = icalendar::ParseOptions {
+        lenient: true,
+        ..Default::default()
+    };
+    let parsed = icalendar::parse_with(&input, options)?;
+    let output: String = parsed
+        .calendars
+        .iter()
+        .map(icalendar::Calendar::to_ics_string)
+        .collect();
+    let warnings: Vec<_> = parsed.warnings.iter().map(ToString::to_string).collect();
+    anyhow::ensure!(
+        warnings == provider.warnings,
+        "expected warnings {:?}, found {warnings:?}",
+        provider.warnings
+    );
+
+    // every line of the input must be in the output, parameters and value included
+    let mut after = properties(&output);
+    let mut lost = vec![];
+    for (property, line) in properties(&input) {
+        match after.iter().position(|(_, written)| *written == line) {
+            Some(idx) => {
+                after.swap_remove(idx);
+            }
+            None => lost.push((property, line)),
+        }
+    }
+    let changed: Vec<_> = lost
+        .iter()
+        .filter(|(property, _)| !provider.dropped.contains(&property.as_str()))
+        .map(|(property, line)| format!("{property}: {line}"))
+        .collect();
+    let dropped: BTreeSet<_> = lost.iter().map(|(property, _)| property.as_str()).collect();
+    let extensions: BTreeSet<_> = properties(&input)
+        .into_iter()
+        .map(|(property, _)| property)
+        .filter(|property| property.contains(":X-") && !dropped.contains(property.as_str()))
+        .collect();
+    println!("{}: dropped {dropped:?}", provider.name);
+    println!("{}: X- properties kept {extensions:?}", provider.name);
+    anyhow::ensure!(
+        changed.is_empty(),
+        "lines lost or changed when written out:\n{}",
+        changed.join("\n")
+    );
+    anyhow::ensure!(
+        dropped.iter().eq(provider.dropped),
+        "expected to drop {:?}, dropped {dropped:?}",
+        provider.dropped
+    );
+    Ok(())
+}
+
+#[test]
+fn providers() {
+    let failures: Vec<_> = PROVIDERS
+        .iter()
+        .filter_map(|provider| {
+            let error = check_provider(provider).err()?;
+            Some(format!("{}: {error:#}", provider.name))
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
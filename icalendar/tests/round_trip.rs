@@ -0,0 +1,53 @@
+//! Parse every calendar in `tests/fixtures`, write it out and parse it again, checking
+//! nothing changed.
+//!
+//! Every `.ics` file in the directory is covered. Fixtures should only use properties the
+//! writer supports, with extension properties sorted by name (the order they are written in).
+
+use std::{fs, path::Path};
+
+use anyhow::Context;
+
+fn round_trip(path: &Path) -> anyhow::Result<()> {
+    let input = fs::read_to_string(path)?;
+    let calendars = icalendar::parse(&input).context("cannot parse fixture")?;
+    anyhow::ensure!(!calendars.is_empty(), "no calendars in fixture");
+    for calendar in &calendars {
+        let output = calendar.to_ics_string();
+        let long_lines = icalendar::Calendar::check_line_lengths(&output);
+        anyhow::ensure!(long_lines.is_empty(), "lines {long_lines:?} are not folded");
+        let reparsed =
+            icalendar::parse(&output).with_context(|| format!("cannot parse output:\n{output}"))?;
+        anyhow::ensure!(
+            reparsed == [calendar.clone()],
+            "calendar changed when written out:\n{output}"
+        );
+        // writing is stable
+        anyhow::ensure!(
+            reparsed[0].to_ics_string() == output,
+            "output changed when written again"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn fixtures() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut paths: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ics"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no fixtures in {}", dir.display());
+
+    let failures: Vec<_> = paths
+        .iter()
+        .filter_map(|path| {
+            let error = round_trip(path).err()?;
+            Some(format!("{}: {error:#}", path.display()))
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
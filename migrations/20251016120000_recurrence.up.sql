@@ -0,0 +1,4 @@
+-- Recurrence rule (`RRULE` value) of recurring events, NULL for single events
+ALTER TABLE events ADD COLUMN rrule TEXT;
+-- Comma-separated exception dates (`EXDATE` values), only used with `rrule`
+ALTER TABLE events ADD COLUMN exdate TEXT;
@@ -0,0 +1 @@
+DROP INDEX events_calendar_start;
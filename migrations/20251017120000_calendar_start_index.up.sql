@@ -0,0 +1,2 @@
+-- For listing the events of one calendar in a time range
+CREATE INDEX events_calendar_start ON events (calendar_id, start_time);
@@ -0,0 +1,3 @@
+-- IANA timezone the event was created in, for display. Times are still stored in UTC.
+-- NULL for UTC and date-only events.
+ALTER TABLE events ADD COLUMN tzid TEXT;
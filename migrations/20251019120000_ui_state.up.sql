@@ -0,0 +1,6 @@
+-- What the UI was showing when it was closed. There is only ever one row, with id 0.
+CREATE TABLE ui_state (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    year INTEGER NOT NULL,
+    iso_week INTEGER NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE calendars DROP COLUMN color;
@@ -0,0 +1,2 @@
+-- `#rrggbb`, or NULL to use a color picked from the calendar's id
+ALTER TABLE calendars ADD COLUMN color TEXT;
@@ -1,20 +1,26 @@
-use std::{env::current_dir, fs};
+use std::{env::current_dir, fs, io};
 
 use anyhow::{Context, Result, anyhow, bail};
 use camino::Utf8Path;
 use clap::Parser;
 use cli_table::{WithTitle, print_stdout};
+use icalendar::{ParseOptions, UnknownComponents};
 use oauth2::{
     AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, EmptyExtraTokenFields,
     EndpointNotSet, EndpointSet, PkceCodeChallenge, RedirectUrl, RevocationUrl, Scope,
     StandardTokenResponse, TokenResponse, TokenUrl,
     basic::{BasicClient, BasicTokenType},
 };
-use plannr::{data::EventInterval, db, env_var, google_creds::GoogleCreds};
+use plannr::{
+    data::{Calendar, Event, EventInterval, EventIntervalRef, Zone},
+    db, env_var,
+    google_creds::GoogleCreds,
+};
 use reqwest::{Url, redirect::Policy};
 use sqlx::{SqliteConnection, SqlitePool, query};
 use time::{
-    Date, Month, UtcDateTime, format_description::BorrowedFormatItem, macros::format_description,
+    Date, Month, PrimitiveDateTime, UtcDateTime, format_description::BorrowedFormatItem,
+    macros::format_description,
 };
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt, BufStream},
@@ -46,6 +52,9 @@ enum Cmd {
         /// Fetch events for a specific calendar
         #[clap(short, long)]
         calendar: Option<String>,
+        /// Output format
+        #[clap(long, value_enum, default_value_t)]
+        format: Format,
     },
     /// Create a new event
     CreateEvent {
@@ -53,20 +62,58 @@ enum Cmd {
         label: String,
         start_time: String,
         end_time: String,
+        /// Read the times as wall-clock times in this IANA timezone, e.g. `Europe/London`,
+        /// rather than UTC
+        #[clap(long)]
+        tz: Option<String>,
+    },
+    /// Find events whose label contains some text
+    Search { query: String },
+    /// Show the events between two dates (inclusive), grouped by day
+    Agenda {
+        /// First day of the agenda (YYYY-MM-DD)
+        from: String,
+        /// Last day of the agenda (YYYY-MM-DD)
+        to: String,
+        /// Only show events for a specific calendar
+        #[clap(short, long)]
+        calendar: Option<String>,
     },
     /// Get google events through CalDAV
     DisplayGoogle,
+    /// Check an .ics file can be imported, without importing it
+    Validate {
+        path: String,
+        /// Accept some invalid input, reporting it as warnings
+        #[clap(long)]
+        lenient: bool,
+    },
+}
+
+#[derive(Debug, Default, Clone, Copy, clap::ValueEnum)]
+enum Format {
+    /// A human-readable table
+    #[default]
+    Table,
+    /// JSON, for scripting
+    Json,
 }
 
 const DATE_DESC: &[BorrowedFormatItem<'_>] = format_description!("[year]-[month]-[day]");
 const DATETIME_DESC: &[BorrowedFormatItem<'_>] =
     format_description!("[year]-[month]-[day] [hour]:[minute]");
+const TIME_DESC: &[BorrowedFormatItem<'_>] = format_description!("[hour]:[minute]");
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
-    // we load env vars before setting up logging, so just use main return
-    dotenv::dotenv()?;
+    // we load env vars before setting up logging, so just use main return. Not all commands
+    // need them, so a missing .env is fine
+    if let Err(e) = dotenv::dotenv()
+        && !e.not_found()
+    {
+        return Err(e.into());
+    }
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
         .init();
@@ -78,14 +125,19 @@ async fn main() -> Result<()> {
         Cmd::ListEvents {
             calendar_id,
             calendar,
-        } => list_events(calendar_id, calendar.as_deref()).await,
+            format,
+        } => list_events(calendar_id, calendar.as_deref(), format).await,
         Cmd::CreateEvent {
             calendar_id,
             label,
             start_time,
             end_time,
-        } => create_event(calendar_id, label, start_time, end_time).await,
+            tz,
+        } => create_event(calendar_id, label, start_time, end_time, tz.as_deref()).await,
+        Cmd::Search { query } => search_events(&query).await,
+        Cmd::Agenda { from, to, calendar } => agenda(&from, &to, calendar.as_deref()).await,
         Cmd::DisplayGoogle => display_google_events().await,
+        Cmd::Validate { path, lenient } => validate(&path, lenient),
     } {
         tracing::error!("{e:?}");
         std::process::exit(1);
@@ -154,7 +206,11 @@ async fn list_calendars() -> Result<()> {
     Ok(())
 }
 
-async fn list_events(calendar_id: Option<i64>, calendar: Option<&str>) -> Result<()> {
+async fn list_events(
+    calendar_id: Option<i64>,
+    calendar: Option<&str>,
+    format: Format,
+) -> Result<()> {
     let pool = SqlitePool::connect(&env_var("DATABASE_URL")?).await?;
     let mut conn = pool.acquire().await?;
     let calendar_id = match (calendar_id, calendar) {
@@ -175,6 +231,26 @@ async fn list_events(calendar_id: Option<i64>, calendar: Option<&str>) -> Result
         }
     };
     let events = db::get_events(calendar_id, &mut *conn).await?;
+    match format {
+        Format::Table => print_stdout(events.with_title())?,
+        Format::Json => {
+            let mut stdout = io::stdout().lock();
+            write_json(&events, &mut stdout)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_json(events: &[Event], mut out: impl io::Write) -> Result<()> {
+    serde_json::to_writer_pretty(&mut out, events)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+async fn search_events(query: &str) -> Result<()> {
+    let pool = SqlitePool::connect(&env_var("DATABASE_URL")?).await?;
+    let mut conn = pool.acquire().await?;
+    let events = db::find_events_by_label(query, &mut *conn).await?;
     print_stdout(events.with_title())?;
     Ok(())
 }
@@ -184,19 +260,9 @@ async fn create_event(
     label: String,
     start_time: String,
     end_time: String,
+    tz: Option<&str>,
 ) -> Result<()> {
-    let date_desc = format_description!("[year]-[month]-[day]");
-    let datetime_desc = format_description!("[year]-[month]-[day] [hour]:[minute]");
-    let interval = if let Ok(start) = Date::parse(&start_time, date_desc) {
-        // end must be date
-        let end = Date::parse(&end_time, date_desc)?;
-        EventInterval::new_date(start, end)
-    } else {
-        // try datetime
-        let start = UtcDateTime::parse(&start_time, datetime_desc)?;
-        let end = UtcDateTime::parse(&end_time, datetime_desc)?;
-        EventInterval::new_datetime(start, end)
-    }?;
+    let interval = parse_interval(&start_time, &end_time, tz)?;
     let pool = SqlitePool::connect(&env_var("DATABASE_URL")?).await?;
     let mut conn = pool.acquire().await?;
     let calendar = db::new_event(calendar_id, &label, interval, &mut *conn).await?;
@@ -204,6 +270,174 @@ async fn create_event(
     Ok(())
 }
 
+/// The interval between two dates, or two times in UTC or in timezone `tz`
+fn parse_interval(start_time: &str, end_time: &str, tz: Option<&str>) -> Result<EventInterval> {
+    let zone = tz.map(Zone::new).transpose()?;
+    if let Ok(start) = Date::parse(start_time, DATE_DESC) {
+        // end must be date
+        let end = Date::parse(end_time, DATE_DESC)?;
+        return Ok(EventInterval::new_date(start, end)?);
+    }
+    // try datetime
+    let start = PrimitiveDateTime::parse(start_time, DATETIME_DESC)?;
+    let end = PrimitiveDateTime::parse(end_time, DATETIME_DESC)?;
+    let interval = match zone {
+        Some(zone) => {
+            EventInterval::new_zoned(zone.from_local(start)?, zone.from_local(end)?, zone)
+        }
+        None => EventInterval::new_datetime(start.as_utc(), end.as_utc()),
+    }?;
+    Ok(interval)
+}
+
+#[derive(cli_table::Table)]
+struct AgendaRow {
+    #[table(title = "Time")]
+    time: String,
+    #[table(title = "Event")]
+    label: String,
+    #[table(title = "Calendar")]
+    calendar: String,
+}
+
+async fn agenda(from: &str, to: &str, calendar: Option<&str>) -> Result<()> {
+    let from = Date::parse(from, DATE_DESC).context("start of agenda")?;
+    let to = Date::parse(to, DATE_DESC).context("end of agenda")?;
+    let Some(after_to) = to.next_day() else {
+        bail!("end of agenda `{to}` is out of range");
+    };
+    let pool = SqlitePool::connect(&env_var("DATABASE_URL")?).await?;
+    let mut conn = pool.acquire().await?;
+    let calendar_id = match calendar {
+        Some(calendar) => Some(db::find_calendar(calendar, &mut *conn).await?.id),
+        None => None,
+    };
+    let calendars = db::get_calendars(&mut *conn).await?;
+    let events = db::get_events_in_range(
+        calendar_id,
+        from.midnight().as_utc(),
+        after_to.midnight().as_utc(),
+        &mut *conn,
+    )
+    .await?;
+
+    let days = agenda_days(events, &calendars, from)?;
+    if days.is_empty() {
+        println!("No events between {from} and {to}");
+    }
+    for (day, rows) in days {
+        println!("{day}");
+        print_stdout(rows.with_title())?;
+    }
+    Ok(())
+}
+
+/// Group `events`, which are sorted, into the days of an agenda starting on `from`. Events that
+/// started before the agenda are shown on its first day.
+fn agenda_days(
+    events: Vec<Event>,
+    calendars: &[Calendar],
+    from: Date,
+) -> Result<Vec<(Date, Vec<AgendaRow>)>> {
+    let mut days: Vec<(Date, Vec<AgendaRow>)> = vec![];
+    for event in events {
+        let (day, time) = match *event.interval {
+            EventIntervalRef::Date { start, end } if start == end => (start, "all day".to_string()),
+            EventIntervalRef::Date { start, end } => (start, format!("all day until {end}")),
+            EventIntervalRef::DateTime { start, end } => {
+                let end = if end.date() == start.date() {
+                    end.format(TIME_DESC)?
+                } else {
+                    end.format(DATETIME_DESC)?
+                };
+                (
+                    start.date(),
+                    format!("{} - {end}", start.format(TIME_DESC)?),
+                )
+            }
+            // shown in the zone the event was created in
+            EventIntervalRef::ZonedDateTime { start, end, zone } => {
+                let (start, end) = (zone.to_local(start), zone.to_local(end));
+                let end = if end.date() == start.date() {
+                    end.format(TIME_DESC)?
+                } else {
+                    end.format(DATETIME_DESC)?
+                };
+                (
+                    start.date(),
+                    format!("{} - {end} {zone}", start.format(TIME_DESC)?),
+                )
+            }
+        };
+        let row = AgendaRow {
+            time,
+            label: event.label,
+            calendar: calendars
+                .iter()
+                .find(|calendar| calendar.id == event.calendar_id)
+                .map_or_else(
+                    || event.calendar_id.to_string(),
+                    |calendar| calendar.name.clone(),
+                ),
+        };
+        let day = day.max(from);
+        match days.last_mut() {
+            Some((last, rows)) if *last == day => rows.push(row),
+            _ => days.push((day, vec![row])),
+        }
+    }
+
+    Ok(days)
+}
+
+fn validate(path: &str, lenient: bool) -> Result<()> {
+    let input = fs::read_to_string(path).with_context(|| format!("reading {path}"))?;
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = validate_report(&input, lenient, &mut stdout) {
+        // this is the report, so print it whatever the log level
+        eprintln!("error: invalid calendar {path}: {e:#}");
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Parse `input`, writing a summary of what it contains and any warnings to `out`. Errors if
+/// the input can't be parsed, with the line it failed on.
+fn validate_report(input: &str, lenient: bool, mut out: impl io::Write) -> Result<()> {
+    let options = ParseOptions {
+        lenient,
+        unknown_components: UnknownComponents::Collect,
+        ..Default::default()
+    };
+    let parsed = icalendar::parse_with(input, options)?;
+    let count =
+        |f: fn(&icalendar::Calendar<'_>) -> usize| parsed.calendars.iter().map(f).sum::<usize>();
+    writeln!(
+        out,
+        "{} calendars: {} events, {} todos, {} journals, {} unknown components",
+        parsed.calendars.len(),
+        count(|calendar| calendar.events.len()),
+        count(|calendar| calendar.todos.len()),
+        count(|calendar| calendar.journals.len()),
+        count(|calendar| calendar.skipped_components.len()),
+    )?;
+    for calendar in &parsed.calendars {
+        for skipped in &calendar.skipped_components {
+            writeln!(
+                out,
+                "skipped {} on lines {}-{}",
+                skipped.name,
+                skipped.lines.start(),
+                skipped.lines.end()
+            )?;
+        }
+    }
+    for warning in &parsed.warnings {
+        writeln!(out, "warning: {warning}")?;
+    }
+    Ok(())
+}
+
 async fn display_google_events() -> Result<()> {
     let pool = SqlitePool::connect(&env_var("DATABASE_URL")?).await?;
     let mut conn = pool.acquire().await?;
@@ -360,3 +594,221 @@ fn google_oauth_client(
             RevocationUrl::new("https://oauth2.googleapis.com/revoke".to_string()).unwrap(),
         ))
 }
+
+#[cfg(test)]
+mod tests {
+    use plannr::data::{Calendar, Event, EventInterval, Zone};
+    use time::{Date, Month};
+
+    #[test]
+    fn interval_in_timezone() {
+        let day = Date::from_calendar_date(2025, Month::July, 3).unwrap();
+        let interval = super::parse_interval(
+            "2025-07-03 10:00",
+            "2025-07-03 11:00",
+            Some("America/New_York"),
+        )
+        .unwrap();
+        // EDT is 4 hours behind UTC
+        assert_eq!(
+            interval,
+            EventInterval::new_zoned(
+                day.with_hms(14, 0, 0).unwrap().as_utc(),
+                day.with_hms(15, 0, 0).unwrap().as_utc(),
+                Zone::new("America/New_York").unwrap(),
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            super::parse_interval("2025-07-03 10:00", "2025-07-03 11:00", None).unwrap(),
+            EventInterval::new_datetime(
+                day.with_hms(10, 0, 0).unwrap().as_utc(),
+                day.with_hms(11, 0, 0).unwrap().as_utc(),
+            )
+            .unwrap()
+        );
+        let error =
+            super::parse_interval("2025-07-03 10:00", "2025-07-03 11:00", Some("Mars/Base"))
+                .unwrap_err();
+        assert_eq!(error.to_string(), "unknown timezone Mars/Base");
+
+        // 02:30 doesn't happen when the clocks go forward, so is read as 03:30 EDT
+        let march = Date::from_calendar_date(2025, Month::March, 9).unwrap();
+        let zone = Zone::new("America/New_York").unwrap();
+        assert_eq!(
+            zone.from_local(march.with_hms(2, 30, 0).unwrap()).unwrap(),
+            march.with_hms(7, 30, 0).unwrap().as_utc()
+        );
+        // 01:30 happens twice when they go back, the first time is in EDT
+        let november = Date::from_calendar_date(2025, Month::November, 2).unwrap();
+        assert_eq!(
+            zone.from_local(november.with_hms(1, 30, 0).unwrap())
+                .unwrap(),
+            november.with_hms(5, 30, 0).unwrap().as_utc()
+        );
+
+        // near the end of the supported range
+        let interval = super::parse_interval(
+            "9999-12-31 01:00",
+            "9999-12-31 02:00",
+            Some("America/New_York"),
+        )
+        .unwrap();
+        let last_day = Date::from_calendar_date(9999, Month::December, 31).unwrap();
+        assert_eq!(
+            interval,
+            EventInterval::new_zoned(
+                last_day.with_hms(6, 0, 0).unwrap().as_utc(),
+                last_day.with_hms(7, 0, 0).unwrap().as_utc(),
+                Zone::new("America/New_York").unwrap(),
+            )
+            .unwrap()
+        );
+        // Tokyo is ahead of UTC, so this is before the first representable instant
+        let tokyo = Zone::new("Asia/Tokyo").unwrap();
+        let error = tokyo.from_local(Date::MIN.midnight()).unwrap_err();
+        assert!(error.to_string().ends_with("in Asia/Tokyo is out of range"));
+    }
+
+    #[test]
+    fn json_output() {
+        let day = Date::from_calendar_date(2025, Month::July, 3).unwrap();
+        let events = [
+            Event {
+                id: 1,
+                calendar_id: 2,
+                label: "multiday event".into(),
+                interval: EventInterval::new_date(day, day.next_day().unwrap()).unwrap(),
+            },
+            Event {
+                id: 3,
+                calendar_id: 2,
+                label: "event".into(),
+                interval: EventInterval::new_datetime(
+                    day.with_hms(10, 0, 0).unwrap().as_utc(),
+                    day.with_hms(10, 30, 0).unwrap().as_utc(),
+                )
+                .unwrap(),
+            },
+        ];
+        let mut output = vec![];
+        super::write_json(&events, &mut output).unwrap();
+
+        let output: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(
+            output,
+            serde_json::json!([
+                {
+                    "id": 1,
+                    "calendar_id": 2,
+                    "label": "multiday event",
+                    "interval": { "date_only": true, "start": "2025-07-03", "end": "2025-07-04" }
+                },
+                {
+                    "id": 3,
+                    "calendar_id": 2,
+                    "label": "event",
+                    "interval": {
+                        "date_only": false,
+                        "start": "2025-07-03T10:00:00Z",
+                        "end": "2025-07-03T10:30:00Z"
+                    }
+                }
+            ])
+        );
+
+        // and back again
+        let interval: EventInterval =
+            serde_json::from_value(output[1]["interval"].clone()).unwrap();
+        assert_eq!(interval, events[1].interval);
+    }
+
+    #[test]
+    fn agenda_days() {
+        let day = Date::from_calendar_date(2025, Month::July, 3).unwrap();
+        let next_day = day.next_day().unwrap();
+        let event = |id, label: &str, interval| Event {
+            id,
+            calendar_id: 1,
+            label: label.into(),
+            interval,
+        };
+        let events = vec![
+            event(
+                1,
+                "conference",
+                EventInterval::new_date(day.previous_day().unwrap(), day).unwrap(),
+            ),
+            event(2, "holiday", EventInterval::new_date(day, day).unwrap()),
+            event(
+                3,
+                "standup",
+                EventInterval::new_datetime(
+                    day.with_hms(9, 0, 0).unwrap().as_utc(),
+                    day.with_hms(9, 15, 0).unwrap().as_utc(),
+                )
+                .unwrap(),
+            ),
+            event(
+                4,
+                "night shift",
+                EventInterval::new_datetime(
+                    day.with_hms(22, 0, 0).unwrap().as_utc(),
+                    next_day.with_hms(6, 0, 0).unwrap().as_utc(),
+                )
+                .unwrap(),
+            ),
+            Event {
+                calendar_id: 2,
+                ..event(
+                    5,
+                    "call",
+                    EventInterval::new_zoned(
+                        next_day.with_hms(14, 0, 0).unwrap().as_utc(),
+                        next_day.with_hms(15, 0, 0).unwrap().as_utc(),
+                        Zone::new("America/New_York").unwrap(),
+                    )
+                    .unwrap(),
+                )
+            },
+        ];
+        let calendars = [Calendar {
+            id: 1,
+            name: "Work".into(),
+            color: None,
+        }];
+
+        let days = super::agenda_days(events, &calendars, day).unwrap();
+        let days: Vec<_> = days
+            .iter()
+            .map(|(day, rows)| {
+                let rows: Vec<_> = rows
+                    .iter()
+                    .map(|row| (&*row.time, &*row.label, &*row.calendar))
+                    .collect();
+                (day.to_string(), rows)
+            })
+            .collect();
+        assert_eq!(
+            days,
+            [
+                (
+                    "2025-07-03".to_string(),
+                    vec![
+                        // started before the agenda
+                        ("all day until 2025-07-03", "conference", "Work"),
+                        ("all day", "holiday", "Work"),
+                        ("09:00 - 09:15", "standup", "Work"),
+                        // shown on the day it starts
+                        ("22:00 - 2025-07-04 06:00", "night shift", "Work"),
+                    ]
+                ),
+                (
+                    "2025-07-04".to_string(),
+                    // calendars that aren't found are shown by ID
+                    vec![("10:00 - 11:00 America/New_York", "call", "2")]
+                ),
+            ]
+        );
+    }
+}
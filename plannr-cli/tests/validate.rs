@@ -0,0 +1,39 @@
+use std::process::{Command, Output};
+
+fn validate(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_plannr-cli"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        // hard errors are reported without logging
+        .env("RUST_LOG", "off")
+        .arg("validate")
+        .arg("tests/fixtures/invalid.ics")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn validate_strict() {
+    let output = validate(&[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr,
+        "error: invalid calendar tests/fixtures/invalid.ics: line 6: fractional seconds are not \
+        allowed in DTSTAMP: `20250704T100000.500Z`\n"
+    );
+}
+
+#[test]
+fn validate_lenient() {
+    let output = validate(&["--lenient"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+    assert_eq!(
+        stdout,
+        "1 calendars: 2 events, 1 todos, 0 journals, 1 unknown components\n\
+        skipped VFREEBUSY on lines 21-24\n\
+        warning: line 6: fractional seconds are not allowed in DTSTAMP: `20250704T100000.500Z`\n\
+        warning: line 13: ORGANIZER address without mailto: `boss@example.com`\n"
+    );
+}
@@ -0,0 +1,192 @@
+//! The form for creating an event
+
+use plannr::data::{EventInterval, RowID};
+use time::{
+    Date, PrimitiveDateTime, format_description::BorrowedFormatItem, macros::format_description,
+};
+use xilem::WidgetView;
+use xilem::style::Style;
+use xilem::view::{Axis, flex, label, text_button, text_input};
+
+use crate::State;
+
+const DATE_DESC: &[BorrowedFormatItem<'_>] = format_description!("[year]-[month]-[day]");
+const DATETIME_DESC: &[BorrowedFormatItem<'_>] =
+    format_description!("[year]-[month]-[day] [hour]:[minute]");
+
+/// What has been typed into the form so far
+#[derive(Debug, Clone)]
+pub struct NewEventForm {
+    pub calendar_id: String,
+    pub label: String,
+    /// `YYYY-MM-DD` for an all-day event or `YYYY-MM-DD HH:MM` (UTC)
+    pub start: String,
+    pub end: String,
+    /// Why the form couldn't be submitted
+    pub error: Option<String>,
+}
+
+impl NewEventForm {
+    /// A form for an all-day event on `day`
+    pub fn new(day: Date) -> Self {
+        // Unwrap: formatting a date can't fail
+        let day = day.format(DATE_DESC).unwrap();
+        Self {
+            calendar_id: "1".into(),
+            label: String::new(),
+            start: day.clone(),
+            end: day,
+            error: None,
+        }
+    }
+}
+
+/// An event ready to be inserted
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewEvent {
+    pub calendar_id: RowID,
+    pub label: String,
+    pub interval: EventInterval,
+}
+
+/// Check the form, giving the event to insert or a message to show.
+///
+/// The start and end must both be dates or both be times, and the end can't be before the
+/// start.
+pub fn form_event(form: &NewEventForm) -> Result<NewEvent, String> {
+    let calendar_id = form
+        .calendar_id
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid calendar ID `{}`", form.calendar_id))?;
+    let label = form.label.trim();
+    if label.is_empty() {
+        return Err("the event needs a label".into());
+    }
+    let (start, end) = (form.start.trim(), form.end.trim());
+    let interval = match (Date::parse(start, DATE_DESC), Date::parse(end, DATE_DESC)) {
+        (Ok(start), Ok(end)) => EventInterval::new_date(start, end),
+        (Ok(_), Err(_)) | (Err(_), Ok(_)) => {
+            return Err("the start and end must both be dates or both be times".into());
+        }
+        (Err(_), Err(_)) => {
+            let parse = |input: &str| {
+                PrimitiveDateTime::parse(input, DATETIME_DESC)
+                    .map(|time| time.as_utc())
+                    .map_err(|_| {
+                        format!("expected YYYY-MM-DD or YYYY-MM-DD HH:MM, found `{input}`")
+                    })
+            };
+            EventInterval::new_datetime(parse(start)?, parse(end)?)
+        }
+    };
+    Ok(NewEvent {
+        calendar_id,
+        label: label.to_string(),
+        interval: interval.map_err(|e| e.to_string())?,
+    })
+}
+
+pub fn form_view(form: &NewEventForm) -> impl WidgetView<State> + use<> {
+    let field = |name: &'static str, value: &str, set: fn(&mut NewEventForm, String)| {
+        flex((
+            label(name),
+            text_input(value.to_string(), move |state: &mut State, value| {
+                if let Some(form) = &mut state.new_event {
+                    set(form, value);
+                }
+            }),
+        ))
+        .direction(Axis::Horizontal)
+    };
+    flex((
+        field("Calendar ID", &form.calendar_id, |form, value| {
+            form.calendar_id = value
+        }),
+        field("Label", &form.label, |form, value| form.label = value),
+        field("Start", &form.start, |form, value| form.start = value),
+        field("End", &form.end, |form, value| form.end = value),
+        form.error.clone().map(label),
+        flex((
+            text_button("Create", |state: &mut State| {
+                let Some(form) = &mut state.new_event else {
+                    return;
+                };
+                // the form stays open until the event has been inserted
+                match form_event(form) {
+                    Ok(event) => {
+                        form.error = None;
+                        state.pending_event = Some(event);
+                    }
+                    Err(error) => form.error = Some(error),
+                }
+            }),
+            text_button("Cancel", |state: &mut State| state.new_event = None),
+        ))
+        .direction(Axis::Horizontal),
+    ))
+    .gap(4.)
+    .padding(8.)
+}
+
+#[cfg(test)]
+mod tests {
+    use plannr::data::EventInterval;
+    use time::{Date, Month};
+
+    use super::{NewEventForm, form_event};
+
+    fn form(start: &str, end: &str) -> NewEventForm {
+        NewEventForm {
+            calendar_id: "2".into(),
+            label: " Lunch ".into(),
+            start: start.into(),
+            end: end.into(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn form_event_interval() {
+        let day = Date::from_calendar_date(2025, Month::July, 3).unwrap();
+
+        let error = form_event(&NewEventForm::new(day)).unwrap_err();
+        assert_eq!(error, "the event needs a label");
+
+        let event = form_event(&form("2025-07-03", "2025-07-04")).unwrap();
+        assert_eq!(event.calendar_id, 2);
+        assert_eq!(event.label, "Lunch");
+        assert_eq!(
+            event.interval,
+            EventInterval::new_date(day, day.next_day().unwrap()).unwrap()
+        );
+
+        let event = form_event(&form("2025-07-03 12:00", "2025-07-03 13:30")).unwrap();
+        assert_eq!(
+            event.interval,
+            EventInterval::new_datetime(
+                day.with_hms(12, 0, 0).unwrap().as_utc(),
+                day.with_hms(13, 30, 0).unwrap().as_utc(),
+            )
+            .unwrap()
+        );
+
+        // the interval's own error is shown
+        let error = form_event(&form("2025-07-04", "2025-07-03")).unwrap_err();
+        assert_eq!(error, "end date 2025-07-03 is before start date 2025-07-04");
+        assert_eq!(
+            form_event(&form("2025-07-03", "2025-07-03 13:30")).unwrap_err(),
+            "the start and end must both be dates or both be times"
+        );
+        assert_eq!(
+            form_event(&form("tomorrow", "2025-07-03 13:30")).unwrap_err(),
+            "expected YYYY-MM-DD or YYYY-MM-DD HH:MM, found `tomorrow`"
+        );
+        let mut bad_calendar = form("2025-07-03", "2025-07-03");
+        bad_calendar.calendar_id = "work".into();
+        assert_eq!(
+            form_event(&bad_calendar).unwrap_err(),
+            "invalid calendar ID `work`"
+        );
+    }
+}
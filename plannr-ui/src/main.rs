@@ -2,33 +2,139 @@ use std::env;
 use std::sync::Arc;
 
 use anyhow::Result;
-use plannr::data::Event;
-use plannr::db::get_events;
+use plannr::data::{Calendar, Event};
+use plannr::db::{
+    get_calendars, get_events, get_events_in_range, get_ui_state, new_event, set_ui_state,
+};
 use sqlx::SqlitePool;
+use time::{Date, Duration, Month, Weekday};
 use xilem::core::fork;
 use xilem::masonry::peniko::color::AlphaColor;
 use xilem::style::{Padding, Style};
 use xilem::view::{
     Axis, CrossAxisAlignment, FlexExt, MainAxisAlignment, flex, label, sized_box, task_raw,
+    text_button,
 };
 use xilem::{EventLoop, FontWeight, LineBreaking, WidgetView, WindowOptions, Xilem};
 
+mod form;
+mod month;
+
+use form::{NewEvent, NewEventForm};
+
 struct State {
     pool: Arc<SqlitePool>,
+    /// Fetched with the events, for their colors
+    calendars: Vec<Calendar>,
     events: Vec<Event>,
     year: i32,
     iso_week: u8,
+    month: Month,
+    view_mode: ViewMode,
+    /// The event being created, if a day has been clicked
+    new_event: Option<NewEventForm>,
+    /// An event from the form waiting to be inserted
+    pending_event: Option<NewEvent>,
+    /// The days each running fetch of `events` is for
+    fetches: Tasks<(Date, Date)>,
+    /// The weeks being saved
+    saves: Tasks<(i32, u8)>,
+    /// The last database error, shown above the view
+    error: Option<String>,
 }
 
 impl State {
     fn new(pool: Arc<SqlitePool>) -> Result<Self> {
         Ok(Self {
             pool,
+            calendars: vec![],
             events: vec![],
             year: 2025,
             iso_week: 27,
+            month: Month::July,
+            view_mode: ViewMode::Week,
+            new_event: None,
+            pending_event: None,
+            // fetched once the last week viewed has been loaded
+            fetches: Tasks::default(),
+            saves: Tasks::default(),
+            error: None,
         })
     }
+
+    /// Show the ISO week `iso_week` of `year`, and its month
+    fn show_week(&mut self, year: i32, iso_week: u8) -> Result<()> {
+        // the month of the thursday is in the same year as the week
+        let thursday = Date::from_iso_week_date(year, iso_week, Weekday::Thursday)?;
+        self.year = year;
+        self.iso_week = iso_week;
+        self.month = thursday.month();
+        self.fetches.push(self.visible_range()?);
+        Ok(())
+    }
+
+    /// Move `weeks` weeks forwards or backwards, saving where we are
+    fn move_weeks(&mut self, weeks: i64) {
+        // Unwrap: the current week is always valid
+        let monday = Date::from_iso_week_date(self.year, self.iso_week, Weekday::Monday).unwrap();
+        let Some(monday) = monday.checked_add(Duration::weeks(weeks)) else {
+            return;
+        };
+        let (year, iso_week, _) = monday.to_iso_week_date();
+        // Unwrap: the week comes from a valid date
+        self.show_week(year, iso_week).unwrap();
+        self.saves.push((year, iso_week));
+    }
+
+    /// The first and last day shown in either view
+    fn visible_range(&self) -> Result<(Date, Date)> {
+        let week_start = Date::from_iso_week_date(self.year, self.iso_week, Weekday::Monday)?;
+        let week_end = week_start + Duration::days(6);
+        let (month_start, month_end) = month::visible_range(self.year, self.month);
+        Ok((week_start.min(month_start), week_end.max(month_end)))
+    }
+}
+
+/// What each running task of one kind was started for.
+///
+/// A task isn't restarted when its view is rebuilt, so each request gets a task of its own,
+/// and they are only all dropped once every one has finished.
+struct Tasks<T> {
+    requests: Vec<T>,
+    finished: usize,
+}
+
+impl<T> Default for Tasks<T> {
+    fn default() -> Self {
+        Self {
+            requests: vec![],
+            finished: 0,
+        }
+    }
+}
+
+impl<T> Tasks<T> {
+    fn push(&mut self, request: T) {
+        self.requests.push(request);
+    }
+
+    /// Record that one of the tasks has finished
+    fn finish(&mut self) {
+        self.finished += 1;
+        if self.finished == self.requests.len() {
+            self.requests.clear();
+            self.finished = 0;
+        }
+    }
+}
+
+/// The days fetched, and their calendars and events
+type FetchResult = ((Date, Date), Result<(Vec<Calendar>, Vec<Event>), String>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ViewMode {
+    Week,
+    Month,
 }
 
 fn event_view(event: &Event, alt_row: bool) -> impl WidgetView<State> + use<> {
@@ -50,21 +156,181 @@ fn event_view(event: &Event, alt_row: bool) -> impl WidgetView<State> + use<> {
 }
 
 fn week_view(data: &mut State) -> impl WidgetView<State> + use<> {
-    // ISO weeks start on monday
-    flex((
-        label("Monday").flex(1.),
-        label("Tuesday").flex(1.),
-        label("Wednesday").flex(1.),
-        label("Thursday").flex(1.),
-        label("Friday").flex(1.),
-        label("Saturday").flex(1.),
-        label("Sunday").flex(1.),
-    ))
-    .direction(Axis::Horizontal)
+    // ISO weeks start on monday. Unwrap: the week is always valid
+    let monday = Date::from_iso_week_date(data.year, data.iso_week, Weekday::Monday).unwrap();
+    let days = (0..7)
+        .map(|offset| {
+            let day = monday + Duration::days(offset);
+            text_button(
+                format!("{} {}", day.weekday(), day.day()),
+                move |state: &mut State| state.new_event = Some(NewEventForm::new(day)),
+            )
+            .flex(1.)
+        })
+        .collect::<Vec<_>>();
+    flex(days).direction(Axis::Horizontal)
 }
 
 fn app_logic(data: &mut State) -> impl WidgetView<State> + use<> {
-    week_view(data)
+    let (mode_label, other_mode) = match data.view_mode {
+        ViewMode::Week => ("Month", ViewMode::Month),
+        ViewMode::Month => ("Week", ViewMode::Week),
+    };
+    let toggle = text_button(mode_label, move |state: &mut State| {
+        state.view_mode = other_mode;
+    });
+    let navigation = flex((
+        text_button("<", |state: &mut State| state.move_weeks(-1)),
+        label(format!("Week {} of {}", data.iso_week, data.year)),
+        text_button(">", |state: &mut State| state.move_weeks(1)),
+        toggle,
+    ))
+    .direction(Axis::Horizontal);
+    let view = match data.view_mode {
+        ViewMode::Week => week_view(data).boxed(),
+        ViewMode::Month => flex((month::weekday_headings(), month::month_view(data)))
+            .gap(4.)
+            .boxed(),
+    };
+    let form = data.new_event.as_ref().map(form::form_view);
+    let error = data.error.clone().map(label);
+    let insert = data.pending_event.clone().map(|event| {
+        let pool = data.pool.clone();
+        task_raw(
+            move |proxy| {
+                let (pool, event) = (pool.clone(), event.clone());
+                async move {
+                    let result = async {
+                        let mut conn = pool.acquire().await?;
+                        new_event(event.calendar_id, &event.label, event.interval, &mut *conn).await
+                    };
+                    let result = result.await.map_err(|e| format!("{e:#}"));
+                    let _ = proxy.message(result);
+                }
+            },
+            |state: &mut State, result: Result<Event, String>| {
+                state.pending_event = None;
+                match result {
+                    Ok(event) => {
+                        state.events.push(event);
+                        state.new_event = None;
+                    }
+                    // the form is still open, so show it there
+                    Err(error) => {
+                        if let Some(form) = &mut state.new_event {
+                            form.error = Some(error);
+                        }
+                    }
+                }
+            },
+        )
+    });
+    // the last week viewed is restored once on startup
+    let load = {
+        let pool = data.pool.clone();
+        task_raw(
+            move |proxy| {
+                let pool = pool.clone();
+                async move {
+                    let result = async {
+                        let mut conn = pool.acquire().await?;
+                        get_ui_state(&mut *conn).await
+                    };
+                    let result = result.await.map_err(|e| format!("{e:#}"));
+                    let _ = proxy.message(result);
+                }
+            },
+            |state: &mut State, result: Result<Option<(i32, u8)>, String>| {
+                let week = result.unwrap_or_else(|error| {
+                    state.error = Some(format!("could not load the last week viewed: {error}"));
+                    None
+                });
+                // a week that isn't valid is ignored, and the default shown instead
+                let restored =
+                    week.is_some_and(|(year, iso_week)| state.show_week(year, iso_week).is_ok());
+                if !restored {
+                    // Unwrap: the default week is valid
+                    let range = state.visible_range().unwrap();
+                    state.fetches.push(range);
+                }
+            },
+        )
+    };
+    let fetches = data
+        .fetches
+        .requests
+        .iter()
+        .map(|&(from, to)| {
+            let pool = data.pool.clone();
+            task_raw(
+                move |proxy| {
+                    let pool = pool.clone();
+                    async move {
+                        let result = async {
+                            let mut conn = pool.acquire().await?;
+                            let calendars = get_calendars(&mut *conn).await?;
+                            let events = get_events_in_range(
+                                None,
+                                from.midnight().as_utc(),
+                                to.next_day().unwrap_or(to).midnight().as_utc(),
+                                &mut *conn,
+                            )
+                            .await?;
+                            anyhow::Ok((calendars, events))
+                        };
+                        let result = result.await.map_err(|e| format!("{e:#}"));
+                        let _ = proxy.message(((from, to), result));
+                    }
+                },
+                |state: &mut State, (range, result): FetchResult| {
+                    state.fetches.finish();
+                    // the events of a week that has since been left aren't wanted
+                    if state.visible_range().ok() != Some(range) {
+                        return;
+                    }
+                    match result {
+                        Ok((calendars, events)) => {
+                            state.calendars = calendars;
+                            state.events = events;
+                            state.error = None;
+                        }
+                        Err(error) => state.error = Some(format!("could not load events: {error}")),
+                    }
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    let saves = data
+        .saves
+        .requests
+        .iter()
+        .map(|&(year, iso_week)| {
+            let pool = data.pool.clone();
+            task_raw(
+                move |proxy| {
+                    let pool = pool.clone();
+                    async move {
+                        let result = async {
+                            let mut conn = pool.acquire().await?;
+                            set_ui_state(year, iso_week, &mut *conn).await
+                        };
+                        let result = result.await.map_err(|e| format!("{e:#}"));
+                        let _ = proxy.message(result);
+                    }
+                },
+                |state: &mut State, result: Result<(), String>| {
+                    state.saves.finish();
+                    if let Err(error) = result {
+                        state.error = Some(format!("could not save the week: {error}"));
+                    }
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    fork(
+        flex((navigation, error, form, view)).gap(8.).padding(8.),
+        (insert, load, fetches, saves),
+    )
 }
 
 fn app_logic_bak(data: &mut State) -> impl WidgetView<State> + use<> {
@@ -0,0 +1,268 @@
+//! A month at a glance: a 6 week grid with the events on their days
+
+use std::cmp::Reverse;
+
+use plannr::data::{Calendar, Event, EventIntervalRef, default_color};
+use time::{Date, Duration, Month, Time, Weekday};
+use xilem::masonry::peniko::color::{AlphaColor, Srgb};
+use xilem::style::Style;
+use xilem::view::{Axis, FlexExt, GridExt, GridParams, flex, grid, label, text_button};
+use xilem::{LineBreaking, WidgetView};
+
+use crate::State;
+use crate::form::NewEventForm;
+
+/// Rows of events shown for each week, more are counted in a "+N more"
+const LANES: usize = 3;
+/// Grid rows for each week: the day numbers, the lanes and the overflow counts
+const ROWS_PER_WEEK: usize = LANES + 2;
+const WEEKS: usize = 6;
+
+/// The first and last day shown for `year`/`month`: from the monday on or before the 1st,
+/// for 6 weeks
+pub fn visible_range(year: i32, month: Month) -> (Date, Date) {
+    // Unwrap: the 1st exists in every month
+    let first = Date::from_calendar_date(year, month, 1).unwrap();
+    let start = first - Duration::days(first.weekday().number_days_from_monday().into());
+    (start, start + Duration::days(7 * WEEKS as i64 - 1))
+}
+
+/// The days an event covers, inclusive. Timed events are placed in their own timezone, or
+/// UTC.
+fn event_days(event: &Event) -> (Date, Date) {
+    let (start, end) = match *event.interval {
+        EventIntervalRef::Date { start, end } => return (start, end),
+        EventIntervalRef::DateTime { start, end } => {
+            ((start.date(), start.time()), (end.date(), end.time()))
+        }
+        EventIntervalRef::ZonedDateTime { start, end, zone } => {
+            let (start, end) = (zone.to_local(start), zone.to_local(end));
+            ((start.date(), start.time()), (end.date(), end.time()))
+        }
+    };
+    // an event ending at midnight doesn't take up the next day
+    let end_date = match end {
+        (date, time) if time == Time::MIDNIGHT && end > start => {
+            date.previous_day().unwrap_or(date)
+        }
+        (date, _) => date,
+    };
+    (start.0, end_date)
+}
+
+/// Part of an event within one week
+struct Segment<'a> {
+    event: &'a Event,
+    /// Columns (0 is monday) the segment covers, inclusive
+    from: usize,
+    to: usize,
+    /// Whether the event carries on from the week before or into the next
+    continued: (bool, bool),
+}
+
+/// Assign the segments of each week to lanes, first come first served. Returns the segments
+/// that fit with their lane, and how many didn't fit on each day.
+fn layout<'a>(events: &'a [Event], week_start: Date) -> (Vec<(usize, Segment<'a>)>, [usize; 7]) {
+    let week_end = week_start + Duration::days(6);
+    let mut segments: Vec<Segment<'a>> = events
+        .iter()
+        .filter_map(|event| {
+            let (start, end) = event_days(event);
+            if end < week_start || start > week_end {
+                return None;
+            }
+            let column = |day: Date| (day - week_start).whole_days() as usize;
+            Some(Segment {
+                event,
+                from: column(start.max(week_start)),
+                to: column(end.min(week_end)),
+                continued: (start < week_start, end > week_end),
+            })
+        })
+        .collect();
+    // longer events first, so bars stay in one lane
+    segments.sort_by_key(|segment| (segment.from, Reverse(segment.to)));
+
+    let mut lanes_used = [[false; 7]; LANES];
+    let mut placed = vec![];
+    let mut overflow = [0; 7];
+    for segment in segments {
+        let days = segment.from..=segment.to;
+        match (0..LANES).find(|&lane| days.clone().all(|day| !lanes_used[lane][day])) {
+            Some(lane) => {
+                days.for_each(|day| lanes_used[lane][day] = true);
+                placed.push((lane, segment));
+            }
+            None => days.for_each(|day| overflow[day] += 1),
+        }
+    }
+    (placed, overflow)
+}
+
+/// The color of the calendar `event` is in
+fn event_color(event: &Event, calendars: &[Calendar]) -> AlphaColor<Srgb> {
+    let [r, g, b] = calendars
+        .iter()
+        .find(|calendar| calendar.id == event.calendar_id)
+        .map(Calendar::rgb)
+        .unwrap_or_else(|| default_color(event.calendar_id));
+    AlphaColor::from_rgb8(r, g, b)
+}
+
+fn chip(segment: &Segment<'_>, color: AlphaColor<Srgb>) -> impl WidgetView<State> + use<> {
+    let text = match segment.continued {
+        (true, true) => format!("< {} >", segment.event.label),
+        (true, false) => format!("< {}", segment.event.label),
+        (false, true) => format!("{} >", segment.event.label),
+        (false, false) => segment.event.label.clone(),
+    };
+    flex((label(text).line_break_mode(LineBreaking::Clip),))
+        .background_color(color.with_alpha(0.6))
+        .padding(2.)
+}
+
+pub fn month_view(data: &mut State) -> impl WidgetView<State> + use<> {
+    let (first_day, _) = visible_range(data.year, data.month);
+    let mut cells = vec![];
+    for week in 0..WEEKS {
+        let week_start = first_day + Duration::weeks(week as i64);
+        let top = (week * ROWS_PER_WEEK) as i32;
+        for column in 0..7 {
+            let day = week_start + Duration::days(column);
+            // days in the month can be clicked to add an event, those outside are dimmed
+            let number = if day.month() == data.month {
+                text_button(day.day().to_string(), move |state: &mut State| {
+                    state.new_event = Some(NewEventForm::new(day));
+                })
+                .boxed()
+            } else {
+                label(day.day().to_string())
+                    .brush(AlphaColor::WHITE.with_alpha(0.4))
+                    .boxed()
+            };
+            cells.push(number.grid_pos(column as i32, top).boxed());
+        }
+
+        let (segments, overflow) = layout(&data.events, week_start);
+        for (lane, segment) in &segments {
+            let params = GridParams::new(
+                segment.from as i32,
+                top + 1 + *lane as i32,
+                (segment.to - segment.from + 1) as i32,
+                1,
+            );
+            let color = event_color(segment.event, &data.calendars);
+            cells.push(chip(segment, color).grid_item(params).boxed());
+        }
+        for (column, hidden) in overflow.into_iter().enumerate() {
+            if hidden > 0 {
+                let more = label(format!("+{hidden} more"));
+                let row = top + 1 + LANES as i32;
+                cells.push(more.grid_pos(column as i32, row).boxed());
+            }
+        }
+    }
+    grid(cells, 7, (WEEKS * ROWS_PER_WEEK) as i32).spacing(2.)
+}
+
+/// The days of the week, monday first, as column headings
+pub fn weekday_headings() -> impl WidgetView<State> + use<> {
+    let mut day = Weekday::Monday;
+    let mut headings = vec![];
+    for _ in 0..7 {
+        headings.push(label(day.to_string()).flex(1.));
+        day = day.next();
+    }
+    flex(headings).direction(Axis::Horizontal)
+}
+
+#[cfg(test)]
+mod tests {
+    use plannr::data::{Event, EventInterval, Zone};
+    use time::{Date, Duration, Month};
+
+    use super::{event_days, layout};
+
+    fn day(day: u8) -> Date {
+        Date::from_calendar_date(2025, Month::July, day).unwrap()
+    }
+
+    fn event(label: &str, interval: EventInterval) -> Event {
+        Event {
+            id: 0,
+            calendar_id: 1,
+            label: label.into(),
+            interval,
+        }
+    }
+
+    fn all_day(label: &str, start: Date, end: Date) -> Event {
+        event(label, EventInterval::new_date(start, end).unwrap())
+    }
+
+    #[test]
+    fn days_of_events() {
+        let event_days = |interval| event_days(&event("Event", interval));
+        assert_eq!(
+            event_days(EventInterval::new_date(day(3), day(4)).unwrap()),
+            (day(3), day(4))
+        );
+
+        // an event ending at midnight doesn't take up the next day
+        let start = day(3).with_hms(9, 0, 0).unwrap().as_utc();
+        let midnight = day(4).midnight().as_utc();
+        assert_eq!(
+            event_days(EventInterval::new_datetime(start, midnight).unwrap()),
+            (day(3), day(3))
+        );
+
+        // 23:30 UTC is already the next day in Berlin
+        let start = day(3).with_hms(23, 30, 0).unwrap().as_utc();
+        let end = day(4).with_hms(0, 30, 0).unwrap().as_utc();
+        let zone = Zone::new("Europe/Berlin").unwrap();
+        assert_eq!(
+            event_days(EventInterval::new_zoned(start, end, zone).unwrap()),
+            (day(4), day(4))
+        );
+    }
+
+    #[test]
+    fn lanes() {
+        // the week starting on monday 2025-06-30
+        let monday = day(1).previous_day().unwrap();
+        let events = vec![
+            all_day("Holiday", monday - Duration::days(2), day(2)),
+            all_day("A", day(3), day(3)),
+            all_day("B", day(3), day(3)),
+            all_day("C", day(3), day(3)),
+            all_day("Trip", day(5), day(8)),
+            all_day("Next week", day(7), day(8)),
+        ];
+        let (placed, overflow) = layout(&events, monday);
+        let placed = placed
+            .iter()
+            .map(|(lane, segment)| {
+                let label = segment.event.label.as_str();
+                (label, *lane, segment.from, segment.to, segment.continued)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            placed,
+            [
+                ("Holiday", 0, 0, 2, (true, false)),
+                ("A", 0, 3, 3, (false, false)),
+                ("B", 1, 3, 3, (false, false)),
+                ("C", 2, 3, 3, (false, false)),
+                ("Trip", 0, 5, 6, (false, true)),
+            ]
+        );
+        assert_eq!(overflow, [0; 7]);
+
+        // a fourth event on the thursday doesn't fit
+        let mut events = events;
+        events.push(all_day("D", day(3), day(3)));
+        let (placed, overflow) = layout(&events, monday);
+        assert_eq!(placed.len(), 5);
+        assert_eq!(overflow, [0, 0, 0, 1, 0, 0, 0]);
+    }
+}
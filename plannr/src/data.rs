@@ -3,15 +3,66 @@ use serde::{Deserialize, Serialize};
 mod interval;
 pub use interval::{EventInterval, EventIntervalError, EventIntervalRef};
 
+mod zone;
+pub use zone::{LocalOutOfRange, UnknownZone, Zone};
+
 pub type RowID = i64;
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, cli_table::Table)]
 pub struct Calendar {
     pub id: RowID,
     pub name: String,
+    /// `#rrggbb`, if one has been chosen. See [`Calendar::rgb`]
+    #[table(display_fn = "display_color")]
+    pub color: Option<String>,
+}
+
+/// Colors for calendars without one, from the Tableau 10 palette
+const DEFAULT_COLORS: [[u8; 3]; 10] = [
+    [0x4e, 0x79, 0xa7],
+    [0xf2, 0x8e, 0x2b],
+    [0xe1, 0x57, 0x59],
+    [0x76, 0xb7, 0xb2],
+    [0x59, 0xa1, 0x4f],
+    [0xed, 0xc9, 0x48],
+    [0xb0, 0x7a, 0xa1],
+    [0xff, 0x9d, 0xa7],
+    [0x9c, 0x75, 0x5f],
+    [0xba, 0xb0, 0xac],
+];
+
+impl Calendar {
+    /// The calendar's color, or else its [default color](default_color)
+    pub fn rgb(&self) -> [u8; 3] {
+        self.color
+            .as_deref()
+            .and_then(parse_color)
+            .unwrap_or_else(|| default_color(self.id))
+    }
+}
+
+/// A color for the calendar with ID `id`, always the same for the same ID
+pub fn default_color(id: RowID) -> [u8; 3] {
+    // Fibonacci hashing, so that nearby IDs are spread over the palette
+    let hash = (id as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    // scale the top 32 bits down to an index, rather than taking a remainder which would
+    // only use the low bits
+    let index = ((hash >> 32) * DEFAULT_COLORS.len() as u64) >> 32;
+    DEFAULT_COLORS[index as usize]
+}
+
+/// Parse a `#rrggbb` color
+pub fn parse_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |at: usize| u8::from_str_radix(hex.get(at..at + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn display_color(color: &Option<String>) -> impl std::fmt::Display + '_ {
+    color.as_deref().unwrap_or("")
 }
 
-#[derive(Debug, Serialize, Deserialize, cli_table::Table)]
+#[derive(Debug, Clone, Serialize, Deserialize, cli_table::Table)]
 pub struct Event {
     pub id: RowID,
     pub calendar_id: RowID,
@@ -27,8 +78,9 @@ impl Event {
         start_time: i64,
         end_time: i64,
         date_only: bool,
+        tzid: Option<String>,
     ) -> Result<Self, sqlx::Error> {
-        let interval = EventInterval::from_db(start_time, end_time, date_only)
+        let interval = EventInterval::from_db(start_time, end_time, date_only, tzid.as_deref())
             .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
         Ok(Event {
             id,
@@ -38,3 +90,32 @@ impl Event {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Calendar, DEFAULT_COLORS, default_color, parse_color};
+
+    #[test]
+    fn default_colors() {
+        // the same every time
+        assert_eq!(default_color(1), default_color(1));
+        assert_eq!(default_color(1), [0xb0, 0x7a, 0xa1]);
+        // consecutive IDs get different colors
+        for id in 1..20 {
+            assert_ne!(default_color(id), default_color(id + 1));
+        }
+        assert!(DEFAULT_COLORS.contains(&default_color(-5)));
+
+        let mut calendar = Calendar {
+            id: 1,
+            name: "work".into(),
+            color: Some("#0080FF".into()),
+        };
+        assert_eq!(calendar.rgb(), [0x00, 0x80, 0xff]);
+        // invalid colors are ignored
+        calendar.color = Some("blue".into());
+        assert_eq!(calendar.rgb(), default_color(1));
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("#12345é"), None);
+    }
+}
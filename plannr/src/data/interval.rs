@@ -1,12 +1,21 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de, ser};
 use std::{cmp, fmt, ops};
 use thiserror::Error;
-use time::{Date, UtcDateTime, error::ComponentRange};
+use time::{
+    Date, UtcDateTime, error::ComponentRange, format_description::well_known::Rfc3339,
+    macros::format_description,
+};
+
+use super::{UnknownZone, Zone};
 
 type Result<T, E = EventIntervalError> = std::result::Result<T, E>;
 
 /// Type representing the start and end time of an event
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+///
+/// Serialized as `{"date_only": bool, "start": string, "end": string}`, where the start and
+/// end are `YYYY-MM-DD` dates for date-only intervals and RFC 3339 UTC times otherwise. Zoned
+/// intervals also have a `"tzid"`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct EventInterval {
     inner: EventIntervalRef,
 }
@@ -27,6 +36,12 @@ impl EventInterval {
         Self::new_checked(inner)
     }
 
+    /// Create datetime interval from start and end times, to be shown in timezone `zone`.
+    pub fn new_zoned(start: UtcDateTime, end: UtcDateTime, zone: Zone) -> Result<Self> {
+        let inner = EventIntervalRef::ZonedDateTime { start, end, zone };
+        Self::new_checked(inner)
+    }
+
     /// Convert from DB representation to typed repr.
     ///
     /// Should never fail because only validated data should be inserted into DB
@@ -34,8 +49,9 @@ impl EventInterval {
         start_time: i64,
         end_time: i64,
         date_only: bool,
+        tzid: Option<&str>,
     ) -> Result<Self, EventIntervalError> {
-        let inner = EventIntervalRef::from_db(start_time, end_time, date_only)?;
+        let inner = EventIntervalRef::from_db(start_time, end_time, date_only, tzid)?;
         Self::new_checked(inner)
     }
 
@@ -60,6 +76,64 @@ impl TryFrom<EventIntervalRef> for EventInterval {
     }
 }
 
+/// Serialized form of [`EventInterval`]
+#[derive(Serialize, Deserialize)]
+struct IntervalRepr {
+    date_only: bool,
+    start: String,
+    end: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tzid: Option<Zone>,
+}
+
+impl Serialize for EventInterval {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let date_desc = format_description!("[year]-[month]-[day]");
+        let repr = match self.inner {
+            EventIntervalRef::Date { start, end } => IntervalRepr {
+                date_only: true,
+                start: start.format(date_desc).map_err(ser::Error::custom)?,
+                end: end.format(date_desc).map_err(ser::Error::custom)?,
+                tzid: None,
+            },
+            EventIntervalRef::DateTime { start, end } => IntervalRepr {
+                date_only: false,
+                start: start.format(&Rfc3339).map_err(ser::Error::custom)?,
+                end: end.format(&Rfc3339).map_err(ser::Error::custom)?,
+                tzid: None,
+            },
+            EventIntervalRef::ZonedDateTime { start, end, zone } => IntervalRepr {
+                date_only: false,
+                start: start.format(&Rfc3339).map_err(ser::Error::custom)?,
+                end: end.format(&Rfc3339).map_err(ser::Error::custom)?,
+                tzid: Some(zone),
+            },
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for EventInterval {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let date_desc = format_description!("[year]-[month]-[day]");
+        let repr = IntervalRepr::deserialize(deserializer)?;
+        let inner = if repr.date_only {
+            EventIntervalRef::Date {
+                start: Date::parse(&repr.start, date_desc).map_err(de::Error::custom)?,
+                end: Date::parse(&repr.end, date_desc).map_err(de::Error::custom)?,
+            }
+        } else {
+            let start = UtcDateTime::parse(&repr.start, &Rfc3339).map_err(de::Error::custom)?;
+            let end = UtcDateTime::parse(&repr.end, &Rfc3339).map_err(de::Error::custom)?;
+            match repr.tzid {
+                Some(zone) => EventIntervalRef::ZonedDateTime { start, end, zone },
+                None => EventIntervalRef::DateTime { start, end },
+            }
+        };
+        Self::new_checked(inner).map_err(de::Error::custom)
+    }
+}
+
 impl fmt::Display for EventInterval {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt::Display::fmt(&self.inner, f)
@@ -70,6 +144,8 @@ impl fmt::Display for EventInterval {
 pub enum EventIntervalError {
     #[error("{0}")]
     Inner(#[from] ComponentRange),
+    #[error(transparent)]
+    UnknownZone(#[from] UnknownZone),
     #[error("end date {end} is before start date {start}")]
     NegativeDateRange { start: Date, end: Date },
     #[error("end time {end} is before start time {start}")]
@@ -92,23 +168,34 @@ pub enum EventIntervalRef {
         start: UtcDateTime,
         end: UtcDateTime,
     },
+    /// A datetime interval created in timezone `zone`, which it should be shown in
+    ZonedDateTime {
+        start: UtcDateTime,
+        end: UtcDateTime,
+        zone: Zone,
+    },
 }
 
 /// Order is only chronological for timezone UTC, as date-only events
 /// are interpreted differently in different timezones
 ///
-/// date is (arbitrarily) before datetime
+/// date is (arbitrarily) before datetime, and intervals at the same times are ordered by zone
+/// name, with no zone first
 impl Ord for EventIntervalRef {
     fn cmp(&self, other: &Self) -> cmp::Ordering {
         let res = self.to_datetime().cmp(&other.to_datetime());
         if res != cmp::Ordering::Equal {
             return res;
         }
-        match (self.is_date_only(), other.is_date_only()) {
+        let res = match (self.is_date_only(), other.is_date_only()) {
             (true, true) | (false, false) => cmp::Ordering::Equal,
             (true, false) => cmp::Ordering::Less,
             (false, true) => cmp::Ordering::Greater,
-        }
+        };
+        res.then_with(|| {
+            let name = |interval: &Self| interval.zone().map(|zone| zone.name());
+            name(self).cmp(&name(other))
+        })
     }
 }
 
@@ -123,15 +210,45 @@ impl EventIntervalRef {
         start_time: i64,
         end_time: i64,
         date_only: bool,
-    ) -> Result<Self, ComponentRange> {
+        tzid: Option<&str>,
+    ) -> Result<Self, EventIntervalError> {
         if date_only {
             let start = UtcDateTime::from_unix_timestamp(start_time)?.date();
             let end = UtcDateTime::from_unix_timestamp(end_time)?.date();
-            Ok(Self::Date { start, end })
-        } else {
-            let start = UtcDateTime::from_unix_timestamp(start_time)?;
-            let end = UtcDateTime::from_unix_timestamp(end_time)?;
-            Ok(Self::DateTime { start, end })
+            return Ok(Self::Date { start, end });
+        }
+        let start = UtcDateTime::from_unix_timestamp(start_time)?;
+        let end = UtcDateTime::from_unix_timestamp(end_time)?;
+        Ok(match tzid {
+            Some(tzid) => Self::ZonedDateTime {
+                start,
+                end,
+                zone: Zone::new(tzid)?,
+            },
+            None => Self::DateTime { start, end },
+        })
+    }
+
+    /// The DB representation: unix timestamps of the start and end, and whether the interval
+    /// is date-only. The timezone is stored separately, see [`zone`](Self::zone).
+    ///
+    /// Dates are stored as midnight UTC, so a whole number of days since the epoch, and
+    /// [`from_db`](Self::from_db) takes the UTC date of the timestamps. Both sides use UTC so
+    /// the date doesn't depend on any local timezone.
+    pub(crate) fn to_db_parts(self) -> (i64, i64, bool) {
+        let (start, end) = self.to_datetime();
+        (
+            start.unix_timestamp(),
+            end.unix_timestamp(),
+            self.is_date_only(),
+        )
+    }
+
+    /// The timezone of zoned intervals
+    pub fn zone(&self) -> Option<Zone> {
+        match self {
+            EventIntervalRef::ZonedDateTime { zone, .. } => Some(*zone),
+            _ => None,
         }
     }
 
@@ -141,7 +258,36 @@ impl EventIntervalRef {
                 start.with_hms(0, 0, 0).unwrap().as_utc(),
                 end.with_hms(0, 0, 0).unwrap().as_utc(),
             ),
-            EventIntervalRef::DateTime { start, end } => (*start, *end),
+            EventIntervalRef::DateTime { start, end }
+            | EventIntervalRef::ZonedDateTime { start, end, .. } => (*start, *end),
+        }
+    }
+
+    /// Whether the two intervals share any time.
+    ///
+    /// Intervals that only touch (one ends when the other starts) don't overlap. Date-only
+    /// intervals last until the end of their (inclusive) end date, in UTC as for ordering.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let (start, end) = self.exclusive_bounds();
+        let (other_start, other_end) = other.exclusive_bounds();
+        start < other_end && other_start < end
+    }
+
+    /// Whether `at` is in the interval: at or after the start and before the end.
+    ///
+    /// As for [`overlaps`](Self::overlaps), date-only intervals include all of their end
+    /// date.
+    pub fn contains(&self, at: UtcDateTime) -> bool {
+        let (start, end) = self.exclusive_bounds();
+        start <= at && at < end
+    }
+
+    /// The start and (exclusive) end of the interval, with dates as midnight UTC
+    pub(crate) fn exclusive_bounds(&self) -> (UtcDateTime, UtcDateTime) {
+        let (start, end) = self.to_datetime();
+        match self {
+            EventIntervalRef::Date { .. } => (start, end + time::Duration::DAY),
+            _ => (start, end),
         }
     }
 
@@ -156,7 +302,8 @@ impl EventIntervalRef {
                     return Err(EventIntervalError::NegativeDateRange { start, end });
                 }
             }
-            EventIntervalRef::DateTime { start, end } => {
+            EventIntervalRef::DateTime { start, end }
+            | EventIntervalRef::ZonedDateTime { start, end, .. } => {
                 if end < start {
                     return Err(EventIntervalError::NegativeDateTimeRange { start, end });
                 }
@@ -179,7 +326,105 @@ impl fmt::Display for EventIntervalRef {
                 f.write_str(" - ")?;
                 fmt::Display::fmt(end, f)?;
             }
+            EventIntervalRef::ZonedDateTime { start, end, zone } => {
+                fmt::Display::fmt(&zone.to_local(*start), f)?;
+                f.write_str(" - ")?;
+                fmt::Display::fmt(&zone.to_local(*end), f)?;
+                write!(f, " ({zone})")?;
+            }
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    use time::{Date, Month, UtcDateTime};
+
+    use super::EventInterval;
+    use crate::data::Zone;
+
+    fn day() -> Date {
+        Date::from_calendar_date(2025, Month::July, 3).unwrap()
+    }
+
+    fn at(hour: u8) -> UtcDateTime {
+        day().with_hms(hour, 0, 0).unwrap().as_utc()
+    }
+
+    #[test]
+    fn overlaps() {
+        let morning = EventInterval::new_datetime(at(9), at(12)).unwrap();
+        let afternoon = EventInterval::new_datetime(at(12), at(17)).unwrap();
+        let meeting = EventInterval::new_datetime(at(10), at(11)).unwrap();
+        // touching isn't overlapping
+        assert!(!morning.overlaps(&afternoon));
+        assert!(!afternoon.overlaps(&morning));
+        // fully contained
+        assert!(morning.overlaps(&meeting));
+        assert!(meeting.overlaps(&morning));
+        assert!(!meeting.overlaps(&afternoon));
+
+        // a date-only interval lasts all of its end date
+        let all_day = EventInterval::new_date(day(), day()).unwrap();
+        assert!(all_day.overlaps(&afternoon));
+        let next_day = day().next_day().unwrap();
+        let tomorrow = EventInterval::new_date(next_day, next_day).unwrap();
+        assert!(!all_day.overlaps(&tomorrow));
+    }
+
+    #[test]
+    fn db_round_trip() {
+        // clocks went forward in Europe on 2025-03-30 and in the US on 2025-03-09
+        for (start, end) in [(30, 30), (29, 31), (8, 9)] {
+            let date = |day| Date::from_calendar_date(2025, Month::March, day).unwrap();
+            let interval = EventInterval::new_date(date(start), date(end)).unwrap();
+            let (start_time, end_time, date_only) = interval.to_db_parts();
+            assert!(date_only);
+            // whole days, so no time of day to be shifted by a timezone
+            assert_eq!(start_time % 86_400, 0);
+            assert_eq!(end_time % 86_400, 0);
+            let read = EventInterval::from_db(start_time, end_time, date_only, None).unwrap();
+            assert_eq!(read, interval);
+        }
+
+        let interval = EventInterval::new_datetime(at(9), at(12)).unwrap();
+        let (start_time, end_time, date_only) = interval.to_db_parts();
+        assert_eq!(start_time, at(9).unix_timestamp());
+        assert!(!date_only);
+        let read = EventInterval::from_db(start_time, end_time, date_only, None).unwrap();
+        assert_eq!(read, interval);
+    }
+
+    #[test]
+    fn order_by_zone() {
+        let utc = EventInterval::new_datetime(at(9), at(12)).unwrap();
+        let zoned = |name| EventInterval::new_zoned(at(9), at(12), Zone::new(name).unwrap());
+        let (berlin, paris) = (
+            zoned("Europe/Berlin").unwrap(),
+            zoned("Europe/Paris").unwrap(),
+        );
+        // consistent with `Eq`
+        assert_eq!(berlin.cmp(&paris), Ordering::Less);
+        assert_eq!(utc.cmp(&berlin), Ordering::Less);
+        assert_eq!(berlin.cmp(&berlin), Ordering::Equal);
+        // the instant still comes first
+        let later = EventInterval::new_datetime(at(10), at(12)).unwrap();
+        assert_eq!(paris.cmp(&later), Ordering::Less);
+    }
+
+    #[test]
+    fn contains() {
+        let morning = EventInterval::new_datetime(at(9), at(12)).unwrap();
+        assert!(morning.contains(at(9)));
+        assert!(morning.contains(at(11)));
+        // the end is exclusive
+        assert!(!morning.contains(at(12)));
+        assert!(!morning.contains(at(8)));
+
+        let all_day = EventInterval::new_date(day(), day()).unwrap();
+        assert!(all_day.contains(at(23)));
+        assert!(!all_day.contains(day().next_day().unwrap().midnight().as_utc()));
+    }
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use std::fmt;
+use thiserror::Error;
+use time::{Duration, OffsetDateTime, PrimitiveDateTime, UtcDateTime, UtcOffset};
+
+/// An IANA timezone from the bundled timezone database, e.g. `Europe/Berlin`
+///
+/// Serialized as its name.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Zone {
+    name: &'static str,
+}
+
+#[derive(Debug, Error)]
+#[error("unknown timezone {0}")]
+pub struct UnknownZone(pub String);
+
+#[derive(Debug, Error)]
+#[error("{0} in {1} is out of range")]
+pub struct LocalOutOfRange(pub PrimitiveDateTime, pub Zone);
+
+impl Zone {
+    /// Look up timezone `name` (ignoring case).
+    pub fn new(name: &str) -> Result<Self, UnknownZone> {
+        match tzdb::TZ_NAMES
+            .iter()
+            .find(|zone| zone.eq_ignore_ascii_case(name))
+        {
+            Some(name) => Ok(Self { name }),
+            None => Err(UnknownZone(name.to_owned())),
+        }
+    }
+
+    /// The canonical name of the timezone
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The offset from UTC in this timezone at `at`
+    pub fn offset_at(&self, at: UtcDateTime) -> UtcOffset {
+        // Unwraps: names come from the database, and the offsets it contains are all valid
+        let timezone = tzdb::tz_by_name(self.name).unwrap();
+        let offset = timezone
+            .find_local_time_type(at.unix_timestamp())
+            .map_or(0, |local_time| local_time.ut_offset());
+        UtcOffset::from_whole_seconds(offset).unwrap()
+    }
+
+    /// The local time in this timezone at `at`
+    pub fn to_local(&self, at: UtcDateTime) -> OffsetDateTime {
+        at.to_offset(self.offset_at(at))
+    }
+
+    /// The instant the wall-clock time `local` happens in this timezone.
+    ///
+    /// Times skipped when the clocks go forward use the offset from before the change, and
+    /// times repeated when they go back are taken the first time. Fails if the instant is
+    /// outside the range of [`UtcDateTime`].
+    pub fn from_local(&self, local: PrimitiveDateTime) -> Result<UtcDateTime, LocalOutOfRange> {
+        // the offset a day either side is the one before and after any change around `local`
+        let guess = local.as_utc();
+        let before = self.offset_at(guess.checked_sub(Duration::DAY).unwrap_or(guess));
+        let after = self.offset_at(guess.checked_add(Duration::DAY).unwrap_or(guess));
+        let at = |offset: UtcOffset| {
+            guess
+                .checked_sub(Duration::seconds(offset.whole_seconds().into()))
+                .ok_or(LocalOutOfRange(local, *self))
+        };
+        for offset in [before, after] {
+            let utc = at(offset)?;
+            if self.offset_at(utc) == offset {
+                return Ok(utc);
+            }
+        }
+        at(before)
+    }
+}
+
+impl fmt::Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+impl Serialize for Zone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for Zone {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Zone::new(&name).map_err(de::Error::custom)
+    }
+}
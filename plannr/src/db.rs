@@ -1,13 +1,44 @@
 use std::borrow::Cow;
 
 use anyhow::bail;
+use icalendar::types::{DateOrDateTime, Recur};
 use oauth2::{EmptyExtraTokenFields, StandardTokenResponse, basic::BasicTokenType};
-use sqlx::{SqliteConnection, SqliteExecutor};
+use sqlx::{QueryBuilder, Sqlite, SqliteConnection, SqliteExecutor};
+#[cfg(any(test, feature = "testing"))]
+use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use time::UtcDateTime;
 
-use crate::data::{Calendar, Event, EventInterval, EventIntervalRef, RowID};
+use crate::{
+    data::{Calendar, Event, EventInterval, RowID, parse_color},
+    ical,
+};
+
+/// A pool for a new in-memory database with all migrations applied, for tests.
+///
+/// The pool has a single connection that is never closed, because every connection to
+/// `sqlite::memory:` gets its own database.
+///
+/// # Panics
+///
+/// If the database can't be created or migrated.
+#[cfg(any(test, feature = "testing"))]
+pub async fn test_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .expect("connect to in-memory database");
+    sqlx::migrate!("../migrations")
+        .run(&pool)
+        .await
+        .expect("migrate in-memory database");
+    pool
+}
 
 pub async fn get_calendars(exec: impl SqliteExecutor<'_>) -> sqlx::Result<Vec<Calendar>> {
-    sqlx::query_as!(Calendar, "SELECT id, name FROM calendars")
+    sqlx::query_as!(Calendar, "SELECT id, name, color FROM calendars")
         .fetch_all(exec)
         .await
 }
@@ -18,32 +49,24 @@ pub async fn get_calendar(
 ) -> sqlx::Result<Option<Calendar>> {
     sqlx::query_as!(
         Calendar,
-        "SELECT id, name FROM calendars WHERE id = ?",
+        "SELECT id, name, color FROM calendars WHERE id = ?",
         calendar_id
     )
     .fetch_optional(exec)
     .await
 }
 
+/// Find the calendar called `name`, allowing any calendar whose name contains `name` if it is
+/// the only one.
 pub async fn find_calendar(name: &str, exec: impl SqliteExecutor<'_>) -> anyhow::Result<Calendar> {
-    let like_input = format!("%{}%", escape_like(name));
-    tracing::debug!("Input to LIKE statment: `{like_input}`");
-    let calendars = sqlx::query_as!(
-        Calendar,
-        "SELECT id, name FROM calendars WHERE name LIKE ?",
-        like_input
-    )
-    .fetch_all(exec)
-    .await?;
+    let calendars = search_calendars(name, exec).await?;
     match calendars.len() {
         0 => bail!("no calendars matched `{name}`"),
         1 => Ok(calendars.into_iter().next().unwrap()),
         _ => {
-            if let Some(calendar) = calendars
-                .into_iter()
-                .find(|cal| cal.name.eq_ignore_ascii_case(name))
-            {
-                Ok(calendar)
+            // an exact match sorts first
+            if calendars[0].name.eq_ignore_ascii_case(name) {
+                Ok(calendars.into_iter().next().unwrap())
             } else {
                 bail!("`{name}` is ambiguous as calendar name")
             }
@@ -51,23 +74,76 @@ pub async fn find_calendar(name: &str, exec: impl SqliteExecutor<'_>) -> anyhow:
     }
 }
 
+/// All calendars whose name contains `name`, ignoring (ASCII) case.
+///
+/// The closest matches come first: an exact match, then names starting with `name`, then
+/// shorter names.
+pub async fn search_calendars(
+    name: &str,
+    exec: impl SqliteExecutor<'_>,
+) -> anyhow::Result<Vec<Calendar>> {
+    let like_input = format!("%{}%", escape_like(name));
+    tracing::debug!("Input to LIKE statment: `{like_input}`");
+    let mut calendars = sqlx::query_as!(
+        Calendar,
+        r"SELECT id, name, color FROM calendars WHERE name LIKE ? ESCAPE '\'",
+        like_input
+    )
+    .fetch_all(exec)
+    .await?;
+    let name = name.to_ascii_lowercase();
+    calendars.sort_by_cached_key(|calendar| {
+        let candidate = calendar.name.to_ascii_lowercase();
+        (
+            candidate != name,
+            !candidate.starts_with(&name),
+            candidate.len(),
+            candidate,
+        )
+    });
+    Ok(calendars)
+}
+
 pub async fn new_calendar(name: &str, exec: impl SqliteExecutor<'_>) -> sqlx::Result<Calendar> {
     sqlx::query_as!(
         Calendar,
-        "INSERT INTO calendars (name) VALUES (?1) RETURNING id, name",
+        "INSERT INTO calendars (name) VALUES (?1) RETURNING id, name, color",
         name
     )
     .fetch_one(exec)
     .await
 }
 
+/// Set the color the UI shows a calendar's events in, as `#rrggbb`, or `None` to go back to
+/// the default.
+pub async fn set_calendar_color(
+    calendar_id: RowID,
+    color: Option<&str>,
+    exec: impl SqliteExecutor<'_>,
+) -> anyhow::Result<()> {
+    if let Some(color) = color.filter(|color| parse_color(color).is_none()) {
+        bail!("expected a color like `#rrggbb`, found `{color}`");
+    }
+    let result = sqlx::query!(
+        "UPDATE calendars SET color = ? WHERE id = ?",
+        color,
+        calendar_id
+    )
+    .execute(exec)
+    .await?;
+    if result.rows_affected() == 0 {
+        bail!("no calendar with ID {calendar_id}");
+    }
+    Ok(())
+}
+
 pub async fn get_events(
     calendar_id: Option<RowID>,
     exec: impl SqliteExecutor<'_>,
 ) -> anyhow::Result<Vec<Event>> {
     Ok(if let Some(calendar_id) = calendar_id {
         // TODO if we use a custom type for raw event we could share code between branches
-        let raw = sqlx::query!("SELECT id, calendar_id, label, start_time, end_time, date_only FROM events WHERE calendar_id = ?", calendar_id)
+        let raw = sqlx::query!("SELECT id AS \"id!\", calendar_id, label, start_time, end_time, date_only, tzid FROM events WHERE calendar_id = ?", calendar_id)
             .fetch_all(exec)
             .await?;
         raw.into_iter()
@@ -79,12 +155,13 @@ pub async fn get_events(
                     row.start_time,
                     row.end_time,
                     row.date_only,
+                    row.tzid,
                 )
             })
             .collect::<Result<Vec<_>, sqlx::Error>>()
     } else {
         let raw = sqlx::query!(
-            "SELECT id, calendar_id, label, start_time, end_time, date_only FROM events"
+            "SELECT id, calendar_id, label, start_time, end_time, date_only, tzid FROM events"
         )
         .fetch_all(exec)
         .await?;
@@ -97,18 +174,54 @@ pub async fn get_events(
                     row.start_time,
                     row.end_time,
                     row.date_only,
+                    row.tzid,
                 )
             })
             .collect::<Result<Vec<_>, sqlx::Error>>()
     }?)
 }
 
+/// All events whose label contains `query`, ignoring (ASCII) case, sorted chronologically.
+pub async fn find_events_by_label(
+    query: &str,
+    exec: impl SqliteExecutor<'_>,
+) -> anyhow::Result<Vec<Event>> {
+    let like_input = format!("%{}%", escape_like(query));
+    let rows = sqlx::query!(
+        r"SELECT id, calendar_id, label, start_time, end_time, date_only, tzid FROM events
+        WHERE label LIKE ? ESCAPE '\'",
+        like_input
+    )
+    .fetch_all(exec)
+    .await?;
+    let mut events = rows
+        .into_iter()
+        .map(|row| {
+            Event::from_db(
+                row.id,
+                row.calendar_id,
+                row.label,
+                row.start_time,
+                row.end_time,
+                row.date_only,
+                row.tzid,
+            )
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()?;
+    events.sort_by(|left, right| {
+        left.interval
+            .cmp(&right.interval)
+            .then(left.label.cmp(&right.label))
+    });
+    Ok(events)
+}
+
 pub async fn get_events_for_calendar(
     exec: impl SqliteExecutor<'_>,
     calendar_id: RowID,
 ) -> anyhow::Result<Vec<Event>> {
     let rows =
-        sqlx::query!("SELECT id, calendar_id, label, start_time, end_time, date_only FROM events WHERE calendar_id = ?", calendar_id)
+        sqlx::query!("SELECT id AS \"id!\", calendar_id, label, start_time, end_time, date_only, tzid FROM events WHERE calendar_id = ?", calendar_id)
             .fetch_all(exec)
             .await?;
     Ok(rows
@@ -121,19 +234,158 @@ pub async fn get_events_for_calendar(
                 row.start_time,
                 row.end_time,
                 row.date_only,
+                row.tzid,
             )
         })
         .collect::<Result<Vec<_>, sqlx::Error>>()?)
 }
 
+/// The most parameters SQLite allows in one statement (`SQLITE_MAX_VARIABLE_NUMBER`)
+const MAX_PARAMS: usize = 32_766;
+
+/// Get the events of all of `calendar_ids`, sorted chronologically then by label.
 pub async fn get_events_for_calendars(
     exec: impl SqliteExecutor<'_> + Copy,
     calendar_ids: &[RowID],
 ) -> anyhow::Result<Vec<Event>> {
     let mut events = vec![];
-    for cal_id in calendar_ids {
-        events.extend(get_events_for_calendar(exec, *cal_id).await?);
+    // one query unless there are too many ids for a single statement
+    for calendar_ids in calendar_ids.chunks(MAX_PARAMS) {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT id, calendar_id, label, start_time, end_time, date_only, tzid FROM events \
+            WHERE calendar_id IN (",
+        );
+        let mut ids = query.separated(", ");
+        for calendar_id in calendar_ids {
+            ids.push_bind(*calendar_id);
+        }
+        // the same order as `EventInterval`: date-only events first on a tie, then events
+        // without a timezone, then by timezone
+        query.push(") ORDER BY start_time, end_time, date_only DESC, tzid, label");
+        let rows: Vec<EventRow> = query.build_query_as().fetch_all(exec).await?;
+        for row in rows {
+            events.push(Event::from_db(
+                row.id,
+                row.calendar_id,
+                row.label,
+                row.start_time,
+                row.end_time,
+                row.date_only,
+                row.tzid,
+            )?);
+        }
+    }
+    if calendar_ids.len() > MAX_PARAMS {
+        events.sort_by(|left, right| {
+            left.interval
+                .cmp(&right.interval)
+                .then(left.label.cmp(&right.label))
+        });
+    }
+    Ok(events)
+}
+
+/// A row of `events`, for queries that can't use `query!`
+#[derive(sqlx::FromRow)]
+struct EventRow {
+    id: RowID,
+    calendar_id: RowID,
+    label: String,
+    start_time: i64,
+    end_time: i64,
+    date_only: bool,
+    tzid: Option<String>,
+}
+
+/// Get the events that start before `to` and end after `from`, optionally only for one
+/// calendar.
+///
+/// Date-only events last until the end of their (inclusive) end date, as for
+/// [`EventIntervalRef::overlaps`](crate::data::EventIntervalRef::overlaps). Events are sorted
+/// chronologically, then by label.
+pub async fn get_events_in_range(
+    calendar_id: Option<RowID>,
+    from: UtcDateTime,
+    to: UtcDateTime,
+    exec: impl SqliteExecutor<'_>,
+) -> anyhow::Result<Vec<Event>> {
+    if to < from {
+        bail!("end of range {to} is before start {from}");
+    }
+    let from = from.unix_timestamp();
+    let to = to.unix_timestamp();
+    let rows = sqlx::query!(
+        "SELECT id, calendar_id, label, start_time, end_time, date_only, tzid FROM events \
+        WHERE start_time < ?1 AND end_time + IIF(date_only, 86400, 0) > ?2 \
+        AND (?3 IS NULL OR calendar_id = ?3)",
+        to,
+        from,
+        calendar_id
+    )
+    .fetch_all(exec)
+    .await?;
+    let mut events = rows
+        .into_iter()
+        .map(|row| {
+            Event::from_db(
+                row.id,
+                row.calendar_id,
+                row.label,
+                row.start_time,
+                row.end_time,
+                row.date_only,
+                row.tzid,
+            )
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()?;
+    events.sort_by(|left, right| {
+        left.interval
+            .cmp(&right.interval)
+            .then(left.label.cmp(&right.label))
+    });
+    Ok(events)
+}
+
+/// Get the events of `calendar_id` that start before `to` and end after `from`.
+///
+/// Like [`get_events_in_range`] for a single calendar, but uses the
+/// `(calendar_id, start_time)` index. Events are sorted chronologically, then by label.
+pub async fn get_events_for_calendar_in_range(
+    calendar_id: RowID,
+    from: UtcDateTime,
+    to: UtcDateTime,
+    exec: impl SqliteExecutor<'_>,
+) -> anyhow::Result<Vec<Event>> {
+    if to < from {
+        bail!("end of range {to} is before start {from}");
     }
+    let from = from.unix_timestamp();
+    let to = to.unix_timestamp();
+    // `id!`: sqlx can't tell `id` is never null when the query goes through an index
+    let rows = sqlx::query!(
+        "SELECT id AS \"id!\", calendar_id, label, start_time, end_time, date_only, tzid FROM events \
+        WHERE calendar_id = ?1 AND start_time < ?2 \
+        AND end_time + IIF(date_only, 86400, 0) > ?3",
+        calendar_id,
+        to,
+        from
+    )
+    .fetch_all(exec)
+    .await?;
+    let mut events = rows
+        .into_iter()
+        .map(|row| {
+            Event::from_db(
+                row.id,
+                row.calendar_id,
+                row.label,
+                row.start_time,
+                row.end_time,
+                row.date_only,
+                row.tzid,
+            )
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()?;
     events.sort_by(|left, right| {
         left.interval
             .cmp(&right.interval)
@@ -141,31 +393,83 @@ pub async fn get_events_for_calendars(
     });
     Ok(events)
 }
+
 pub async fn new_event(
     calendar_id: RowID,
     label: &str,
     interval: EventInterval,
     exec: impl SqliteExecutor<'_>,
 ) -> anyhow::Result<Event> {
-    let (start, end, date_only) = match &*interval {
-        EventIntervalRef::Date { start, end } => (
-            start.with_hms(0, 0, 0).unwrap().as_utc().unix_timestamp(),
-            end.with_hms(0, 0, 0).unwrap().as_utc().unix_timestamp(),
-            true,
-        ),
-        EventIntervalRef::DateTime { start, end } => {
-            (start.unix_timestamp(), end.unix_timestamp(), false)
-        }
+    let (start, end, date_only, tzid) = interval_to_db(interval);
+    let row = sqlx::query!(
+        "INSERT INTO events (calendar_id, label, start_time, end_time, date_only, tzid) \
+        VALUES (?, ?, ?, ?, ?, ?) \
+        RETURNING id, calendar_id, label, start_time, end_time, date_only, tzid",
+        calendar_id,
+        label,
+        start,
+        end,
+        date_only,
+        tzid
+    )
+    .fetch_one(exec)
+    .await?;
+    Ok(Event::from_db(
+        row.id,
+        row.calendar_id,
+        row.label,
+        row.start_time,
+        row.end_time,
+        row.date_only,
+        row.tzid,
+    )?)
+}
+
+/// Import an iCalendar event from `calendar`, keeping its recurrence rule and exception dates.
+///
+/// See [`ical::event_interval`] for how times are read, and [`ical::exception_dates`] for how
+/// exception dates are stored. Events with more than one `RRULE` or
+/// with `RDATE`s are not supported.
+pub async fn import_event(
+    calendar_id: RowID,
+    calendar: &icalendar::Calendar<'_>,
+    event: &icalendar::Event<'_>,
+    exec: impl SqliteExecutor<'_>,
+) -> anyhow::Result<Event> {
+    let interval = ical::event_interval(calendar, event)?;
+    let (start, end, date_only, tzid) = interval_to_db(interval);
+    let label = match &event.summary {
+        Some(summary) => &summary.text,
+        None => &event.uid,
+    };
+    let rrule = match event.recurrence_rules.as_slice() {
+        [] => None,
+        [rule] => Some(rule.to_string()),
+        _ => bail!("event `{}` has more than one RRULE", event.uid),
+    };
+    if !event.recurrence_dates.is_empty() {
+        bail!("event `{}` has RDATEs, which are not supported", event.uid);
+    }
+    let exdate = match rrule {
+        Some(_) => ical::exception_dates(calendar, event)?
+            .iter()
+            .map(|date| date.to_string())
+            .reduce(|list, date| format!("{list},{date}")),
+        None => None,
     };
     let row = sqlx::query!(
-        "INSERT INTO events (calendar_id, label, start_time, end_time, date_only) \
-        VALUES (?, ?, ?, ?, ?) \
-        RETURNING id, calendar_id, label, start_time, end_time, date_only",
+        "INSERT INTO events \
+        (calendar_id, label, start_time, end_time, date_only, tzid, rrule, exdate) \
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?) \
+        RETURNING id, calendar_id, label, start_time, end_time, date_only, tzid",
         calendar_id,
         label,
         start,
         end,
-        date_only
+        date_only,
+        tzid,
+        rrule,
+        exdate
     )
     .fetch_one(exec)
     .await?;
@@ -176,9 +480,96 @@ pub async fn new_event(
         row.start_time,
         row.end_time,
         row.date_only,
+        row.tzid,
     )?)
 }
 
+/// Like [`get_events_in_range`] for all calendars, but with recurring events expanded into an
+/// event for each of their occurrences in `range`.
+pub async fn get_events_expanded(
+    range: (UtcDateTime, UtcDateTime),
+    exec: impl SqliteExecutor<'_>,
+) -> anyhow::Result<Vec<Event>> {
+    let (from, to) = range;
+    if to < from {
+        bail!("end of range {to} is before start {from}");
+    }
+    let from_ts = from.unix_timestamp();
+    let to_ts = to.unix_timestamp();
+    let rows = sqlx::query!(
+        "SELECT id, calendar_id, label, start_time, end_time, date_only, tzid, rrule, exdate \
+        FROM events WHERE start_time < ?1 \
+        AND (rrule IS NOT NULL OR end_time + IIF(date_only, 86400, 0) > ?2)",
+        to_ts,
+        from_ts
+    )
+    .fetch_all(exec)
+    .await?;
+    let mut events = vec![];
+    for row in rows {
+        let event = Event::from_db(
+            row.id,
+            row.calendar_id,
+            row.label,
+            row.start_time,
+            row.end_time,
+            row.date_only,
+            row.tzid,
+        )?;
+        let Some(rrule) = row.rrule else {
+            events.push(event);
+            continue;
+        };
+        let rule: Recur = rrule.parse()?;
+        let excluded = row
+            .exdate
+            .iter()
+            .flat_map(|list| list.split(','))
+            .map(|date| date.parse())
+            .collect::<anyhow::Result<Vec<DateOrDateTime>>>()?;
+        for interval in ical::expand(event.interval, &rule, &excluded, range)? {
+            events.push(Event {
+                id: event.id,
+                calendar_id: event.calendar_id,
+                label: event.label.clone(),
+                interval,
+            });
+        }
+    }
+    events.sort_by(|left, right| {
+        left.interval
+            .cmp(&right.interval)
+            .then(left.label.cmp(&right.label))
+    });
+    Ok(events)
+}
+
+/// Pairs of events, from any calendars, that overlap in `range`.
+///
+/// Recurring events are expanded as for [`get_events_expanded`], so two occurrences of
+/// different events can conflict. Each pair is in chronological order, and the pairs are
+/// ordered by their first event.
+pub async fn find_conflicts(
+    range: (UtcDateTime, UtcDateTime),
+    exec: impl SqliteExecutor<'_>,
+) -> anyhow::Result<Vec<(Event, Event)>> {
+    let events = get_events_expanded(range, exec).await?;
+    let mut conflicts = vec![];
+    for (idx, event) in events.iter().enumerate() {
+        let (_, end) = event.interval.exclusive_bounds();
+        // events are sorted by start, so only those starting before this one ends can overlap
+        for other in &events[idx + 1..] {
+            if other.interval.exclusive_bounds().0 >= end {
+                break;
+            }
+            if event.interval.overlaps(&other.interval) {
+                conflicts.push((event.clone(), other.clone()));
+            }
+        }
+    }
+    Ok(conflicts)
+}
+
 pub async fn google_token(
     exec: impl SqliteExecutor<'_>,
 ) -> anyhow::Result<Option<StandardTokenResponse<EmptyExtraTokenFields, BasicTokenType>>> {
@@ -206,6 +597,45 @@ pub async fn store_google_token(
     Ok(())
 }
 
+/// The `(year, iso_week)` the UI was last showing, if it has been saved
+pub async fn get_ui_state(exec: impl SqliteExecutor<'_>) -> anyhow::Result<Option<(i32, u8)>> {
+    let row = sqlx::query!("SELECT year, iso_week FROM ui_state WHERE id = 0")
+        .fetch_optional(exec)
+        .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    Ok(Some((row.year.try_into()?, row.iso_week.try_into()?)))
+}
+
+/// Save the week the UI is showing, see [`get_ui_state`]
+pub async fn set_ui_state(
+    year: i32,
+    iso_week: u8,
+    exec: impl SqliteExecutor<'_>,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        "INSERT INTO ui_state (id, year, iso_week) VALUES (0, ?, ?) \
+        ON CONFLICT (id) DO UPDATE SET year = excluded.year, iso_week = excluded.iso_week",
+        year,
+        iso_week
+    )
+    .execute(exec)
+    .await?;
+    Ok(())
+}
+
+/// Unix timestamps of the start and end, whether the interval is date-only, and its timezone
+fn interval_to_db(interval: EventInterval) -> (i64, i64, bool, Option<&'static str>) {
+    let (start, end, date_only) = interval.to_db_parts();
+    (
+        start,
+        end,
+        date_only,
+        interval.zone().map(|zone| zone.name()),
+    )
+}
+
 /// Assumes a `ESCAPE '\' as part of the LIKE clause`
 // TODO could return a Cow and be slightly more efficient, possibly
 fn escape_like(input: &str) -> Cow<'_, str> {
@@ -225,3 +655,334 @@ fn escape_like(input: &str) -> Cow<'_, str> {
     }));
     Cow::Owned(output)
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::Row;
+
+    use crate::data::{EventInterval, Zone};
+
+    #[tokio::test]
+    async fn search_calendars() {
+        let pool = super::test_pool().await;
+        let family = super::new_calendar("Family events", &pool).await.unwrap();
+        let events = super::new_calendar("Events", &pool).await.unwrap();
+        super::new_calendar("Work", &pool).await.unwrap();
+        super::new_calendar("100% done", &pool).await.unwrap();
+
+        let found = super::search_calendars("event", &pool).await.unwrap();
+        let ids: Vec<_> = found.iter().map(|calendar| calendar.id).collect();
+        assert_eq!(ids, [events.id, family.id]);
+        // ambiguous for the single-result lookup
+        assert!(super::find_calendar("event", &pool).await.is_err());
+        assert_eq!(
+            super::find_calendar("EVENTS", &pool).await.unwrap().id,
+            events.id
+        );
+
+        // wildcards are matched literally
+        assert!(
+            super::search_calendars("0%d", &pool)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            super::search_calendars("0% d", &pool).await.unwrap().len(),
+            1
+        );
+        assert!(
+            super::search_calendars("_", &pool)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn ui_state() {
+        let pool = super::test_pool().await;
+        assert_eq!(super::get_ui_state(&pool).await.unwrap(), None);
+        super::set_ui_state(2025, 27, &pool).await.unwrap();
+        assert_eq!(super::get_ui_state(&pool).await.unwrap(), Some((2025, 27)));
+        // replaced, not added to
+        super::set_ui_state(2026, 1, &pool).await.unwrap();
+        assert_eq!(super::get_ui_state(&pool).await.unwrap(), Some((2026, 1)));
+    }
+
+    #[tokio::test]
+    async fn find_events_by_label() {
+        let pool = super::test_pool().await;
+        let calendar = super::new_calendar("work", &pool).await.unwrap();
+        let day = time::Date::from_calendar_date(2025, time::Month::July, 3).unwrap();
+        for (label, day) in [
+            ("Team standup", day.next_day().unwrap()),
+            ("Lunch", day),
+            ("Standup_notes 100%", day),
+        ] {
+            let interval = EventInterval::new_date(day, day).unwrap();
+            super::new_event(calendar.id, label, interval, &pool)
+                .await
+                .unwrap();
+        }
+
+        let found = super::find_events_by_label("STANDUP", &pool).await.unwrap();
+        let labels: Vec<_> = found.iter().map(|event| event.label.as_str()).collect();
+        assert_eq!(labels, ["Standup_notes 100%", "Team standup"]);
+
+        // wildcards are matched literally
+        let found = super::find_events_by_label("p_n", &pool).await.unwrap();
+        assert_eq!(found.len(), 1);
+        let found = super::find_events_by_label("%", &pool).await.unwrap();
+        assert_eq!(found.len(), 1);
+        let found = super::find_events_by_label("a_", &pool).await.unwrap();
+        assert!(found.is_empty());
+    }
+
+    #[tokio::test]
+    async fn events_for_calendars() {
+        let pool = super::test_pool().await;
+        let work = super::new_calendar("work", &pool).await.unwrap();
+        let home = super::new_calendar("home", &pool).await.unwrap();
+        let other = super::new_calendar("other", &pool).await.unwrap();
+        let day = time::Date::from_calendar_date(2025, time::Month::July, 3).unwrap();
+        let at = |hour| day.with_hms(hour, 0, 0).unwrap().as_utc();
+        for (calendar, label, interval) in [
+            (&work, "Standup", EventInterval::new_datetime(at(9), at(10))),
+            (&home, "Dinner", EventInterval::new_datetime(at(18), at(19))),
+            (&work, "Holiday", EventInterval::new_date(day, day)),
+            (
+                &home,
+                "Breakfast",
+                EventInterval::new_datetime(at(0), at(1)),
+            ),
+            (&home, "Errands", EventInterval::new_datetime(at(9), at(10))),
+            (&other, "Elsewhere", EventInterval::new_date(day, day)),
+            // at the same time, so ordered by timezone before label
+            (
+                &work,
+                "Abroad",
+                EventInterval::new_zoned(at(9), at(10), Zone::new("Europe/Berlin").unwrap()),
+            ),
+            (
+                &home,
+                "Zoom",
+                EventInterval::new_zoned(at(9), at(10), Zone::new("America/New_York").unwrap()),
+            ),
+        ] {
+            super::new_event(calendar.id, label, interval.unwrap(), &pool)
+                .await
+                .unwrap();
+        }
+
+        let ids = [work.id, home.id];
+        let mut expected = vec![];
+        for id in ids {
+            expected.extend(super::get_events_for_calendar(&pool, id).await.unwrap());
+        }
+        expected.sort_by(|left, right| {
+            left.interval
+                .cmp(&right.interval)
+                .then(left.label.cmp(&right.label))
+        });
+        let labels = |events: &[super::Event]| -> Vec<String> {
+            events.iter().map(|event| event.label.clone()).collect()
+        };
+
+        let events = super::get_events_for_calendars(&pool, &ids).await.unwrap();
+        assert_eq!(labels(&events), labels(&expected));
+        assert_eq!(
+            labels(&events),
+            [
+                "Holiday",
+                "Breakfast",
+                "Errands",
+                "Standup",
+                "Zoom",
+                "Abroad",
+                "Dinner"
+            ]
+        );
+        assert!(
+            super::get_events_for_calendars(&pool, &[])
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn events_for_calendar_in_range() {
+        let pool = super::test_pool().await;
+        let work = super::new_calendar("work", &pool).await.unwrap();
+        let home = super::new_calendar("home", &pool).await.unwrap();
+        let day = time::Date::from_calendar_date(2025, time::Month::July, 3).unwrap();
+        let at = |hour| day.with_hms(hour, 0, 0).unwrap().as_utc();
+        for (calendar, label, start, end) in [
+            (&work, "Before", 6, 8),
+            (&work, "Ends at start", 7, 9),
+            (&work, "Overlaps", 8, 10),
+            (&work, "Inside", 10, 11),
+            (&work, "Starts at end", 12, 13),
+            (&home, "Other calendar", 10, 11),
+        ] {
+            let interval = EventInterval::new_datetime(at(start), at(end)).unwrap();
+            super::new_event(calendar.id, label, interval, &pool)
+                .await
+                .unwrap();
+        }
+        // lasts all day, so overlaps even though its end date is stored as midnight
+        let holiday = EventInterval::new_date(day, day).unwrap();
+        super::new_event(work.id, "Holiday", holiday, &pool)
+            .await
+            .unwrap();
+
+        let found = super::get_events_for_calendar_in_range(work.id, at(9), at(12), &pool)
+            .await
+            .unwrap();
+        let labels: Vec<_> = found.iter().map(|event| event.label.as_str()).collect();
+        // bounds are exclusive, so events touching the range aren't in it
+        assert_eq!(labels, ["Holiday", "Overlaps", "Inside"]);
+        assert!(
+            super::get_events_for_calendar_in_range(work.id, at(12), at(9), &pool)
+                .await
+                .is_err()
+        );
+
+        let plan = sqlx::query(
+            "EXPLAIN QUERY PLAN SELECT id FROM events \
+            WHERE calendar_id = ?1 AND start_time < ?2 \
+            AND end_time + IIF(date_only, 86400, 0) > ?3",
+        )
+        .bind(work.id)
+        .bind(0)
+        .bind(0)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let detail: String = plan[0].get("detail");
+        assert!(
+            detail.contains("USING INDEX events_calendar_start"),
+            "{detail}"
+        );
+    }
+
+    #[tokio::test]
+    async fn find_conflicts() {
+        let pool = super::test_pool().await;
+        let work = super::new_calendar("work", &pool).await.unwrap();
+        let home = super::new_calendar("home", &pool).await.unwrap();
+        let day = time::Date::from_calendar_date(2025, time::Month::July, 3).unwrap();
+        let at = |hour| day.with_hms(hour, 0, 0).unwrap().as_utc();
+        for (calendar, label, start, end) in [
+            (&work, "Standup", 9, 10),
+            (&home, "Dentist", 9, 11),
+            // touching isn't overlapping
+            (&work, "Lunch", 11, 12),
+        ] {
+            let interval = EventInterval::new_datetime(at(start), at(end)).unwrap();
+            super::new_event(calendar.id, label, interval, &pool)
+                .await
+                .unwrap();
+        }
+
+        let conflicts = super::find_conflicts((at(0), at(23)), &pool).await.unwrap();
+        let labels: Vec<_> = conflicts
+            .iter()
+            .map(|(first, second)| (first.label.as_str(), second.label.as_str()))
+            .collect();
+        assert_eq!(labels, [("Standup", "Dentist")]);
+
+        // outside the range
+        let conflicts = super::find_conflicts((at(12), at(23)), &pool)
+            .await
+            .unwrap();
+        assert!(conflicts.is_empty());
+        // the standup ends as the range starts
+        let conflicts = super::find_conflicts((at(10), at(23)), &pool)
+            .await
+            .unwrap();
+        assert!(conflicts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn zoned_event_round_trip() {
+        let pool = super::test_pool().await;
+        let calendar = super::new_calendar("work", &pool).await.unwrap();
+        let berlin = Zone::new("europe/berlin").unwrap();
+        assert_eq!(berlin.name(), "Europe/Berlin");
+        // 9am in Berlin, which is on summer time
+        let day = time::Date::from_calendar_date(2025, time::Month::July, 3).unwrap();
+        let start = day.with_hms(7, 0, 0).unwrap().as_utc();
+        let end = day.with_hms(8, 0, 0).unwrap().as_utc();
+        let interval = EventInterval::new_zoned(start, end, berlin).unwrap();
+        let event = super::new_event(calendar.id, "Standup", interval, &pool)
+            .await
+            .unwrap();
+        assert_eq!(event.interval, interval);
+
+        let fetched = super::get_events_for_calendar(&pool, calendar.id)
+            .await
+            .unwrap();
+        assert_eq!(fetched[0].interval, interval);
+        assert_eq!(fetched[0].interval.zone(), Some(berlin));
+        assert_eq!(berlin.to_local(start).hour(), 9);
+        assert_eq!(
+            fetched[0].interval.to_string(),
+            "2025-07-03 9:00:00.0 +02:00:00 - 2025-07-03 10:00:00.0 +02:00:00 (Europe/Berlin)"
+        );
+
+        // still ordered by the UTC instant
+        let later = EventInterval::new_datetime(start + time::Duration::MINUTE, end).unwrap();
+        assert_eq!(interval.cmp(&later), std::cmp::Ordering::Less);
+        assert!(Zone::new("Europe/Nowhere").is_err());
+    }
+
+    #[tokio::test]
+    async fn create_and_fetch_calendar() {
+        let pool = super::test_pool().await;
+        let calendar = super::new_calendar("work", &pool).await.unwrap();
+
+        let fetched = super::get_calendar(calendar.id, &pool)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fetched.name, "work");
+        let found = super::find_calendar("WORK", &pool).await.unwrap();
+        assert_eq!(found.id, calendar.id);
+        assert_eq!(super::get_calendars(&pool).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn calendar_color() {
+        let pool = super::test_pool().await;
+        let calendar = super::new_calendar("work", &pool).await.unwrap();
+        assert_eq!(calendar.color, None);
+
+        super::set_calendar_color(calendar.id, Some("#4e79a7"), &pool)
+            .await
+            .unwrap();
+        let fetched = super::get_calendar(calendar.id, &pool)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fetched.color.as_deref(), Some("#4e79a7"));
+        assert_eq!(fetched.rgb(), [0x4e, 0x79, 0xa7]);
+
+        assert!(
+            super::set_calendar_color(calendar.id, Some("blue"), &pool)
+                .await
+                .is_err()
+        );
+        assert!(
+            super::set_calendar_color(calendar.id + 1, None, &pool)
+                .await
+                .is_err()
+        );
+        super::set_calendar_color(calendar.id, None, &pool)
+            .await
+            .unwrap();
+        let calendars = super::get_calendars(&pool).await.unwrap();
+        assert_eq!(calendars[0].color, None);
+    }
+}
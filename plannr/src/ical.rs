@@ -0,0 +1,421 @@
+//! Conversion between [`icalendar`] types and plannr's own
+
+use anyhow::{Context, bail};
+use icalendar::{
+    EventEnd,
+    types::{self, DateOrDateTime, Duration, DurationKind, Recur, Time},
+};
+use time::{Date, Month, PrimitiveDateTime, UtcDateTime};
+
+use crate::data::{EventInterval, EventIntervalRef, Zone};
+
+/// The interval of an iCalendar event in `calendar`.
+///
+/// Times are converted to UTC by [`Calendar::utc_interval`](icalendar::Calendar::utc_interval),
+/// so `TZID`s are resolved using the calendar's timezones or the bundled database. Events
+/// starting at a local time in a timezone from the database are zoned. The end is
+/// [`Event::effective_end`](icalendar::Event::effective_end), so days of a `DURATION` are added
+/// in local time. The end of date-only intervals is inclusive, so the (exclusive) end of
+/// all-day events is moved back a day. A `DURATION` must be a whole number of days for
+/// date-only events. Events without an end take no time, or the day they start on for
+/// date-only events.
+pub fn event_interval(
+    calendar: &icalendar::Calendar<'_>,
+    event: &icalendar::Event<'_>,
+) -> anyhow::Result<EventInterval> {
+    let Some(start) = &event.start else {
+        bail!("event `{}` has no start", event.uid);
+    };
+    let interval = match start {
+        DateOrDateTime::Date(start) => {
+            let start = to_date(*start)?;
+            let end = match event.effective_end(&calendar.timezones) {
+                Some(DateOrDateTime::Date(end)) => to_date(end)?.previous_day().unwrap_or(start),
+                Some(DateOrDateTime::DateTime(_)) => match &event.end {
+                    Some(EventEnd::Duration(duration)) => bail!(
+                        "event `{}` starts on a date but lasts {duration}, which is not a whole \
+                        number of days",
+                        event.uid
+                    ),
+                    _ => bail!("event `{}` starts on a date but ends at a time", event.uid),
+                },
+                Some(DateOrDateTime::Raw(end)) => bail!(non_gregorian(event, &end)),
+                None => bail!("end of event `{}` is out of range", event.uid),
+            };
+            EventInterval::new_date(start, end.max(start))?
+        }
+        DateOrDateTime::Raw(start) => bail!(non_gregorian(event, start)),
+        DateOrDateTime::DateTime(local_start) => {
+            if let Some(EventEnd::DateTime {
+                value: DateOrDateTime::Date(_),
+                ..
+            }) = &event.end
+            {
+                bail!("event `{}` starts at a time but ends on a date", event.uid);
+            }
+            let Some((start, end)) = calendar.utc_interval(event)? else {
+                bail!("end of event `{}` is out of range", event.uid);
+            };
+            let (start, end) = (to_utc(start)?, to_utc(end)?);
+            let zone = event
+                .start_timezone_id
+                .as_ref()
+                .filter(|_| !local_start.time.utc)
+                .and_then(|id| Zone::new(id.id()).ok());
+            match zone {
+                Some(zone) => EventInterval::new_zoned(start, end, zone)?,
+                None => EventInterval::new_datetime(start, end)?,
+            }
+        }
+    };
+    Ok(interval)
+}
+
+fn non_gregorian(event: &icalendar::Event<'_>, value: &str) -> String {
+    format!(
+        "event `{}` is at `{value}`, which isn't a Gregorian date",
+        event.uid
+    )
+}
+
+/// The exception dates (`EXDATE`) of `event` in `calendar`, with times converted to UTC as for
+/// [`event_interval`].
+pub fn exception_dates(
+    calendar: &icalendar::Calendar<'_>,
+    event: &icalendar::Event<'_>,
+) -> anyhow::Result<Vec<DateOrDateTime>> {
+    let mut output = vec![];
+    for dates in &event.exception_dates {
+        for date in &dates.values {
+            output.push(match date {
+                DateOrDateTime::Date(_) => date.clone(),
+                _ => DateOrDateTime::DateTime(
+                    calendar.to_utc(date.clone(), dates.timezone_id.as_ref())?,
+                ),
+            });
+        }
+    }
+    Ok(output)
+}
+
+/// The intervals of the occurrences of `rule` for an event lasting `interval` that overlap
+/// `range` (start before `range.1` and end after `range.0`, with date-only occurrences lasting
+/// all of their end date), minus `excluded`. Excluded times are UTC, see [`exception_dates`].
+///
+/// Zoned intervals recur at the same local time in their zone, so occurrences after a DST
+/// change are at a different UTC time.
+pub fn expand(
+    interval: EventInterval,
+    rule: &Recur,
+    excluded: &[DateOrDateTime],
+    range: (UtcDateTime, UtcDateTime),
+) -> anyhow::Result<Vec<EventInterval>> {
+    let excluded = excluded
+        .iter()
+        .map(|date| match date {
+            DateOrDateTime::Date(date) => Ok(to_date(*date)?.midnight().as_utc()),
+            DateOrDateTime::DateTime(date_time) => to_utc(*date_time),
+            DateOrDateTime::Raw(value) => bail!("`{value}` isn't a Gregorian date"),
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let mut output = vec![];
+    match *interval {
+        EventIntervalRef::Date { start, end } => {
+            let length = end - start;
+            let first = DateOrDateTime::Date(from_date(start)?);
+            let last = DateOrDateTime::Date(from_date(range.1.date())?);
+            for occurrence in rule.occurrences_until(first, last) {
+                let DateOrDateTime::Date(occurrence) = occurrence else {
+                    unreachable!("occurrences have the same form as the start");
+                };
+                let start = to_date(occurrence)?;
+                if start.midnight().as_utc() >= range.1 {
+                    break;
+                }
+                let end = start + length;
+                if end
+                    .next_day()
+                    .is_some_and(|end| end.midnight().as_utc() <= range.0)
+                    || excluded.contains(&start.midnight().as_utc())
+                {
+                    continue;
+                }
+                output.push(EventInterval::new_date(start, end)?);
+            }
+        }
+        EventIntervalRef::DateTime { start, end }
+        | EventIntervalRef::ZonedDateTime { start, end, .. } => {
+            // zoned events recur at the same local time, so move across DST changes
+            let zone = interval.zone();
+            let length = end - start;
+            let first = match zone {
+                Some(zone) => {
+                    let local = zone.to_local(start);
+                    from_local(PrimitiveDateTime::new(local.date(), local.time()))?
+                }
+                None => from_utc(start)?,
+            };
+            let to_instant = move |occurrence| -> anyhow::Result<UtcDateTime> {
+                Ok(match zone {
+                    Some(zone) => zone.from_local(to_primitive(occurrence)?)?,
+                    None => to_utc(occurrence)?,
+                })
+            };
+            // `UNTIL` is in UTC for zoned events
+            let last = DateOrDateTime::DateTime(from_utc(range.1)?);
+            let occurrences = rule
+                .occurrences_until(DateOrDateTime::DateTime(first), last)
+                .with_to_utc(move |local| {
+                    let utc = to_instant(local).and_then(from_utc);
+                    utc.unwrap_or(local)
+                });
+            for occurrence in occurrences {
+                let DateOrDateTime::DateTime(occurrence) = occurrence else {
+                    unreachable!("occurrences have the same form as the start");
+                };
+                let start = to_instant(occurrence)?;
+                if start >= range.1 {
+                    break;
+                }
+                let end = start + length;
+                if end <= range.0 || excluded.contains(&start) {
+                    continue;
+                }
+                output.push(match zone {
+                    Some(zone) => EventInterval::new_zoned(start, end, zone)?,
+                    None => EventInterval::new_datetime(start, end)?,
+                });
+            }
+        }
+    }
+    Ok(output)
+}
+
+fn to_date(date: types::Date) -> anyhow::Result<Date> {
+    Ok(Date::from_calendar_date(
+        date.full_year.into(),
+        Month::try_from(date.month)?,
+        date.day,
+    )?)
+}
+
+/// The date and time of `date_time`, ignoring whether it is UTC
+fn to_primitive(date_time: types::DateTime) -> anyhow::Result<PrimitiveDateTime> {
+    let time = date_time.time;
+    Ok(to_date(date_time.date)?.with_hms(time.hour, time.minute, time.second)?)
+}
+
+fn to_utc(date_time: types::DateTime) -> anyhow::Result<UtcDateTime> {
+    Ok(to_primitive(date_time)?.as_utc())
+}
+
+fn from_date(date: Date) -> anyhow::Result<types::Date> {
+    Ok(types::Date {
+        full_year: u16::try_from(date.year()).context("years before 0 are not supported")?,
+        month: date.month().into(),
+        day: date.day(),
+    })
+}
+
+fn from_utc(date_time: UtcDateTime) -> anyhow::Result<types::DateTime> {
+    let local = from_local(PrimitiveDateTime::new(date_time.date(), date_time.time()))?;
+    Ok(types::DateTime {
+        time: Time {
+            utc: true,
+            ..local.time
+        },
+        ..local
+    })
+}
+
+/// A local (floating) date and time
+fn from_local(date_time: PrimitiveDateTime) -> anyhow::Result<types::DateTime> {
+    Ok(types::DateTime {
+        date: from_date(date_time.date())?,
+        time: Time {
+            hour: date_time.hour(),
+            minute: date_time.minute(),
+            second: date_time.second(),
+            utc: false,
+        },
+    })
+}
+
+/// Convert an iCalendar duration to a [`time::Duration`], which is signed so keeps the sign.
+///
+/// Weeks and days are always 7 and 1 exact days: DST changes are not taken into account.
+pub fn to_duration(duration: &Duration) -> time::Duration {
+    let magnitude = match duration.kind {
+        DurationKind::Weeks(weeks) => time::Duration::weeks(weeks.into()),
+        DurationKind::DateTime {
+            days,
+            hours,
+            minutes,
+            seconds,
+        } => {
+            time::Duration::days(days.into())
+                + time::Duration::hours(hours.into())
+                + time::Duration::minutes(minutes.into())
+                + time::Duration::seconds(seconds.into())
+        }
+    };
+    if duration.negative {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Convert an iCalendar duration to a [`std::time::Duration`], failing if it is negative.
+pub fn to_std_duration(duration: &Duration) -> anyhow::Result<std::time::Duration> {
+    if duration.negative {
+        bail!("negative duration {duration} cannot be a `std::time::Duration`");
+    }
+    Ok(to_duration(duration).try_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use icalendar::{
+        test_util::calendar,
+        types::{Duration, Recur},
+    };
+    use time::{Date, Month};
+
+    use crate::data::{EventInterval, EventIntervalRef, Zone};
+
+    /// The interval of an event starting at `DTSTART{start}` lasting `duration`
+    fn duration_interval(start: &str, duration: &str) -> anyhow::Result<EventInterval> {
+        let input = calendar(&format!(
+            "BEGIN:VEVENT\r\n\
+            UID:event@example.com\r\n\
+            DTSTART{start}\r\n\
+            DURATION:{duration}\r\n\
+            END:VEVENT\r\n"
+        ));
+        let calendar = icalendar::parse(&input).unwrap().remove(0);
+        super::event_interval(&calendar, &calendar.events[0])
+    }
+
+    #[test]
+    fn duration_end() {
+        let day = Date::from_calendar_date(2025, Month::July, 3).unwrap();
+        let interval = duration_interval(":20250703T090000Z", "PT1H30M").unwrap();
+        assert_eq!(
+            *interval,
+            EventIntervalRef::DateTime {
+                start: day.with_hms(9, 0, 0).unwrap().as_utc(),
+                end: day.with_hms(10, 30, 0).unwrap().as_utc(),
+            }
+        );
+
+        let interval = duration_interval(";VALUE=DATE:20250703", "P2D").unwrap();
+        assert_eq!(
+            *interval,
+            EventIntervalRef::Date {
+                start: day,
+                end: day.next_day().unwrap(),
+            }
+        );
+
+        let error = duration_interval(";VALUE=DATE:20250703", "PT12H").unwrap_err();
+        assert!(error.to_string().contains("not a whole number of days"));
+
+        // the clocks go back in Berlin on 2025-10-26, so this day is 25 hours long
+        let interval = duration_interval(";TZID=Europe/Berlin:20251025T090000", "P1D").unwrap();
+        let (start, end) = match *interval {
+            EventIntervalRef::ZonedDateTime { start, end, .. } => (start, end),
+            other => panic!("expected a zoned interval, found {other:?}"),
+        };
+        assert_eq!(end - start, time::Duration::hours(25));
+    }
+
+    #[test]
+    fn zoned() {
+        let input = calendar(
+            "BEGIN:VEVENT\r\n\
+            UID:event@example.com\r\n\
+            DTSTART;TZID=Europe/Berlin:20250703T090000\r\n\
+            DTEND;TZID=Europe/Berlin:20250703T100000\r\n\
+            RRULE:FREQ=WEEKLY\r\n\
+            EXDATE;TZID=Europe/Berlin:20250710T090000\r\n\
+            END:VEVENT\r\n",
+        );
+        let calendar = icalendar::parse(&input).unwrap().remove(0);
+        let event = &calendar.events[0];
+        let day = Date::from_calendar_date(2025, Month::July, 3).unwrap();
+        // Berlin is on summer time, 2 hours ahead of UTC
+        let interval = super::event_interval(&calendar, event).unwrap();
+        assert_eq!(
+            *interval,
+            EventIntervalRef::ZonedDateTime {
+                start: day.with_hms(7, 0, 0).unwrap().as_utc(),
+                end: day.with_hms(8, 0, 0).unwrap().as_utc(),
+                zone: Zone::new("Europe/Berlin").unwrap(),
+            }
+        );
+        let excluded = super::exception_dates(&calendar, event).unwrap();
+        assert_eq!(excluded, ["20250710T070000Z".parse().unwrap()]);
+    }
+
+    #[test]
+    fn expand_range_bounds() {
+        let day = Date::from_calendar_date(2025, Month::July, 3).unwrap();
+        let at = |hour| day.with_hms(hour, 0, 0).unwrap().as_utc();
+        let daily: Recur = "FREQ=DAILY".parse().unwrap();
+
+        // ends as the range starts
+        let interval = EventInterval::new_datetime(at(8), at(9)).unwrap();
+        let expanded = super::expand(interval, &daily, &[], (at(9), at(12))).unwrap();
+        assert!(expanded.is_empty());
+
+        // lasts all day, so overlaps a range later on that day
+        let interval = EventInterval::new_date(day, day).unwrap();
+        let expanded = super::expand(interval, &daily, &[], (at(9), at(12))).unwrap();
+        assert_eq!(expanded, [interval]);
+    }
+
+    #[test]
+    fn expand_across_dst() {
+        let zone = Zone::new("Europe/Berlin").unwrap();
+        // 09:00 in Berlin on summer time, then the clocks go back on 2025-10-26
+        let october = |day, hour| {
+            Date::from_calendar_date(2025, Month::October, day)
+                .unwrap()
+                .with_hms(hour, 0, 0)
+                .unwrap()
+                .as_utc()
+        };
+        let interval = EventInterval::new_zoned(october(20, 7), october(20, 8), zone).unwrap();
+        let weekly: Recur = "FREQ=WEEKLY;COUNT=2".parse().unwrap();
+        let expanded =
+            super::expand(interval, &weekly, &[], (october(1, 0), october(31, 0))).unwrap();
+        let expected = [
+            interval,
+            EventInterval::new_zoned(october(27, 8), october(27, 9), zone).unwrap(),
+        ];
+        assert_eq!(expanded, expected);
+
+        // `UNTIL` is compared in UTC: 09:00 in Berlin on the 27th is 08:00 UTC
+        let until: Recur = "FREQ=WEEKLY;UNTIL=20251027T080000Z".parse().unwrap();
+        let expanded =
+            super::expand(interval, &until, &[], (october(1, 0), october(31, 0))).unwrap();
+        assert_eq!(expanded, expected);
+    }
+
+    #[test]
+    fn durations() {
+        let duration: Duration = "-P1DT2H".parse().unwrap();
+        let converted = super::to_duration(&duration);
+        assert!(converted.is_negative());
+        assert_eq!(converted, -time::Duration::hours(26));
+        assert!(super::to_std_duration(&duration).is_err());
+
+        let duration: Duration = "P2W".parse().unwrap();
+        assert_eq!(super::to_duration(&duration), time::Duration::days(14));
+        assert_eq!(
+            super::to_std_duration(&duration).unwrap(),
+            std::time::Duration::from_secs(14 * 86_400)
+        );
+    }
+}
@@ -3,6 +3,7 @@ use anyhow::Context;
 pub mod data;
 pub mod db;
 pub mod google_creds;
+pub mod ical;
 
 /// Like `std::env::var` but reports var name in error
 pub fn env_var(name: &str) -> anyhow::Result<String> {
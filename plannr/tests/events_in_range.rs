@@ -0,0 +1,99 @@
+use plannr::{data::EventInterval, db};
+use time::{Date, Month, UtcDateTime};
+
+fn at(day: u8, hour: u8) -> UtcDateTime {
+    Date::from_calendar_date(2025, Month::July, day)
+        .unwrap()
+        .with_hms(hour, 0, 0)
+        .unwrap()
+        .as_utc()
+}
+
+fn date(day: u8) -> Date {
+    Date::from_calendar_date(2025, Month::July, day).unwrap()
+}
+
+#[tokio::test]
+async fn ordered_across_two_days() {
+    let pool = db::test_pool().await;
+    let calendar = db::new_calendar("agenda", &pool).await.unwrap();
+
+    // inserted out of order
+    let events = [
+        (
+            "day 2 morning",
+            EventInterval::new_datetime(at(4, 9), at(4, 10)),
+        ),
+        (
+            "day 1 afternoon",
+            EventInterval::new_datetime(at(3, 14), at(3, 15)),
+        ),
+        ("day 2 all day", EventInterval::new_date(date(4), date(4))),
+        (
+            "day 1 morning",
+            EventInterval::new_datetime(at(3, 9), at(3, 10)),
+        ),
+        ("before", EventInterval::new_datetime(at(2, 9), at(2, 10))),
+        ("after", EventInterval::new_datetime(at(5, 9), at(5, 10))),
+    ];
+    for (label, interval) in events {
+        db::new_event(calendar.id, label, interval.unwrap(), &pool)
+            .await
+            .unwrap();
+    }
+
+    let events = db::get_events_in_range(None, at(3, 0), at(5, 0), &pool)
+        .await
+        .unwrap();
+    let labels: Vec<_> = events.iter().map(|event| event.label.as_str()).collect();
+    assert_eq!(
+        labels,
+        [
+            "day 1 morning",
+            "day 1 afternoon",
+            "day 2 all day",
+            "day 2 morning"
+        ]
+    );
+
+    let events = db::get_events_in_range(Some(calendar.id + 1), at(3, 0), at(5, 0), &pool)
+        .await
+        .unwrap();
+    assert!(events.is_empty());
+}
+
+#[tokio::test]
+async fn exclusive_bounds() {
+    let pool = db::test_pool().await;
+    let calendar = db::new_calendar("agenda", &pool).await.unwrap();
+    let events = [
+        ("all day", EventInterval::new_date(date(3), date(3))),
+        (
+            "ends at start",
+            EventInterval::new_datetime(at(3, 8), at(3, 9)),
+        ),
+        (
+            "starts at end",
+            EventInterval::new_datetime(at(3, 12), at(3, 13)),
+        ),
+        ("inside", EventInterval::new_datetime(at(3, 10), at(3, 11))),
+    ];
+    for (label, interval) in events {
+        db::new_event(calendar.id, label, interval.unwrap(), &pool)
+            .await
+            .unwrap();
+    }
+
+    // the all-day event lasts until the end of its end date
+    let events = db::get_events_in_range(None, at(3, 9), at(3, 12), &pool)
+        .await
+        .unwrap();
+    let labels: Vec<_> = events.iter().map(|event| event.label.as_str()).collect();
+    assert_eq!(labels, ["all day", "inside"]);
+
+    let events = db::get_events_expanded((at(3, 9), at(3, 12)), &pool)
+        .await
+        .unwrap();
+    let labels: Vec<_> = events.iter().map(|event| event.label.as_str()).collect();
+    assert_eq!(labels, ["all day", "inside"]);
+}
@@ -0,0 +1,77 @@
+use plannr::{data::EventIntervalRef, db};
+use time::{Date, Month, UtcDateTime};
+
+const WEEKLY: &str = "BEGIN:VCALENDAR\r\n\
+    PRODID:-//Example Corp.//CalDAV Client//EN\r\n\
+    VERSION:2.0\r\n\
+    BEGIN:VEVENT\r\n\
+    UID:weekly@example.com\r\n\
+    DTSTAMP:20250101T000000Z\r\n\
+    SUMMARY:Weekly meeting\r\n\
+    DTSTART:20250701T090000Z\r\n\
+    DTEND:20250701T100000Z\r\n\
+    RRULE:FREQ=WEEKLY\r\n\
+    EXDATE:20250715T090000Z\r\n\
+    END:VEVENT\r\n\
+    BEGIN:VEVENT\r\n\
+    UID:single@example.com\r\n\
+    DTSTAMP:20250101T000000Z\r\n\
+    SUMMARY:Single\r\n\
+    DTSTART;VALUE=DATE:20250710\r\n\
+    DTEND;VALUE=DATE:20250711\r\n\
+    END:VEVENT\r\n\
+    END:VCALENDAR\r\n";
+
+fn at(month: Month, day: u8, hour: u8) -> UtcDateTime {
+    Date::from_calendar_date(2025, month, day)
+        .unwrap()
+        .with_hms(hour, 0, 0)
+        .unwrap()
+        .as_utc()
+}
+
+#[tokio::test]
+async fn expand_weekly_event() {
+    let pool = db::test_pool().await;
+    let calendar = db::new_calendar("imported", &pool).await.unwrap();
+    let ical = icalendar::parse(WEEKLY).unwrap().remove(0);
+    for event in &ical.events {
+        db::import_event(calendar.id, &ical, event, &pool)
+            .await
+            .unwrap();
+    }
+
+    let events = db::get_events_expanded((at(Month::July, 1, 0), at(Month::August, 1, 0)), &pool)
+        .await
+        .unwrap();
+    let summary: Vec<_> = events
+        .iter()
+        .map(|event| (event.label.as_str(), *event.interval))
+        .collect();
+    let weekly = |day| {
+        (
+            "Weekly meeting",
+            EventIntervalRef::DateTime {
+                start: at(Month::July, day, 9),
+                end: at(Month::July, day, 10),
+            },
+        )
+    };
+    let single_day = Date::from_calendar_date(2025, Month::July, 10).unwrap();
+    assert_eq!(
+        summary,
+        [
+            weekly(1),
+            weekly(8),
+            (
+                "Single",
+                EventIntervalRef::Date {
+                    start: single_day,
+                    end: single_day
+                }
+            ),
+            weekly(22),
+            weekly(29),
+        ]
+    );
+}